@@ -11,13 +11,14 @@ use winit::{
 };
 use winit_input_helper::WinitInputHelper;
 
-use crate::engine::{signal, InSignal, Items, SystemPipeline};
+use crate::engine::{signal, GamepadInput, InSignal, Items, SystemPipeline};
 
 /// The main engine struct that create the window and runs the system pipeline.
 pub struct Engine<T: SystemPipeline> {
     rx: Option<mpsc::Receiver<InSignal<T>>>,
     tx: Option<mpsc::Sender<T::OutSignal>>,
     queued_signals: VecDeque<T::InSignal>,
+    is_batching_signals: bool,
     state: EngineState<T>,
 }
 
@@ -34,6 +35,7 @@ impl<T: SystemPipeline> Engine<T> {
             rx: None,
             tx: None,
             queued_signals: VecDeque::new(),
+            is_batching_signals: false,
             state,
         }
     }
@@ -49,10 +51,30 @@ impl<T: SystemPipeline> Engine<T> {
         self.tx = Some(tx);
         self
     }
+
+    /// Set whether to pass all the signals of a frame together to
+    /// [`SystemPipeline::in_signals`], instead of each to
+    /// [`SystemPipeline::in_signal`].
+    pub fn with_batching_signals(mut self, is_batching_signals: bool) -> Self {
+        self.is_batching_signals = is_batching_signals;
+        self
+    }
 }
 
 impl<T: SystemPipeline> ApplicationHandler for Engine<T> {
     fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+        // Resumed after suspended
+        if let EngineState::PostInit {
+            items,
+            system_pipeline,
+        } = &mut self.state
+        {
+            log::info!("Engine resuming");
+            system_pipeline.on_resume(items.window.clone());
+            items.window.request_redraw();
+            return;
+        }
+
         // Already initialized
         if !matches!(&self.state, EngineState::PreInit { .. }) {
             log::warn!("Engine already initializing or initialized");
@@ -66,6 +88,7 @@ impl<T: SystemPipeline> ApplicationHandler for Engine<T> {
         } = self.state.initialize_engine();
 
         // Set up window and control flow
+        let window_attributes = T::window_attributes(&system_pipeline_args, window_attributes);
         let window = Arc::new(event_loop.create_window(window_attributes).unwrap());
 
         #[cfg(target_arch = "wasm32")]
@@ -104,11 +127,34 @@ impl<T: SystemPipeline> ApplicationHandler for Engine<T> {
         log::info!("System pipeline initializing asynchronously");
     }
 
+    fn suspended(&mut self, _event_loop: &ActiveEventLoop) {
+        if let EngineState::PostInit {
+            system_pipeline, ..
+        } = &mut self.state
+        {
+            log::info!("Engine suspending");
+            system_pipeline.on_suspend();
+        }
+    }
+
     fn window_event(&mut self, event_loop: &ActiveEventLoop, _: WindowId, event: WindowEvent) {
-        // Shut down if the window is closed
+        // Shut down if the window is closed, unless the system pipeline vetoes
         if let WindowEvent::CloseRequested = event {
-            log::info!("Engine exiting");
-            event_loop.exit();
+            let is_closing = match &mut self.state {
+                EngineState::PostInit {
+                    items,
+                    system_pipeline,
+                } => system_pipeline.on_close_requested(items),
+                _ => true,
+            };
+
+            match is_closing {
+                true => {
+                    log::info!("Engine exiting");
+                    event_loop.exit();
+                }
+                false => log::info!("Engine close request vetoed"),
+            }
         }
 
         match &mut self.state {
@@ -117,18 +163,36 @@ impl<T: SystemPipeline> ApplicationHandler for Engine<T> {
 
                 // Wait for the system pipeline to initialize
                 if let WindowEvent::RedrawRequested = event {
-                    if let Ok((window, system_pipeline)) = init_rx.try_recv() {
-                        window.request_redraw();
-
-                        self.state = EngineState::PostInit {
-                            items: Items::<T::OutSignal> {
-                                window,
-                                input: std::mem::take(input),
-                                tx: self.tx.clone(),
-                            },
-                            system_pipeline,
-                        };
-                        log::info!("Engine initialized")
+                    match init_rx.try_recv() {
+                        Ok((window, Ok(system_pipeline))) => {
+                            window.request_redraw();
+
+                            self.state = EngineState::PostInit {
+                                items: Items::<T::OutSignal> {
+                                    window,
+                                    input: std::mem::take(input),
+                                    gamepad: GamepadInput::new(),
+                                    tx: self.tx.clone(),
+                                },
+                                system_pipeline,
+                            };
+                            log::info!("Engine initialized")
+                        }
+                        Ok((window, Err(e))) => {
+                            log::error!("System pipeline failed to initialize: {e}");
+                            if let (Some(tx), Some(signal)) = (&self.tx, T::init_error_signal(&e)) {
+                                let _ = tx.send(signal);
+                            }
+
+                            self.state = EngineState::Stopped { window };
+
+                            // Nothing can restart the engine without receiver
+                            if self.rx.is_none() {
+                                log::info!("Engine exiting");
+                                event_loop.exit();
+                            }
+                        }
+                        Err(..) => {}
                     }
                 }
             }
@@ -142,6 +206,8 @@ impl<T: SystemPipeline> ApplicationHandler for Engine<T> {
                 items.input.window_event(&event);
 
                 if let WindowEvent::RedrawRequested = event {
+                    items.gamepad.update();
+
                     // Call system pipeline `update`
                     system_pipeline.update(items);
 
@@ -149,29 +215,36 @@ impl<T: SystemPipeline> ApplicationHandler for Engine<T> {
                     items.input.new_events();
                 }
 
-                // Handle queued events
-                while let Some(signal) = self.queued_signals.pop_front() {
-                    system_pipeline.in_signal(items, signal);
-                }
-
-                // Handle incoming events
+                // Collect queued and incoming events, up to a stop
+                let mut signals = self.queued_signals.drain(..).collect::<Vec<_>>();
+                let mut is_stopping = false;
                 if let Some(rx) = &self.rx {
                     for signal in rx.try_iter() {
                         match signal {
                             InSignal::Stop => {
-                                log::info!("Engine stopping");
-                                self.state = EngineState::Stopped {
-                                    window: items.window.clone(),
-                                };
-                                return;
+                                is_stopping = true;
+                                break;
                             }
                             InSignal::Start { .. } => log::warn!("Engine already started"),
-                            InSignal::Custom { signal, .. } => {
-                                system_pipeline.in_signal(items, signal)
-                            }
+                            InSignal::Custom { signal, .. } => signals.push(signal),
                         }
                     }
                 }
+
+                // Call system pipeline `in_signals` or `in_signal`
+                match self.is_batching_signals {
+                    true => system_pipeline.in_signals(items, signals),
+                    false => signals
+                        .into_iter()
+                        .for_each(|signal| system_pipeline.in_signal(items, signal)),
+                }
+
+                if is_stopping {
+                    log::info!("Engine stopping");
+                    self.state = EngineState::Stopped {
+                        window: items.window.clone(),
+                    };
+                }
             }
             EngineState::Stopped { window } => {
                 // Handle incoming events
@@ -252,7 +325,7 @@ enum EngineState<T: SystemPipeline> {
     },
     InitializingEngine,
     InitializingSystemPipeline {
-        init_rx: mpsc::Receiver<(Arc<Window>, T)>,
+        init_rx: mpsc::Receiver<(Arc<Window>, Result<T, T::InitError>)>,
         input: Box<WinitInputHelper>,
     },
     PostInit {
@@ -1,7 +1,12 @@
 use thiserror::Error;
 
+use crate::engine::SceneId;
+
 #[derive(Error, Debug)]
 pub enum Error {
     #[error("Winit event loop error: {0}")]
     WinitEventLoopError(#[from] winit::error::EventLoopError),
+
+    #[error("Scene {0} is not registered")]
+    SceneNotRegistered(SceneId),
 }
@@ -0,0 +1,123 @@
+use glam::*;
+
+/// Gamepad input state.
+///
+/// This polls the gamepads through `gilrs` when the `gamepad` feature is
+/// enabled, otherwise all the values are always zero.
+pub struct GamepadInput {
+    #[cfg(feature = "gamepad")]
+    gilrs: Option<gilrs::Gilrs>,
+    #[cfg(feature = "gamepad")]
+    active: Option<gilrs::GamepadId>,
+}
+
+impl GamepadInput {
+    /// Stick values with a length below this are treated as zero.
+    pub const DEADZONE: f32 = 0.15;
+
+    pub fn new() -> Self {
+        cfg_if::cfg_if! {
+            if #[cfg(feature = "gamepad")] {
+                let gilrs = match gilrs::Gilrs::new() {
+                    Ok(gilrs) => Some(gilrs),
+                    Err(e) => {
+                        log::warn!("Unable to initialize gamepad input: {e}");
+                        None
+                    }
+                };
+
+                Self {
+                    gilrs,
+                    active: None,
+                }
+            } else {
+                Self {}
+            }
+        }
+    }
+
+    /// Process the pending gamepad events.
+    ///
+    /// The most recently used gamepad becomes the active one.
+    pub fn update(&mut self) {
+        cfg_if::cfg_if! {
+            if #[cfg(feature = "gamepad")] {
+                if let Some(gilrs) = self.gilrs.as_mut() {
+                    while let Some(event) = gilrs.next_event() {
+                        self.active = Some(event.id);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Left stick with the deadzone applied, `y` is positive upward.
+    pub fn left_stick(&self) -> Vec2 {
+        Self::apply_deadzone(vec2(
+            self.axis(GamepadAxis::LeftStickX),
+            self.axis(GamepadAxis::LeftStickY),
+        ))
+    }
+
+    /// Right stick with the deadzone applied, `y` is positive upward.
+    pub fn right_stick(&self) -> Vec2 {
+        Self::apply_deadzone(vec2(
+            self.axis(GamepadAxis::RightStickX),
+            self.axis(GamepadAxis::RightStickY),
+        ))
+    }
+
+    /// Value of an axis of the active gamepad, in `-1.0..=1.0` for sticks and
+    /// `0.0..=1.0` for triggers.
+    pub fn axis(&self, axis: GamepadAxis) -> f32 {
+        cfg_if::cfg_if! {
+            if #[cfg(feature = "gamepad")] {
+                let (gilrs, id) = match (self.gilrs.as_ref(), self.active) {
+                    (Some(gilrs), Some(id)) => (gilrs, id),
+                    _ => return 0.0,
+                };
+                let gamepad = gilrs.gamepad(id);
+
+                match axis {
+                    GamepadAxis::LeftStickX => gamepad.value(gilrs::Axis::LeftStickX),
+                    GamepadAxis::LeftStickY => gamepad.value(gilrs::Axis::LeftStickY),
+                    GamepadAxis::RightStickX => gamepad.value(gilrs::Axis::RightStickX),
+                    GamepadAxis::RightStickY => gamepad.value(gilrs::Axis::RightStickY),
+                    GamepadAxis::LeftTrigger => gamepad
+                        .button_data(gilrs::Button::LeftTrigger2)
+                        .map_or(0.0, |data| data.value()),
+                    GamepadAxis::RightTrigger => gamepad
+                        .button_data(gilrs::Button::RightTrigger2)
+                        .map_or(0.0, |data| data.value()),
+                }
+            } else {
+                let _ = axis;
+                0.0
+            }
+        }
+    }
+
+    fn apply_deadzone(stick: Vec2) -> Vec2 {
+        match stick.length() {
+            length if length < Self::DEADZONE => Vec2::ZERO,
+            _ => stick.clamp_length_max(1.0),
+        }
+    }
+}
+
+impl Default for GamepadInput {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Analog axes of a gamepad.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GamepadAxis {
+    LeftStickX,
+    LeftStickY,
+    RightStickX,
+    RightStickY,
+    LeftTrigger,
+    RightTrigger,
+}
@@ -3,6 +3,8 @@ use std::sync::{mpsc, Arc};
 use winit::window::Window;
 use winit_input_helper::WinitInputHelper;
 
+use crate::engine::GamepadInput;
+
 /// Items in the engine.
 pub struct Items<T> {
     /// The window.
@@ -11,6 +13,9 @@ pub struct Items<T> {
     /// Input helper.
     pub input: WinitInputHelper,
 
+    /// Gamepad input.
+    pub gamepad: GamepadInput,
+
     /// Outgoing signal sender.
     pub tx: Option<mpsc::Sender<T>>,
 }
@@ -1,14 +1,20 @@
 mod core;
 mod error;
+mod gamepad;
 mod items;
+mod resource;
 mod runner;
+mod scene;
 pub mod signal;
 mod system_pipeline;
 pub mod utils;
 
 pub use core::Engine;
 pub use error::Error;
+pub use gamepad::{GamepadAxis, GamepadInput};
 pub use items::Items;
+pub use resource::{yield_now, LoadProgress, PendingResource};
 pub use runner::Runner;
+pub use scene::{Scene, SceneId, Scenes};
 pub use signal::InSignal;
 pub use system_pipeline::SystemPipeline;
@@ -0,0 +1,98 @@
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+};
+
+/// A resource loaded by a future, polled each frame without blocking.
+///
+/// This lets [`crate::engine::SystemPipeline::init`] kick off loads and
+/// return immediately, then [`PendingResource::poll`] it in
+/// [`crate::engine::SystemPipeline::update`] to show a loading screen until it
+/// is ready.
+///
+/// Dropping the pending resource drops the future, which cancels the load at
+/// its current await point. So if the scene switches mid-load, the system
+/// pipeline only needs to drop it.
+pub struct PendingResource<T> {
+    future: Option<Pin<Box<dyn Future<Output = T>>>>,
+    progress: LoadProgress,
+}
+
+impl<T: 'static> PendingResource<T> {
+    /// Create a pending resource from the future returned by `load`.
+    ///
+    /// `load` is given a [`LoadProgress`] to report the progress through.
+    pub fn new<F: Future<Output = T> + 'static>(load: impl FnOnce(LoadProgress) -> F) -> Self {
+        let progress = LoadProgress::new();
+
+        Self {
+            future: Some(Box::pin(load(progress.clone()))),
+            progress,
+        }
+    }
+
+    /// The progress of the load from 0.0 to 1.0.
+    pub fn progress(&self) -> f32 {
+        self.progress.get()
+    }
+
+    /// Whether the resource has been taken by [`PendingResource::poll`].
+    pub fn is_done(&self) -> bool {
+        self.future.is_none()
+    }
+
+    /// Poll the future once, returns the resource when it is ready.
+    ///
+    /// The resource is only returned once, after which this always returns
+    /// [`None`].
+    pub fn poll(&mut self) -> Option<T> {
+        let future = self.future.as_mut()?;
+
+        let mut context = Context::from_waker(futures::task::noop_waker_ref());
+        match future.as_mut().poll(&mut context) {
+            Poll::Ready(resource) => {
+                self.future = None;
+                self.progress.set(1.0);
+                Some(resource)
+            }
+            Poll::Pending => None,
+        }
+    }
+}
+
+/// Handle to report the progress of a [`PendingResource`].
+#[derive(Debug, Clone)]
+pub struct LoadProgress(Arc<AtomicU32>);
+
+impl LoadProgress {
+    fn new() -> Self {
+        Self(Arc::new(AtomicU32::new(0.0f32.to_bits())))
+    }
+
+    /// The progress from 0.0 to 1.0.
+    pub fn get(&self) -> f32 {
+        f32::from_bits(self.0.load(Ordering::Relaxed))
+    }
+
+    /// Set the progress, clamped to 0.0 to 1.0.
+    pub fn set(&self, progress: f32) {
+        self.0
+            .store(progress.clamp(0.0, 1.0).to_bits(), Ordering::Relaxed);
+    }
+}
+
+/// Yield to the next [`PendingResource::poll`], so long synchronous work can
+/// be split across frames.
+pub async fn yield_now() {
+    let mut is_yielded = false;
+    futures::future::poll_fn(|_| match std::mem::replace(&mut is_yielded, true) {
+        true => Poll::Ready(()),
+        false => Poll::Pending,
+    })
+    .await
+}
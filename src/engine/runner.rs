@@ -10,6 +10,7 @@ use crate::engine::{Engine, Error, InSignal, SystemPipeline};
 /// Build and run engine.
 pub struct Runner<T, U, V> {
     window_attributes: WindowAttributes,
+    is_batching_signals: bool,
     system_pipeline: T,
     rx: U,
     tx: V,
@@ -29,6 +30,7 @@ impl Runner<NoSystemPipeline, NoRx, NoTx> {
     pub fn new() -> Self {
         Self {
             window_attributes: Window::default_attributes().with_title("wgpu + Leptos"),
+            is_batching_signals: false,
             system_pipeline: NoSystemPipeline,
             rx: NoRx,
             tx: NoTx,
@@ -45,6 +47,15 @@ impl<T, U, V> Runner<T, U, V> {
         }
     }
 
+    /// Set whether to pass all the signals of a frame together to
+    /// [`SystemPipeline::in_signals`].
+    pub fn with_batching_signals(self, is_batching_signals: bool) -> Self {
+        Self {
+            is_batching_signals,
+            ..self
+        }
+    }
+
     /// Set the system pipeline to use, and the arguments to pass to
     /// [`SystemPipeline::init`].
     pub fn with_system_pipeline<W: SystemPipeline>(
@@ -53,6 +64,7 @@ impl<T, U, V> Runner<T, U, V> {
     ) -> Runner<WithSystemPipeline<W>, U, V> {
         Runner {
             window_attributes: self.window_attributes,
+            is_batching_signals: self.is_batching_signals,
             system_pipeline: WithSystemPipeline(args),
             rx: self.rx,
             tx: self.tx,
@@ -66,6 +78,7 @@ impl<T, U, V> Runner<T, U, V> {
     ) -> Runner<T, WithRx<W>, V> {
         Runner {
             window_attributes: self.window_attributes,
+            is_batching_signals: self.is_batching_signals,
             system_pipeline: self.system_pipeline,
             rx: WithRx(rx),
             tx: self.tx,
@@ -79,6 +92,7 @@ impl<T, U, V> Runner<T, U, V> {
     ) -> Runner<T, U, WithTx<W>> {
         Runner {
             window_attributes: self.window_attributes,
+            is_batching_signals: self.is_batching_signals,
             system_pipeline: self.system_pipeline,
             rx: self.rx,
             tx: WithTx(tx),
@@ -92,7 +106,8 @@ impl<T: SystemPipeline> Runner<WithSystemPipeline<T>, WithRx<T>, WithTx<T>> {
         let event_loop = EventLoop::new()?;
         let mut engine = Engine::<T>::new(self.window_attributes, self.system_pipeline.0)
             .with_rx(self.rx.0)
-            .with_tx(self.tx.0);
+            .with_tx(self.tx.0)
+            .with_batching_signals(self.is_batching_signals);
 
         log::info!("Starting engine");
         Ok(event_loop.run_app(&mut engine)?)
@@ -103,7 +118,8 @@ impl<T: SystemPipeline> Runner<WithSystemPipeline<T>, NoRx, NoTx> {
     /// Run the engine.
     pub fn run(self) -> Result<(), Error> {
         let event_loop = EventLoop::new()?;
-        let mut engine = Engine::<T>::new(self.window_attributes, self.system_pipeline.0);
+        let mut engine = Engine::<T>::new(self.window_attributes, self.system_pipeline.0)
+            .with_batching_signals(self.is_batching_signals);
 
         log::info!("Starting engine");
         Ok(event_loop.run_app(&mut engine)?)
@@ -0,0 +1,142 @@
+use std::collections::HashMap;
+
+use winit::event::{DeviceEvent, WindowEvent};
+
+use crate::engine::{Error, Items};
+
+/// Identifier of a scene registered in [`Scenes`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SceneId(pub String);
+
+impl From<&str> for SceneId {
+    fn from(id: &str) -> Self {
+        Self(id.to_string())
+    }
+}
+
+impl std::fmt::Display for SceneId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[allow(unused_variables)]
+/// Trait for a scene hosted in [`Scenes`].
+///
+/// The context `C` is owned by the system pipeline and shared by all the
+/// scenes, so resources like the device and queue are kept when switching.
+pub trait Scene<C, T>: 'static {
+    /// Called when the scene is switched to.
+    fn init(context: &mut C, items: &mut Items<T>) -> Self
+    where
+        Self: Sized;
+
+    /// Called when the scene is switched away from, before it is dropped.
+    fn exit(&mut self, context: &mut C, items: &mut Items<T>) {}
+
+    /// Called when there is a [`winit::event::DeviceEvent`].
+    fn device_event(&mut self, context: &mut C, items: &mut Items<T>, event: &DeviceEvent) {}
+
+    /// Called when there is a [`winit::event::WindowEvent`].
+    fn window_event(&mut self, context: &mut C, items: &mut Items<T>, event: &WindowEvent) {}
+
+    /// Called every frame.
+    fn update(&mut self, context: &mut C, items: &mut Items<T>) {}
+}
+
+type SceneInit<C, T> = fn(&mut C, &mut Items<T>) -> Box<dyn Scene<C, T>>;
+
+/// Registry of scenes, holding the active one.
+///
+/// The system pipeline forwards its calls to the active scene, and switches
+/// between the registered scenes at runtime.
+pub struct Scenes<C, T> {
+    inits: HashMap<SceneId, SceneInit<C, T>>,
+    active: Option<(SceneId, Box<dyn Scene<C, T>>)>,
+}
+
+impl<C: 'static, T: 'static> Scenes<C, T> {
+    pub fn new() -> Self {
+        Self {
+            inits: HashMap::new(),
+            active: None,
+        }
+    }
+
+    /// Register the scene `S` with `id`, replacing any scene with the same id.
+    pub fn with_scene<S: Scene<C, T>>(mut self, id: impl Into<SceneId>) -> Self {
+        self.register::<S>(id);
+        self
+    }
+
+    /// Register the scene `S` with `id`, replacing any scene with the same id.
+    pub fn register<S: Scene<C, T>>(&mut self, id: impl Into<SceneId>) {
+        self.inits.insert(id.into(), |context, items| {
+            Box::new(S::init(context, items))
+        });
+    }
+
+    pub fn is_registered(&self, id: &SceneId) -> bool {
+        self.inits.contains_key(id)
+    }
+
+    /// The id of the active scene, [`None`] if no scene is active.
+    pub fn active_id(&self) -> Option<&SceneId> {
+        self.active.as_ref().map(|(id, _)| id)
+    }
+
+    /// Exit the active scene and initialize the scene of `id`.
+    pub fn switch(
+        &mut self,
+        id: &SceneId,
+        context: &mut C,
+        items: &mut Items<T>,
+    ) -> Result<(), Error> {
+        let init = self
+            .inits
+            .get(id)
+            .ok_or_else(|| Error::SceneNotRegistered(id.clone()))?;
+
+        if let Some((active_id, mut scene)) = self.active.take() {
+            log::debug!("Exiting scene {active_id}");
+            scene.exit(context, items);
+        }
+
+        log::debug!("Initializing scene {id}");
+        self.active = Some((id.clone(), init(context, items)));
+
+        Ok(())
+    }
+
+    /// Exit the active scene, leaving no scene active.
+    pub fn clear(&mut self, context: &mut C, items: &mut Items<T>) {
+        if let Some((active_id, mut scene)) = self.active.take() {
+            log::debug!("Exiting scene {active_id}");
+            scene.exit(context, items);
+        }
+    }
+
+    pub fn device_event(&mut self, context: &mut C, items: &mut Items<T>, event: &DeviceEvent) {
+        if let Some((_, scene)) = self.active.as_mut() {
+            scene.device_event(context, items, event);
+        }
+    }
+
+    pub fn window_event(&mut self, context: &mut C, items: &mut Items<T>, event: &WindowEvent) {
+        if let Some((_, scene)) = self.active.as_mut() {
+            scene.window_event(context, items, event);
+        }
+    }
+
+    pub fn update(&mut self, context: &mut C, items: &mut Items<T>) {
+        if let Some((_, scene)) = self.active.as_mut() {
+            scene.update(context, items);
+        }
+    }
+}
+
+impl<C: 'static, T: 'static> Default for Scenes<C, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
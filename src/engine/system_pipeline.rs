@@ -2,7 +2,7 @@ use std::sync::Arc;
 
 use winit::{
     event::{DeviceEvent, WindowEvent},
-    window::Window,
+    window::{Window, WindowAttributes},
 };
 
 use crate::engine::Items;
@@ -19,8 +19,32 @@ pub trait SystemPipeline: Sized + 'static {
     /// Outgoing signal.
     type OutSignal;
 
+    /// Error returned by [`SystemPipeline::init`].
+    type InitError: std::fmt::Display;
+
+    /// Called before the window is created, returns the attributes to create
+    /// it with.
+    ///
+    /// `window_attributes` are the ones given to the engine, e.g. with the
+    /// canvas on the web.
+    fn window_attributes(
+        args: &Self::Args,
+        window_attributes: WindowAttributes,
+    ) -> WindowAttributes {
+        window_attributes
+    }
+
     /// Called when the window is just created.
-    async fn init(window: Arc<Window>, args: Self::Args) -> Self;
+    async fn init(window: Arc<Window>, args: Self::Args) -> Result<Self, Self::InitError>;
+
+    /// Called when [`SystemPipeline::init`] fails, returns the signal to send
+    /// out to report the error.
+    ///
+    /// The engine is stopped after this, and can be restarted with
+    /// [`crate::engine::InSignal::Start`].
+    fn init_error_signal(error: &Self::InitError) -> Option<Self::OutSignal> {
+        None
+    }
 
     /// Called when there is a [`winit::event::DeviceEvent`].
     ///
@@ -39,9 +63,49 @@ pub trait SystemPipeline: Sized + 'static {
     /// [`Items::input`] is processed.
     fn update(&mut self, items: &mut Items<Self::OutSignal>) {}
 
+    /// Called when the application is suspended, e.g. the tab or app is sent
+    /// to the background.
+    ///
+    /// The surface may be lost after this, so GPU resources tied to it should
+    /// be dropped. [`SystemPipeline::update`] may still be called until
+    /// [`SystemPipeline::on_resume`], and should not present to the surface.
+    fn on_suspend(&mut self) {}
+
+    /// Called when the application is resumed after
+    /// [`SystemPipeline::on_suspend`].
+    ///
+    /// This is called before the next [`SystemPipeline::update`], so the
+    /// surface can be recreated for `window`.
+    fn on_resume(&mut self, window: Arc<Window>) {}
+
+    /// Called when the window is requested to close, e.g. by its close button,
+    /// returns whether to close it.
+    ///
+    /// This is the last chance to flush state before the event loop exits,
+    /// e.g. to send a [`SystemPipeline::OutSignal`]. The event loop exits
+    /// after this event is handled, so [`SystemPipeline::update`] is not called
+    /// again. Returning false keeps the engine running as if the request never
+    /// happened, e.g. to show a confirmation, and the window only closes on the
+    /// next request returning true. This is not called on the web, where the
+    /// canvas cannot be closed, or before [`SystemPipeline::init`] completes.
+    fn on_close_requested(&mut self, items: &mut Items<Self::OutSignal>) -> bool {
+        true
+    }
+
     /// Called when there is a [`SystemPipeline::InSignal`].
     ///
     /// This is called after [`SystemPipeline::window_event`] and
     /// [`SystemPipeline::update`].
     fn in_signal(&mut self, items: &mut Items<Self::OutSignal>, signal: Self::InSignal) {}
+
+    /// Called with all the [`SystemPipeline::InSignal`] received in a frame,
+    /// if the engine is set to batch signals.
+    ///
+    /// This allows coalescing signals before handling them, by default each
+    /// signal is passed to [`SystemPipeline::in_signal`] in order.
+    fn in_signals(&mut self, items: &mut Items<Self::OutSignal>, signals: Vec<Self::InSignal>) {
+        for signal in signals {
+            self.in_signal(items, signal);
+        }
+    }
 }
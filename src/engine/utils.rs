@@ -13,3 +13,49 @@ pub fn set_timeout(callback: impl FnMut() + 'static, millis: i32) {
         .unwrap();
     closure.forget();
 }
+
+#[cfg(target_arch = "wasm32")]
+/// Observe the size of an element in physical pixels.
+///
+/// The callback is called with the width and height whenever the element is
+/// resized, which are zero when it is hidden. The size is the device pixel
+/// content box where supported, otherwise the content box scaled by the
+/// device pixel ratio. Call [`web_sys::ResizeObserver::disconnect`] on the
+/// returned observer to stop observing.
+pub fn observe_resize(
+    element: &web_sys::Element,
+    mut callback: impl FnMut(u32, u32) + 'static,
+) -> web_sys::ResizeObserver {
+    use wasm_bindgen::{prelude::*, JsCast};
+
+    let closure = Closure::wrap(Box::new(move |entries: js_sys::Array| {
+        let entry = match entries.get(0).dyn_into::<web_sys::ResizeObserverEntry>() {
+            Ok(entry) => entry,
+            Err(_) => return,
+        };
+
+        // Not all browsers report the device pixel content box
+        let device_pixel_size = js_sys::Reflect::get(&entry, &"devicePixelContentBoxSize".into())
+            .ok()
+            .and_then(|sizes| sizes.dyn_into::<js_sys::Array>().ok())
+            .and_then(|sizes| sizes.get(0).dyn_into::<web_sys::ResizeObserverSize>().ok());
+
+        let (width, height) = match device_pixel_size {
+            Some(size) => (size.inline_size(), size.block_size()),
+            None => {
+                let ratio = web_sys::window().expect("window").device_pixel_ratio();
+                let rect = entry.content_rect();
+                (rect.width() * ratio, rect.height() * ratio)
+            }
+        };
+
+        callback(width.round() as u32, height.round() as u32);
+    }) as Box<dyn FnMut(js_sys::Array)>);
+
+    let observer =
+        web_sys::ResizeObserver::new(closure.as_ref().unchecked_ref()).expect("resize observer");
+    closure.forget();
+
+    observer.observe(element);
+    observer
+}
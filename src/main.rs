@@ -13,10 +13,8 @@ pub fn main() {
 
             mount_to_body(ui::App);
         } else {
-            use winit::{
-                dpi::LogicalSize,
-                window::Window,
-            };
+            use glam::*;
+            use winit::dpi::LogicalSize;
 
             if std::env::var("RUST_LOG").is_err() {
                 std::env::set_var("RUST_LOG", "debug");
@@ -24,12 +22,19 @@ pub fn main() {
             env_logger::init();
 
             engine::Runner::new()
-                .with_window_attributes(Window::default_attributes()
-                    .with_title("wgpu")
-                    .with_inner_size(LogicalSize::new(800.0, 600.0))
-                )
                 .with_system_pipeline::<systems::Pipeline>(systems::Args {
+                    title: Some("wgpu".to_string()),
+                    initial_size: Some(LogicalSize::new(800.0, 600.0)),
                     fps_limit: systems::FpsLimit::new(60),
+                    // A row of pyramids sharing the transform buffer
+                    pyramid_objects: (-1..=1)
+                        .map(|i| systems::Transform::new(
+                            Vec3::new(i as f32 * 2.0, 0.0, 0.0),
+                            Quat::IDENTITY,
+                            Vec3::ONE,
+                        ))
+                        .collect(),
+                    ..Default::default()
                 })
                 .run()
                 .unwrap();
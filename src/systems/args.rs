@@ -1,32 +1,293 @@
 use glam::*;
+use thiserror::Error;
+use winit::{dpi::LogicalSize, window::WindowAttributes};
 
 use crate::systems::{
-    handlers::{PyramidModel, PyramidTransform},
-    RgbColor,
+    handlers::{Pyramid, PyramidModel, PyramidTransform},
+    ActionMap, Pipeline, RgbColor, Texture, TextureData, Transform,
 };
 
 /// The configurations of the system pipeline.
+///
+/// With the `serde` feature, missing fields are filled with the defaults, so
+/// only the fields to change need to be given, e.g. in a shared link.
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct Args {
+    /// The title of the window, [`None`] to keep the one given to the engine.
+    ///
+    /// This is ignored on the web, where the page has the title.
+    pub title: Option<String>,
+    /// The initial inner size of the window, or the canvas on the web, [`None`]
+    /// to keep the one given to the engine.
+    pub initial_size: Option<LogicalSize<f64>>,
+    /// The minimum inner size of the window, or the canvas on the web.
+    pub min_size: Option<LogicalSize<f64>>,
+    /// Whether the window can be resized by the user, ignored on the web.
+    pub resizable: bool,
+    /// Whether the window has the title bar and borders, ignored on the web.
+    pub decorations: bool,
     pub fps_limit: FpsLimit,
     pub clear_color: RgbColor,
+    pub sample_count: u32,
+    pub hdr: bool,
+    pub fxaa: bool,
+    /// The scale of the render size relative to the window size, e.g. 2 to
+    /// supersample, clamped to the maximum texture dimension.
+    pub render_scale: f32,
+    /// The anisotropic filtering level of the pyramid texture, a power of two
+    /// up to 16, clamped to what the adapter supports. Defaults to 1 to
+    /// disable it, as WebGL may not support it.
+    pub anisotropy: u16,
+    /// The fixed aspect ratio to letterbox the scene to, [`None`] to fill the
+    /// window.
+    pub target_aspect: Option<f32>,
+    /// Draw a second camera looking at the pyramid from another angle to the
+    /// right of the controlled one.
+    pub split_screen: bool,
+    /// Keep the previous frames instead of clearing them, leaving trails
+    /// behind the moving objects. This needs an offscreen target, e.g. a
+    /// sample count above 1.
+    pub trails: bool,
+    /// The skybox faces in the order of +X, -X, +Y, -Y, +Z, -Z.
+    ///
+    /// If [`None`], the clear color is used as the background.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub skybox: Option<[TextureData; 6]>,
+    /// The bytes of a glTF model, loaded asynchronously after initialization.
+    #[cfg(feature = "gltf")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub model_gltf: Option<std::sync::Arc<[u8]>>,
     pub pyramid_transform: PyramidTransform,
     pub pyramid_model: PyramidModel,
+    /// The objects of the pyramid, each drawn with its own transform, up to
+    /// [`Pyramid::MAX_OBJECTS`].
+    pub pyramid_objects: Vec<Transform>,
+    /// The seed of the random generation, the same seed always generates the
+    /// same scene.
+    #[cfg(feature = "rng")]
+    pub seed: u64,
+    /// The number of pyramid instances scattered with [`Args::seed`], the
+    /// single default instance is used if zero.
+    #[cfg(feature = "rng")]
+    pub pyramid_scatter_count: usize,
+    /// The radius of the disk the pyramid instances are scattered in.
+    #[cfg(feature = "rng")]
+    pub pyramid_scatter_radius: f32,
+    /// The bindings of the input actions, defaults to
+    /// [`Pipeline::default_actions`].
+    pub actions: ActionMap,
 }
 
 impl Default for Args {
     fn default() -> Self {
         Self {
+            title: None,
+            initial_size: None,
+            min_size: None,
+            resizable: true,
+            decorations: true,
             fps_limit: FpsLimit::default(),
             clear_color: RgbColor::GRAY,
+            sample_count: 4,
+            hdr: false,
+            fxaa: false,
+            render_scale: 1.0,
+            anisotropy: 1,
+            target_aspect: None,
+            split_screen: false,
+            trails: false,
+            skybox: None,
+            #[cfg(feature = "gltf")]
+            model_gltf: None,
             pyramid_transform: PyramidTransform::default(),
             pyramid_model: PyramidModel::default(),
+            pyramid_objects: vec![Transform::IDENTITY],
+            #[cfg(feature = "rng")]
+            seed: 0,
+            #[cfg(feature = "rng")]
+            pyramid_scatter_count: 0,
+            #[cfg(feature = "rng")]
+            pyramid_scatter_radius: 5.0,
+            actions: Pipeline::default_actions(),
         }
     }
 }
 
+impl Args {
+    /// The maximum nesting of the query, e.g.
+    /// `pyramid_transform[transform][position][0]`.
+    #[cfg(feature = "serde")]
+    const QUERY_DEPTH: usize = 5;
+
+    /// Parse the args from a JSON object, then [`Args::validate`] them.
+    #[cfg(feature = "serde")]
+    pub fn from_json(json: &str) -> Result<Self, ArgsError> {
+        let args = serde_json::from_str::<Self>(json)?;
+        args.validate()?;
+        Ok(args)
+    }
+
+    /// Parse the args from a URL query, then [`Args::validate`] them.
+    ///
+    /// Nested fields use brackets, e.g.
+    /// `?fps_limit=30&pyramid_model[side_count]=6&clear_color[0]=0.1`, the
+    /// leading `?` is optional.
+    #[cfg(feature = "serde")]
+    pub fn from_query(query: &str) -> Result<Self, ArgsError> {
+        let query = query.strip_prefix('?').unwrap_or(query);
+        let args =
+            serde_qs::Config::new(Self::QUERY_DEPTH, false).deserialize_str::<Self>(query)?;
+        args.validate()?;
+        Ok(args)
+    }
+
+    /// Apply the window fields to `window_attributes`.
+    pub fn window_attributes(&self, mut window_attributes: WindowAttributes) -> WindowAttributes {
+        if let Some(initial_size) = self.initial_size {
+            window_attributes = window_attributes.with_inner_size(initial_size);
+        }
+        if let Some(min_size) = self.min_size {
+            window_attributes = window_attributes.with_min_inner_size(min_size);
+        }
+
+        // The page owns the title and the canvas has no frame
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            if let Some(title) = self.title.as_ref() {
+                window_attributes = window_attributes.with_title(title);
+            }
+            window_attributes = window_attributes
+                .with_resizable(self.resizable)
+                .with_decorations(self.decorations);
+        }
+
+        window_attributes
+    }
+
+    /// Check that the values are in their valid ranges.
+    pub fn validate(&self) -> Result<(), ArgsError> {
+        for size in [self.initial_size, self.min_size].into_iter().flatten() {
+            if !(size.width.is_finite() && size.width > 0.0)
+                || !(size.height.is_finite() && size.height > 0.0)
+            {
+                return Err(ArgsError::InvalidWindowSize(size.width, size.height));
+            }
+        }
+
+        if !self.sample_count.is_power_of_two() || self.sample_count > 16 {
+            return Err(ArgsError::InvalidSampleCount(self.sample_count));
+        }
+
+        if !(self.render_scale.is_finite() && self.render_scale > 0.0) {
+            return Err(ArgsError::InvalidRenderScale(self.render_scale));
+        }
+
+        if !self.anisotropy.is_power_of_two() || self.anisotropy > Texture::MAX_ANISOTROPY {
+            return Err(ArgsError::InvalidAnisotropy(self.anisotropy));
+        }
+
+        if let Some(aspect) = self.target_aspect {
+            if !(aspect.is_finite() && aspect > 0.0) {
+                return Err(ArgsError::InvalidTargetAspect(aspect));
+            }
+        }
+
+        let model = &self.pyramid_model;
+        if model.side_count < 3 {
+            return Err(ArgsError::InvalidPyramidSideCount(model.side_count));
+        }
+        for (name, value) in [("height", model.height), ("base radius", model.base_radius)] {
+            if !(value.is_finite() && value > 0.0) {
+                return Err(ArgsError::InvalidPyramidDimension(name, value));
+            }
+        }
+        for (name, value) in [("metallic", model.metallic), ("roughness", model.roughness)] {
+            if !(0.0..=1.0).contains(&value) {
+                return Err(ArgsError::InvalidPyramidMaterial(name, value));
+            }
+        }
+
+        #[cfg(feature = "rng")]
+        if self.pyramid_scatter_count > 0
+            && !(self.pyramid_scatter_radius.is_finite() && self.pyramid_scatter_radius > 0.0)
+        {
+            return Err(ArgsError::InvalidPyramidDimension(
+                "scatter radius",
+                self.pyramid_scatter_radius,
+            ));
+        }
+
+        let transform = &self.pyramid_transform;
+        if !transform.auto_rotation_speed.is_finite() {
+            return Err(ArgsError::InvalidAutoRotationSpeed(
+                transform.auto_rotation_speed,
+            ));
+        }
+
+        if self.pyramid_objects.len() > Pyramid::MAX_OBJECTS {
+            return Err(ArgsError::TooManyPyramidObjects(self.pyramid_objects.len()));
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ArgsError {
+    #[cfg(feature = "serde")]
+    #[error("args json error: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[cfg(feature = "serde")]
+    #[error("args query error: {0}")]
+    Query(#[from] serde_qs::Error),
+
+    #[error("window size {0}x{1} is not positive")]
+    InvalidWindowSize(f64, f64),
+
+    #[error("sample count {0} is not a power of two up to 16")]
+    InvalidSampleCount(u32),
+
+    #[error("render scale {0} is not positive")]
+    InvalidRenderScale(f32),
+
+    #[error("anisotropy {0} is not a power of two up to {max}", max = Texture::MAX_ANISOTROPY)]
+    InvalidAnisotropy(u16),
+
+    #[error("target aspect {0} is not positive")]
+    InvalidTargetAspect(f32),
+
+    #[error("pyramid side count {0} is less than 3")]
+    InvalidPyramidSideCount(usize),
+
+    #[error("pyramid {0} {1} is not positive")]
+    InvalidPyramidDimension(&'static str, f32),
+
+    #[error("pyramid {0} {1} is not in 0 to 1")]
+    InvalidPyramidMaterial(&'static str, f32),
+
+    #[error("auto rotation speed {0} is not finite")]
+    InvalidAutoRotationSpeed(f32),
+
+    #[error("pyramid object count {0} exceeds the maximum of {max}", max = Pyramid::MAX_OBJECTS)]
+    TooManyPyramidObjects(usize),
+}
+
 /// The maximum number of frames per second.
+///
+/// With the `serde` feature, this is (de)serialized as the number of frames
+/// per second, with 0 being unlimited.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(from = "u32", into = "u32")
+)]
 pub struct FpsLimit(u32);
 
 impl FpsLimit {
@@ -63,3 +324,15 @@ impl Default for FpsLimit {
         Self::new(60)
     }
 }
+
+impl From<u32> for FpsLimit {
+    fn from(fps: u32) -> Self {
+        Self::new(fps)
+    }
+}
+
+impl From<FpsLimit> for u32 {
+    fn from(fps_limit: FpsLimit) -> Self {
+        fps_limit.0
+    }
+}
@@ -1,6 +1,6 @@
 use thiserror::Error;
 
-use crate::systems::ColorError;
+use crate::systems::{handlers::DisplayError, ColorError, ShaderError};
 
 #[derive(Debug, Error)]
 pub enum Error {
@@ -9,4 +9,10 @@ pub enum Error {
 
     #[error("color error: {0}")]
     Color(#[from] ColorError),
+
+    #[error("display error: {0}")]
+    Display(#[from] DisplayError),
+
+    #[error("shader error: {0}")]
+    Shader(#[from] ShaderError),
 }
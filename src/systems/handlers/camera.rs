@@ -1,24 +1,87 @@
+use std::sync::mpsc;
+
 use glam::*;
 use wgpu::util::DeviceExt;
 use winit::keyboard::KeyCode;
 use winit_input_helper::WinitInputHelper;
 
+use crate::{
+    engine::{GamepadAxis, GamepadInput},
+    systems::{
+        handlers::{Display, TouchGestures},
+        ActionMap, CameraMovedSignal, EngineOutSignal, Frustum,
+    },
+};
+
 /// Handler for the camera.
 pub struct Camera {
     model: CameraModel,
+    fov_animation: Option<FovAnimation>,
+    recording: Option<CameraRecording>,
+    playback: Option<CameraPlayback>,
 
     model_buffer: wgpu::Buffer,
 
     bind_group_layout: wgpu::BindGroupLayout,
     bind_group: wgpu::BindGroup,
 
+    signal_interval: f32,
+    signal_timer: f32,
+
+    aspect_ratio: f32,
+
+    is_enabled: bool,
     is_model_dirty: bool,
+    is_signal_pending: bool,
 }
 
 impl Camera {
     pub const PITCH_LIMIT: f32 = std::f32::consts::FRAC_PI_2 - 1e-6;
+    pub const ORBIT_MIN_DISTANCE: f32 = 1e-2;
+    pub const VELOCITY_EPSILON: f32 = 1e-3;
+    pub const MIN_VERTICAL_FOV: f32 = std::f32::consts::PI / 18.0;
+    pub const MAX_VERTICAL_FOV: f32 = std::f32::consts::PI * 2.0 / 3.0;
+
+    /// The smallest radius [`Camera::frame_bounds`] fits, for degenerate boxes.
+    pub const MIN_FRAME_RADIUS: f32 = 1e-2;
+
+    /// The scroll lines per pixel of pinching.
+    pub const PINCH_SCROLL_SCALE: f32 = 0.05;
+    /// The world units per pixel of panning.
+    pub const PAN_SCALE: f32 = 0.01;
+
+    /// The seconds between each sample of [`Camera::start_recording`].
+    pub const RECORDING_INTERVAL: f32 = 1.0 / 30.0;
+
+    /// The default minimum seconds between each [`CameraMovedSignal`].
+    pub const DEFAULT_SIGNAL_INTERVAL: f32 = 0.1;
+
+    /// The actions moving the camera in fly mode, see
+    /// [`Camera::default_actions`].
+    pub const ACTION_MOVE_FORWARD: &'static str = "move_forward";
+    pub const ACTION_MOVE_BACKWARD: &'static str = "move_backward";
+    pub const ACTION_MOVE_LEFT: &'static str = "move_left";
+    pub const ACTION_MOVE_RIGHT: &'static str = "move_right";
+    pub const ACTION_MOVE_UP: &'static str = "move_up";
+    pub const ACTION_MOVE_DOWN: &'static str = "move_down";
+
+    /// The actions changing the speed in fly mode by
+    /// [`CameraModel::speed_factor`] each press, see
+    /// [`Camera::default_actions`].
+    pub const ACTION_SPEED_UP: &'static str = "speed_up";
+    pub const ACTION_SPEED_DOWN: &'static str = "speed_down";
+    /// The action held to change the speed with the scroll wheel in fly mode,
+    /// instead of zooming.
+    pub const ACTION_SPEED_SCROLL: &'static str = "speed_scroll";
+
+    pub fn new(
+        device: &wgpu::Device,
+        aspect_ratio: f32,
+        mut model: CameraModel,
+        signal_interval: f32,
+    ) -> Self {
+        model.update_orbit_position();
 
-    pub fn new(device: &wgpu::Device, aspect_ratio: f32, model: CameraModel) -> Self {
         log::debug!("Creating camera model buffer");
         let model_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Camera Model Buffer"),
@@ -31,7 +94,7 @@ impl Camera {
             label: Some("Camera Model Bind Group Layout"),
             entries: &[wgpu::BindGroupLayoutEntry {
                 binding: 0,
-                visibility: wgpu::ShaderStages::VERTEX,
+                visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
                 ty: wgpu::BindingType::Buffer {
                     ty: wgpu::BufferBindingType::Uniform,
                     has_dynamic_offset: false,
@@ -53,13 +116,25 @@ impl Camera {
 
         Self {
             model,
+            fov_animation: None,
+            recording: None,
+            playback: None,
 
             model_buffer,
 
             bind_group_layout,
             bind_group,
 
+            signal_interval,
+            // Send the initial model on the first frame, so the receiver does
+            // not start from the default model
+            signal_timer: signal_interval,
+
+            aspect_ratio,
+
+            is_enabled: true,
             is_model_dirty: false,
+            is_signal_pending: true,
         }
     }
 
@@ -70,103 +145,786 @@ impl Camera {
 
     /// Camera bind group.
     ///
-    /// A single [`Mat4`] buffer bind group.
+    /// A single uniform buffer bind group, see [`CameraModelBuffer`] for the
+    /// layout.
     pub fn bind_group(&self) -> &wgpu::BindGroup {
         &self.bind_group
     }
 
+    pub fn is_enabled(&self) -> bool {
+        self.is_enabled
+    }
+
+    /// Enable or disable the controls, e.g. while a UI modal is open.
+    ///
+    /// A disabled camera ignores the inputs in [`Camera::update`], but is
+    /// still rendered, so the view stays valid.
+    pub fn set_enabled(&mut self, is_enabled: bool) {
+        self.is_enabled = is_enabled;
+    }
+
     pub fn model(&self) -> &CameraModel {
         &self.model
     }
 
-    pub fn update(&mut self, dt: f32, input: &WinitInputHelper) {
-        let right = self.model.right();
-        let forward = (self.model.forward() * (Vec3::ONE - CameraModel::UP)).normalize();
+    /// The current pose of the camera, e.g. for saving a bookmark.
+    pub fn pose(&self) -> CameraModel {
+        self.model.clone()
+    }
 
-        // Movement
-        if input.key_held(KeyCode::KeyW) {
-            self.model.position += forward * self.model.speed * dt;
-            self.is_model_dirty = true;
-        } else if input.key_held(KeyCode::KeyS) {
-            self.model.position -= forward * self.model.speed * dt;
-            self.is_model_dirty = true;
+    /// Jump to a pose, e.g. one returned from [`Camera::pose`].
+    pub fn set_pose(&mut self, model: CameraModel) {
+        self.set_model(model);
+    }
+
+    /// Sets the model of the camera, e.g. from the UI.
+    pub fn set_model(&mut self, model: CameraModel) {
+        self.model = model;
+        self.is_model_dirty = true;
+    }
+
+    /// Switch between fly and orbit mode, see
+    /// [`CameraModel::toggle_control_mode`].
+    pub fn toggle_control_mode(&mut self) {
+        self.model.toggle_control_mode();
+        self.is_model_dirty = true;
+        log::debug!("Camera control mode: {:?}", self.model.control_mode);
+    }
+
+    /// The default bindings of the camera actions, WASD to move horizontally,
+    /// Space and Left Shift to move up and down, and `=` or `]` and `-` or `[`
+    /// to speed up and down, or the scroll wheel with Left Ctrl held.
+    pub fn default_actions() -> ActionMap {
+        ActionMap::new()
+            .with_binding(Self::ACTION_MOVE_FORWARD, KeyCode::KeyW)
+            .with_binding(Self::ACTION_MOVE_BACKWARD, KeyCode::KeyS)
+            .with_binding(Self::ACTION_MOVE_LEFT, KeyCode::KeyA)
+            .with_binding(Self::ACTION_MOVE_RIGHT, KeyCode::KeyD)
+            .with_binding(Self::ACTION_MOVE_UP, KeyCode::Space)
+            .with_binding(Self::ACTION_MOVE_DOWN, KeyCode::ShiftLeft)
+            .with_binding(Self::ACTION_SPEED_UP, KeyCode::Equal)
+            .with_binding(Self::ACTION_SPEED_UP, KeyCode::BracketRight)
+            .with_binding(Self::ACTION_SPEED_DOWN, KeyCode::Minus)
+            .with_binding(Self::ACTION_SPEED_DOWN, KeyCode::BracketLeft)
+            .with_binding(Self::ACTION_SPEED_SCROLL, KeyCode::ControlLeft)
+    }
+
+    /// Animate the vertical field of view to the target over the duration.
+    ///
+    /// This replaces any ongoing animation, and does nothing for an
+    /// orthographic projection.
+    pub fn animate_fov(&mut self, target_radians: f32, duration_secs: f32) {
+        if let ProjectionKind::Perspective { vertical_fov } = self.model.projection {
+            self.fov_animation = Some(FovAnimation {
+                from: vertical_fov,
+                to: target_radians,
+                duration: duration_secs,
+                elapsed: 0.0,
+            });
+        }
+    }
+
+    pub fn is_fov_animating(&self) -> bool {
+        self.fov_animation.is_some()
+    }
+
+    /// Move the camera so the box from `min` to `max` fits in view at
+    /// `aspect_ratio`, e.g. after loading a model.
+    ///
+    /// The bounding sphere of the box is fitted in the narrower field of view,
+    /// so a thin box fits from any side, and a degenerate box is fitted as a
+    /// sphere of [`Camera::MIN_FRAME_RADIUS`]. An orthographic projection is
+    /// resized to fit instead. The orientation is kept unless the box is behind
+    /// the camera, in which case the camera turns to face it first. In orbit
+    /// mode, the center of the box becomes the target.
+    pub fn frame_bounds(&mut self, min: Vec3, max: Vec3, aspect_ratio: f32) {
+        // E.g. the box of no vertices from infinity to negative infinity
+        if !min.is_finite() || !max.is_finite() {
+            log::warn!("Unable to frame non-finite bounds from {min} to {max}, ignoring");
+            return;
+        }
+
+        let (min, max) = (min.min(max), min.max(max));
+        let center = (min + max) * 0.5;
+        let radius = ((max - min).length() * 0.5).max(Self::MIN_FRAME_RADIUS);
+
+        if (center - self.model.position).dot(self.model.forward()) < 0.0 {
+            self.model.look_at(center);
+        }
+
+        let distance = match &mut self.model.projection {
+            ProjectionKind::Perspective { vertical_fov } => {
+                let half_vertical = *vertical_fov * 0.5;
+                let half_horizontal = (half_vertical.tan() * aspect_ratio).atan();
+                radius / half_vertical.min(half_horizontal).sin()
+            }
+            ProjectionKind::Orthographic { height } => {
+                *height = 2.0 * radius * (1.0 / aspect_ratio).max(1.0);
+                2.0 * radius
+            }
+        };
+
+        match &mut self.model.control_mode {
+            CameraControlMode::Fly => {
+                self.model.position = center - self.model.forward() * distance;
+            }
+            CameraControlMode::Orbit {
+                target,
+                distance: orbit_distance,
+            } => {
+                *target = center;
+                *orbit_distance = distance;
+                self.model.update_orbit_position();
+            }
+        }
+
+        if distance + radius > self.model.z_far {
+            log::warn!(
+                "Framed bounds extend past the far plane at {}",
+                self.model.z_far
+            );
+        }
+
+        log::debug!("Camera framed bounds at {center} with radius {radius}");
+        self.model.velocity = Vec3::ZERO;
+        self.is_model_dirty = true;
+    }
+
+    /// Start recording the model every [`Camera::RECORDING_INTERVAL`] in
+    /// [`Camera::update`], replacing any ongoing recording.
+    ///
+    /// The current model is sampled at time zero.
+    pub fn start_recording(&mut self) {
+        log::debug!("Camera recording started");
+        self.recording = Some(CameraRecording {
+            track: CameraTrack {
+                samples: vec![CameraTrackSample {
+                    time: 0.0,
+                    model: self.model.clone(),
+                }],
+            },
+            elapsed: 0.0,
+            next_sample: Self::RECORDING_INTERVAL,
+        });
+    }
+
+    /// Stop recording, returning the track, [`None`] if not recording.
+    ///
+    /// The model is sampled once more at the end if the last sample is earlier,
+    /// so the track lasts as long as the recording.
+    pub fn stop_recording(&mut self) -> Option<CameraTrack> {
+        let CameraRecording {
+            mut track, elapsed, ..
+        } = self.recording.take()?;
+
+        if track.duration() < elapsed {
+            track.samples.push(CameraTrackSample {
+                time: elapsed,
+                model: self.model.clone(),
+            });
+        }
+
+        log::debug!(
+            "Camera recording stopped with {} samples over {elapsed}s",
+            track.samples.len()
+        );
+        Some(track)
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recording.is_some()
+    }
+
+    /// Play the track from the start in [`Camera::update`], replacing any
+    /// ongoing playback.
+    ///
+    /// The live input is ignored while playing, and the camera stays at the
+    /// last sample when the track ends. Playing an empty track does nothing.
+    pub fn play(&mut self, track: CameraTrack) {
+        if track.samples.is_empty() {
+            log::warn!("Camera track is empty, ignoring");
+            return;
         }
 
-        if input.key_held(KeyCode::KeyA) {
-            self.model.position -= right * self.model.speed * dt;
+        log::debug!("Camera playback started for {}s", track.duration());
+        self.playback = Some(CameraPlayback {
+            track,
+            elapsed: 0.0,
+        });
+    }
+
+    /// Stop the playback, keeping the current pose.
+    pub fn stop_playing(&mut self) {
+        if self.playback.take().is_some() {
+            log::debug!("Camera playback stopped");
+            self.model.velocity = Vec3::ZERO;
+        }
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.playback.is_some()
+    }
+
+    /// Update the camera from the inputs.
+    ///
+    /// The movement in fly mode is queried from `actions`, see
+    /// [`Camera::default_actions`].
+    ///
+    /// `mouse_delta` is the mouse motion of the frame in pixels, which may come
+    /// from the raw device motion instead of [`WinitInputHelper::mouse_diff`].
+    ///
+    /// One finger dragging looks around like the mouse, two fingers pinching
+    /// zooms like the scroll wheel, and two fingers dragging pans.
+    pub fn update(
+        &mut self,
+        dt: f32,
+        input: &WinitInputHelper,
+        actions: &ActionMap,
+        mouse_delta: Vec2,
+        touch: &TouchGestures,
+        gamepad: &GamepadInput,
+    ) {
+        if !self.is_enabled {
+            return;
+        }
+
+        if self.playback.is_some() {
+            self.update_playback(dt);
+            self.update_recording(dt);
+            return;
+        }
+
+        let look = mouse_delta + touch.look;
+        let scroll = input.scroll_diff().1 + touch.pinch * Self::PINCH_SCROLL_SCALE;
+
+        match self.model.control_mode {
+            CameraControlMode::Fly => {
+                self.update_fly(dt, input, actions, look, scroll, touch.pan, gamepad)
+            }
+            CameraControlMode::Orbit { .. } => {
+                self.update_orbit(dt, look, scroll, touch.pan, gamepad)
+            }
+        }
+
+        self.update_fov_animation(dt);
+        self.update_recording(dt);
+    }
+
+    /// Advance the playback, moving to the interpolated pose of the track.
+    fn update_playback(&mut self, dt: f32) {
+        let Some(playback) = self.playback.as_mut() else {
+            return;
+        };
+
+        playback.elapsed += dt;
+        if let Some(model) = playback.track.sample(playback.elapsed) {
+            self.model = model;
             self.is_model_dirty = true;
-        } else if input.key_held(KeyCode::KeyD) {
-            self.model.position += right * self.model.speed * dt;
+        }
+
+        if playback.elapsed >= playback.track.duration() {
+            log::debug!("Camera playback finished");
+            self.stop_playing();
+        }
+    }
+
+    /// Sample the model if the recording interval has passed.
+    fn update_recording(&mut self, dt: f32) {
+        let Some(recording) = self.recording.as_mut() else {
+            return;
+        };
+
+        recording.elapsed += dt;
+        if recording.elapsed < recording.next_sample {
+            return;
+        }
+
+        // Sample once for a long frame instead of repeating the same model
+        recording.track.samples.push(CameraTrackSample {
+            time: recording.elapsed,
+            model: self.model.clone(),
+        });
+        while recording.next_sample <= recording.elapsed {
+            recording.next_sample += Self::RECORDING_INTERVAL;
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn update_fly(
+        &mut self,
+        dt: f32,
+        input: &WinitInputHelper,
+        actions: &ActionMap,
+        look: Vec2,
+        mut scroll: f32,
+        pan: Vec2,
+        gamepad: &GamepadInput,
+    ) {
+        // Speed, the scroll only changes it with the modifier held so it does
+        // not also zoom
+        let mut speed_steps = 0.0;
+        if actions.pressed(input, Self::ACTION_SPEED_SCROLL) {
+            speed_steps += std::mem::take(&mut scroll);
+        }
+        if actions.just_pressed(input, Self::ACTION_SPEED_UP) {
+            speed_steps += 1.0;
+        }
+        if actions.just_pressed(input, Self::ACTION_SPEED_DOWN) {
+            speed_steps -= 1.0;
+        }
+        if speed_steps != 0.0 {
+            self.update_speed(speed_steps);
+        }
+
+        // Roll does not affect the movement plane
+        let right = self.model.horizontal_right();
+        let forward = self.model.horizontal_forward();
+
+        // Input direction
+        let mut direction = Vec3::ZERO;
+
+        if actions.pressed(input, Self::ACTION_MOVE_FORWARD) {
+            direction += forward;
+        } else if actions.pressed(input, Self::ACTION_MOVE_BACKWARD) {
+            direction -= forward;
+        }
+
+        if actions.pressed(input, Self::ACTION_MOVE_LEFT) {
+            direction -= right;
+        } else if actions.pressed(input, Self::ACTION_MOVE_RIGHT) {
+            direction += right;
+        }
+
+        let up = self.model.up_axis.vector();
+        if actions.pressed(input, Self::ACTION_MOVE_UP) {
+            direction += up;
+        } else if actions.pressed(input, Self::ACTION_MOVE_DOWN) {
+            direction -= up;
+        }
+
+        let left_stick = gamepad.left_stick();
+        let triggers =
+            gamepad.axis(GamepadAxis::RightTrigger) - gamepad.axis(GamepadAxis::LeftTrigger);
+        direction += forward * left_stick.y + right * left_stick.x + up * triggers;
+
+        // Velocity
+        self.model.velocity += direction.clamp_length_max(1.0) * self.model.acceleration * dt;
+        self.model.velocity *= (-self.model.damping * dt).exp();
+        self.model.velocity = self.model.velocity.clamp_length_max(self.model.speed);
+
+        // Movement
+        if self.model.velocity.length_squared() < Self::VELOCITY_EPSILON * Self::VELOCITY_EPSILON {
+            self.model.velocity = Vec3::ZERO;
+        } else {
+            let position = self
+                .model
+                .clamp_to_bounds(self.model.position + self.model.velocity * dt);
+
+            if position != self.model.position {
+                self.model.position = position;
+                self.is_model_dirty = true;
+            }
+        }
+
+        // Pan
+        if pan != Vec2::ZERO {
+            self.model.position = self
+                .model
+                .clamp_to_bounds(self.model.position + self.pan_offset(pan));
             self.is_model_dirty = true;
         }
 
-        if input.key_held(KeyCode::Space) {
-            self.model.position += CameraModel::UP * self.model.speed * dt;
+        // Rotation
+        self.update_rotation(dt, look, gamepad);
+
+        // Zoom
+        self.update_zoom(scroll);
+    }
+
+    fn update_orbit(
+        &mut self,
+        dt: f32,
+        look: Vec2,
+        scroll: f32,
+        pan: Vec2,
+        gamepad: &GamepadInput,
+    ) {
+        // Azimuth and elevation
+        self.update_rotation(dt, look, gamepad);
+
+        // Target
+        if pan != Vec2::ZERO {
+            let offset = self.pan_offset(pan);
+            if let CameraControlMode::Orbit { target, .. } = &mut self.model.control_mode {
+                *target += offset;
+            }
             self.is_model_dirty = true;
-        } else if input.key_held(KeyCode::ShiftLeft) {
-            self.model.position -= CameraModel::UP * self.model.speed * dt;
+        }
+
+        // Distance
+        if scroll != 0.0 {
+            if let CameraControlMode::Orbit { distance, .. } = &mut self.model.control_mode {
+                *distance = (*distance - scroll * self.model.speed).max(Self::ORBIT_MIN_DISTANCE);
+            }
             self.is_model_dirty = true;
         }
 
-        // Rotation
-        if input.mouse_diff() != (0.0, 0.0) {
-            let pitch_delta = input.mouse_diff().1.to_radians() * self.model.mouse_sensitivity;
-            let yaw_delta = input.mouse_diff().0.to_radians() * self.model.mouse_sensitivity;
+        if self.is_model_dirty {
+            self.model.update_orbit_position();
+            self.model.position = self.model.clamp_to_bounds(self.model.position);
+        }
+    }
+
+    fn update_rotation(&mut self, dt: f32, look: Vec2, gamepad: &GamepadInput) {
+        let pitch_sign = match self.model.invert_y {
+            true => -1.0,
+            false => 1.0,
+        };
+
+        if look != Vec2::ZERO {
+            let pitch_delta = pitch_sign * look.y.to_radians() * self.model.sensitivity_y;
+            let yaw_delta = look.x.to_radians() * self.model.sensitivity_x;
 
-            self.model.pitch =
-                (self.model.pitch - pitch_delta).clamp(-Self::PITCH_LIMIT, Self::PITCH_LIMIT);
-            self.model.yaw = (self.model.yaw - yaw_delta).rem_euclid(2.0 * std::f32::consts::PI);
+            self.rotate(pitch_delta, yaw_delta);
+        }
+
+        let right_stick = gamepad.right_stick();
+        if right_stick != Vec2::ZERO {
+            let pitch_delta =
+                -pitch_sign * right_stick.y.to_radians() * self.model.gamepad_sensitivity * dt;
+            let yaw_delta = right_stick.x.to_radians() * self.model.gamepad_sensitivity * dt;
+
+            self.rotate(pitch_delta, yaw_delta);
+        }
+    }
+
+    /// Rotate by the deltas in the same direction as the mouse movement.
+    fn rotate(&mut self, pitch_delta: f32, yaw_delta: f32) {
+        let (yaw, pitch, roll) = self.model.euler();
+        self.model.set_euler(
+            (yaw - yaw_delta).rem_euclid(2.0 * std::f32::consts::PI),
+            (pitch - pitch_delta).clamp(-Self::PITCH_LIMIT, Self::PITCH_LIMIT),
+            roll,
+        );
+
+        self.is_model_dirty = true;
+    }
+
+    /// The offset of panning by `pan` pixels, moving the view with the fingers.
+    fn pan_offset(&self, pan: Vec2) -> Vec3 {
+        (-self.model.right() * pan.x + self.model.up() * pan.y) * Self::PAN_SCALE
+    }
+
+    /// Multiply the speed by [`CameraModel::speed_factor`] to the power of
+    /// `steps`, clamped to the speed range.
+    ///
+    /// The speed is not part of the uniform, so nothing is marked dirty, but
+    /// the model is still signalled so the UI keeps the new speed.
+    fn update_speed(&mut self, steps: f32) {
+        let speed = self.model.speed * self.model.speed_factor.powf(steps);
+        self.model.speed = speed.min(self.model.max_speed).max(self.model.min_speed);
+        self.is_signal_pending = true;
+        log::debug!("Camera speed changed to {}", self.model.speed);
+    }
+
+    fn update_zoom(&mut self, scroll: f32) {
+        if scroll == 0.0 || self.is_fov_animating() {
+            return;
+        }
 
+        // Zooming only changes the field of view of a perspective projection
+        if let ProjectionKind::Perspective { vertical_fov } = &mut self.model.projection {
+            *vertical_fov = (*vertical_fov - scroll.to_radians() * self.model.zoom_sensitivity)
+                .clamp(Self::MIN_VERTICAL_FOV, Self::MAX_VERTICAL_FOV);
             self.is_model_dirty = true;
         }
     }
 
-    pub fn render(&mut self, queue: &wgpu::Queue, aspect_ratio: f32, input: &WinitInputHelper) {
-        if self.is_model_dirty || input.window_resized().is_some() {
-            queue.write_buffer(
+    fn update_fov_animation(&mut self, dt: f32) {
+        let animation = match self.fov_animation.as_mut() {
+            Some(animation) => animation,
+            None => return,
+        };
+
+        let vertical_fov = match &mut self.model.projection {
+            ProjectionKind::Perspective { vertical_fov } => vertical_fov,
+            ProjectionKind::Orthographic { .. } => {
+                self.fov_animation = None;
+                return;
+            }
+        };
+
+        animation.elapsed += dt;
+        let t = match animation.duration {
+            duration if duration > 0.0 => (animation.elapsed / duration).min(1.0),
+            _ => 1.0,
+        };
+
+        *vertical_fov = animation.from + (animation.to - animation.from) * t;
+        self.is_model_dirty = true;
+
+        if t >= 1.0 {
+            self.fov_animation = None;
+        }
+    }
+
+    /// The minimum seconds between each [`CameraMovedSignal`].
+    pub fn signal_interval(&self) -> f32 {
+        self.signal_interval
+    }
+
+    pub fn set_signal_interval(&mut self, signal_interval: f32) {
+        self.signal_interval = signal_interval.max(0.0);
+    }
+
+    /// Send the model when it changed, at most once every
+    /// [`Camera::signal_interval`].
+    ///
+    /// This must be called before [`Camera::render`] which clears the dirty
+    /// flag. The last change is always sent once the interval has passed, so
+    /// the receiver ends up with the final pose.
+    pub fn signal(&mut self, dt: f32, tx: &mpsc::Sender<EngineOutSignal>) {
+        self.is_signal_pending |= self.is_model_dirty;
+        self.signal_timer += dt;
+
+        if self.is_signal_pending && self.signal_timer >= self.signal_interval {
+            tx.send(CameraMovedSignal::out_signal(self.model.clone()))
+                .unwrap();
+            self.is_signal_pending = false;
+            self.signal_timer = 0.0;
+        }
+    }
+
+    /// Upload the model through the staging belt of the display, also when the
+    /// aspect ratio changed, which happens on both resizes and scale factor
+    /// changes.
+    pub fn render(&mut self, display: &Display, aspect_ratio: f32) {
+        if self.is_model_dirty || self.aspect_ratio != aspect_ratio {
+            display.write_buffer(
                 &self.model_buffer,
                 0,
                 self.model.buffer(aspect_ratio).as_bytes(),
             );
+            self.aspect_ratio = aspect_ratio;
             self.is_model_dirty = false;
         }
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CameraModel {
     pub position: Vec3,
-    pub pitch: f32,
-    pub yaw: f32,
-    pub vertical_fov: f32,
+    /// The orientation relative to [`CameraModel::up_axis`], so the identity
+    /// looks forward on the horizontal plane whichever axis is up.
+    pub orientation: Quat,
+    /// The world axis pointing up, which yaw rotates around.
+    pub up_axis: UpAxis,
+    pub projection: ProjectionKind,
+    pub control_mode: CameraControlMode,
     pub z_near: f32,
     pub z_far: f32,
+    pub velocity: Vec3,
     pub speed: f32,
-    pub mouse_sensitivity: f32,
+    /// The factor the speed is multiplied or divided by for each step of
+    /// [`Camera::ACTION_SPEED_UP`] and [`Camera::ACTION_SPEED_DOWN`].
+    pub speed_factor: f32,
+    pub min_speed: f32,
+    pub max_speed: f32,
+    pub acceleration: f32,
+    pub damping: f32,
+    /// The horizontal look sensitivity in degrees per pixel.
+    pub sensitivity_x: f32,
+    /// The vertical look sensitivity in degrees per pixel.
+    pub sensitivity_y: f32,
+    /// Whether moving the mouse up looks down.
+    pub invert_y: bool,
+    pub gamepad_sensitivity: f32,
+    pub zoom_sensitivity: f32,
+    pub bounds: Option<(Vec3, Vec3)>,
 }
 
 impl CameraModel {
+    /// The directions of the identity orientation before
+    /// [`UpAxis::basis`].
     const FORWARD: Vec3 = Vec3::NEG_Z;
     const UP: Vec3 = Vec3::Y;
 
+    /// The yaw, pitch, and roll of the orientation.
+    ///
+    /// Yaw is applied first, then pitch, then roll around the camera's own
+    /// forward axis.
+    pub fn euler(&self) -> (f32, f32, f32) {
+        self.orientation.to_euler(EulerRot::YXZ)
+    }
+
+    /// Set the orientation from yaw, pitch, and roll, see
+    /// [`CameraModel::euler`].
+    pub fn set_euler(&mut self, yaw: f32, pitch: f32, roll: f32) {
+        self.orientation = Quat::from_euler(EulerRot::YXZ, yaw, pitch, roll);
+    }
+
+    pub fn yaw(&self) -> f32 {
+        self.euler().0
+    }
+
+    pub fn set_yaw(&mut self, yaw: f32) {
+        let (_, pitch, roll) = self.euler();
+        self.set_euler(yaw, pitch, roll);
+    }
+
+    pub fn pitch(&self) -> f32 {
+        self.euler().1
+    }
+
+    pub fn set_pitch(&mut self, pitch: f32) {
+        let (yaw, _, roll) = self.euler();
+        self.set_euler(yaw, pitch, roll);
+    }
+
+    pub fn roll(&self) -> f32 {
+        self.euler().2
+    }
+
+    pub fn set_roll(&mut self, roll: f32) {
+        let (yaw, pitch, _) = self.euler();
+        self.set_euler(yaw, pitch, roll);
+    }
+
     pub fn forward(&self) -> Vec3 {
-        Quat::from_euler(EulerRot::ZYX, 0.0, self.yaw, self.pitch) * Self::FORWARD
+        self.up_axis.basis() * self.orientation * Self::FORWARD
     }
 
     pub fn right(&self) -> Vec3 {
-        self.forward().cross(Self::UP).normalize()
+        self.up_axis.basis() * self.orientation * Self::FORWARD.cross(Self::UP)
+    }
+
+    pub fn up(&self) -> Vec3 {
+        self.up_axis.basis() * self.orientation * Self::UP
+    }
+
+    /// The forward direction on the horizontal plane, only affected by yaw.
+    ///
+    /// This is used for movement, so pitch and roll do not tilt the movement
+    /// plane.
+    pub fn horizontal_forward(&self) -> Vec3 {
+        self.up_axis.basis() * Quat::from_rotation_y(self.yaw()) * Self::FORWARD
+    }
+
+    /// The right direction on the horizontal plane, only affected by yaw.
+    pub fn horizontal_right(&self) -> Vec3 {
+        self.up_axis.basis() * Quat::from_rotation_y(self.yaw()) * Self::FORWARD.cross(Self::UP)
     }
 
     pub fn view_matrix(&self) -> Mat4 {
-        Mat4::look_to_rh(self.position, self.forward(), Self::UP)
+        Mat4::look_to_rh(self.position, self.forward(), self.up())
     }
 
     pub fn projection_matrix(&self, aspect_ratio: f32) -> Mat4 {
-        Mat4::perspective_rh(self.vertical_fov, aspect_ratio, self.z_near, self.z_far)
+        match self.projection {
+            ProjectionKind::Perspective { vertical_fov } => {
+                Mat4::perspective_rh(vertical_fov, aspect_ratio, self.z_near, self.z_far)
+            }
+            ProjectionKind::Orthographic { height } => {
+                let half_height = height * 0.5;
+                let half_width = half_height * aspect_ratio;
+                Mat4::orthographic_rh(
+                    -half_width,
+                    half_width,
+                    -half_height,
+                    half_height,
+                    self.z_near,
+                    self.z_far,
+                )
+            }
+        }
+    }
+
+    /// The six frustum planes in the order left, right, top, bottom, near, far.
+    pub fn frustum_planes(&self, aspect_ratio: f32) -> [Vec4; 6] {
+        self.frustum(aspect_ratio).planes
+    }
+
+    pub fn frustum(&self, aspect_ratio: f32) -> Frustum {
+        Frustum::from_view_projection(self.projection_matrix(aspect_ratio) * self.view_matrix())
+    }
+
+    /// Point the camera at the target by setting pitch and yaw.
+    ///
+    /// The orientation is left unchanged if the target is at the position, and
+    /// yaw is left unchanged if the target is directly above or below.
+    pub fn look_at(&mut self, target: Vec3) {
+        // The angles are relative to the up axis
+        let direction = match (target - self.position).try_normalize() {
+            Some(direction) => self.up_axis.basis().inverse() * direction,
+            None => return,
+        };
+
+        let (mut yaw, _, roll) = self.euler();
+        let pitch = direction
+            .y
+            .asin()
+            .clamp(-Camera::PITCH_LIMIT, Camera::PITCH_LIMIT);
+
+        if direction.x.abs() > f32::EPSILON || direction.z.abs() > f32::EPSILON {
+            yaw = (-direction.x).atan2(-direction.z);
+        }
+
+        self.set_euler(yaw, pitch, roll);
+    }
+
+    /// Interpolate between two poses.
+    ///
+    /// The position is linearly interpolated and the orientation is spherically
+    /// interpolated, the rest of the model is taken from `self`.
+    pub fn interpolate(&self, other: &CameraModel, t: f32) -> CameraModel {
+        CameraModel {
+            position: self.position.lerp(other.position, t),
+            orientation: self.orientation.slerp(other.orientation, t),
+            ..self.clone()
+        }
+    }
+
+    /// Clamp the position component-wise into the bounds, if there are any.
+    pub fn clamp_to_bounds(&self, position: Vec3) -> Vec3 {
+        match self.bounds {
+            Some((min, max)) => position.clamp(min, max),
+            None => position,
+        }
+    }
+
+    /// Switch between fly and orbit mode.
+    ///
+    /// Flying switches to orbiting the point
+    /// [`CameraControlMode::DEFAULT_ORBIT_DISTANCE`] in front of the
+    /// camera, so it does not jump.
+    pub fn toggle_control_mode(&mut self) {
+        self.control_mode = match self.control_mode {
+            CameraControlMode::Fly => CameraControlMode::Orbit {
+                target: self.position + self.forward() * CameraControlMode::DEFAULT_ORBIT_DISTANCE,
+                distance: CameraControlMode::DEFAULT_ORBIT_DISTANCE,
+            },
+            CameraControlMode::Orbit { .. } => CameraControlMode::Fly,
+        };
+        self.velocity = Vec3::ZERO;
+    }
+
+    /// Move the camera onto its orbit around the target.
+    ///
+    /// The camera is placed so that [`CameraModel::forward`] points toward the
+    /// target. This does nothing in [`CameraControlMode::Fly`].
+    pub fn update_orbit_position(&mut self) {
+        if let CameraControlMode::Orbit { target, distance } = self.control_mode {
+            self.position = target - self.forward() * distance;
+        }
     }
 
     fn buffer(&self, aspect_ratio: f32) -> CameraModelBuffer {
-        CameraModelBuffer::new(self.projection_matrix(aspect_ratio) * self.view_matrix())
+        CameraModelBuffer::new(
+            self.view_matrix(),
+            self.projection_matrix(aspect_ratio),
+            self.position,
+        )
     }
 }
 
@@ -174,27 +932,201 @@ impl Default for CameraModel {
     fn default() -> Self {
         Self {
             position: vec3(0.0, 0.5, 5.0),
-            pitch: 0.0,
-            yaw: 0.0,
-            vertical_fov: 60f32.to_radians(),
+            orientation: Quat::IDENTITY,
+            up_axis: UpAxis::default(),
+            projection: ProjectionKind::default(),
+            control_mode: CameraControlMode::default(),
             z_near: 1e-3,
             z_far: 1e3,
+            velocity: Vec3::ZERO,
             speed: 1.0,
-            mouse_sensitivity: 0.1,
+            speed_factor: 1.25,
+            min_speed: 0.1,
+            max_speed: 100.0,
+            acceleration: 10.0,
+            damping: 5.0,
+            sensitivity_x: 0.1,
+            sensitivity_y: 0.1,
+            invert_y: false,
+            gamepad_sensitivity: 180.0,
+            zoom_sensitivity: 1.0,
+            bounds: None,
+        }
+    }
+}
+
+/// The world axis pointing up for the camera.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum UpAxis {
+    /// Y is up and the camera looks along -Z by default, as in glTF.
+    #[default]
+    Y,
+    /// Z is up and the camera looks along +Y by default, as in Blender and
+    /// most CAD tools.
+    Z,
+}
+
+impl UpAxis {
+    pub fn vector(&self) -> Vec3 {
+        match self {
+            Self::Y => Vec3::Y,
+            Self::Z => Vec3::Z,
+        }
+    }
+
+    /// The rotation from the Y up frame of [`CameraModel::orientation`] to
+    /// the world.
+    pub fn basis(&self) -> Quat {
+        match self {
+            Self::Y => Quat::IDENTITY,
+            Self::Z => Quat::from_rotation_x(std::f32::consts::FRAC_PI_2),
+        }
+    }
+}
+
+/// The projection of the camera.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ProjectionKind {
+    /// Perspective projection with the vertical field of view in radians.
+    Perspective { vertical_fov: f32 },
+    /// Orthographic projection with the height of the view volume in world
+    /// units.
+    Orthographic { height: f32 },
+}
+
+impl Default for ProjectionKind {
+    fn default() -> Self {
+        Self::Perspective {
+            vertical_fov: 60f32.to_radians(),
         }
     }
 }
 
+/// The control mode of the camera.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CameraControlMode {
+    /// Free flying with keyboard movement and mouse look.
+    #[default]
+    Fly,
+    /// Orbiting around the target at a distance.
+    ///
+    /// The mouse controls the azimuth and elevation, and the scroll wheel
+    /// controls the distance.
+    Orbit { target: Vec3, distance: f32 },
+}
+
+impl CameraControlMode {
+    pub const DEFAULT_ORBIT_DISTANCE: f32 = 5.0;
+}
+
+/// A recorded path of the camera, see [`Camera::start_recording`] and
+/// [`Camera::play`].
+///
+/// With the `serde` feature, this can be (de)serialized to save the track.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CameraTrack {
+    /// The samples in ascending order of time.
+    pub samples: Vec<CameraTrackSample>,
+}
+
+impl CameraTrack {
+    /// The time of the last sample in seconds.
+    pub fn duration(&self) -> f32 {
+        self.samples.last().map_or(0.0, |sample| sample.time)
+    }
+
+    /// The model at `time` in seconds, [`None`] if the track is empty.
+    ///
+    /// The samples around the time are interpolated with
+    /// [`CameraModel::interpolate`], and the time is clamped to the first and
+    /// last samples.
+    pub fn sample(&self, time: f32) -> Option<CameraModel> {
+        let next = self.samples.partition_point(|sample| sample.time <= time);
+        match (next.checked_sub(1), self.samples.get(next)) {
+            (Some(i), Some(to)) => {
+                let from = &self.samples[i];
+                let t = (time - from.time) / (to.time - from.time);
+                Some(from.model.interpolate(&to.model, t))
+            }
+            (Some(i), None) => Some(self.samples[i].model.clone()),
+            (None, _) => self.samples.first().map(|sample| sample.model.clone()),
+        }
+    }
+}
+
+/// A model of the camera at a time of a [`CameraTrack`].
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CameraTrackSample {
+    /// The seconds since the start of the track.
+    pub time: f32,
+    pub model: CameraModel,
+}
+
+/// An ongoing recording of a [`CameraTrack`].
+#[derive(Debug, Clone)]
+struct CameraRecording {
+    track: CameraTrack,
+    elapsed: f32,
+    /// The elapsed seconds to take the next sample at.
+    next_sample: f32,
+}
+
+/// An ongoing playback of a [`CameraTrack`].
+#[derive(Debug, Clone)]
+struct CameraPlayback {
+    track: CameraTrack,
+    elapsed: f32,
+}
+
+/// An ongoing animation of the vertical field of view.
+#[derive(Debug, Clone, Copy)]
+struct FovAnimation {
+    from: f32,
+    to: f32,
+    duration: f32,
+    elapsed: f32,
+}
+
 /// Camera model buffer.
+///
+/// This must be kept in sync with the `Camera` struct in the shaders. The
+/// position is stored as a [`Vec4`] with `w = 1.0` to keep the struct 16-byte
+/// aligned.
 #[repr(C)]
 #[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
 struct CameraModelBuffer {
+    view: Mat4,
+    projection: Mat4,
     view_projection: Mat4,
+    inverse_view_projection: Mat4,
+    position: Vec4,
 }
 
 impl CameraModelBuffer {
-    fn new(view_projection: Mat4) -> Self {
-        Self { view_projection }
+    fn new(view: Mat4, projection: Mat4, position: Vec3) -> Self {
+        let view_projection = projection * view;
+        // The determinant of a valid camera can be tiny, e.g. a large orthographic
+        // height, so only a singular matrix with a non-finite inverse is rejected
+        let inverse_view_projection = match view_projection.inverse() {
+            inverse if inverse.is_finite() => inverse,
+            _ => {
+                log::warn!("Camera view projection matrix is not invertible, using identity");
+                Mat4::IDENTITY
+            }
+        };
+
+        Self {
+            view,
+            projection,
+            view_projection,
+            inverse_view_projection,
+            position: position.extend(1.0),
+        }
     }
 
     fn as_bytes(&self) -> &[u8] {
@@ -207,6 +1139,8 @@ pub struct CameraBuilder<T, U> {
     device: T,
     aspect_ratio: U,
     model: CameraModel,
+    look_at: Option<Vec3>,
+    signal_interval: f32,
 }
 
 pub mod builder {
@@ -223,6 +1157,8 @@ impl CameraBuilder<builder::NoDevice, builder::NoAspectRatio> {
             device: builder::NoDevice,
             aspect_ratio: builder::NoAspectRatio,
             model: CameraModel::default(),
+            look_at: None,
+            signal_interval: Camera::DEFAULT_SIGNAL_INTERVAL,
         }
     }
 }
@@ -233,6 +1169,8 @@ impl<T, U> CameraBuilder<T, U> {
             device: builder::WithDevice(device),
             aspect_ratio: self.aspect_ratio,
             model: self.model,
+            look_at: self.look_at,
+            signal_interval: self.signal_interval,
         }
     }
 
@@ -244,6 +1182,8 @@ impl<T, U> CameraBuilder<T, U> {
             device: self.device,
             aspect_ratio: builder::WithAspectRatio(aspect_ratio),
             model: self.model,
+            look_at: self.look_at,
+            signal_interval: self.signal_interval,
         }
     }
 
@@ -257,18 +1197,81 @@ impl<T, U> CameraBuilder<T, U> {
         self
     }
 
+    /// Point the camera at the target when built, after the position is set.
+    pub fn looking_at(mut self, target: Vec3) -> Self {
+        self.look_at = Some(target);
+        self
+    }
+
+    /// Set the world axis pointing up, defaults to [`UpAxis::Y`].
+    pub fn with_up_axis(mut self, up_axis: UpAxis) -> Self {
+        self.model.up_axis = up_axis;
+        self
+    }
+
+    pub fn with_orientation(mut self, orientation: Quat) -> Self {
+        self.model.orientation = orientation;
+        self
+    }
+
     pub fn with_pitch(mut self, pitch: f32) -> Self {
-        self.model.pitch = pitch;
+        self.model.set_pitch(pitch);
         self
     }
 
     pub fn with_yaw(mut self, yaw: f32) -> Self {
-        self.model.yaw = yaw;
+        self.model.set_yaw(yaw);
+        self
+    }
+
+    pub fn with_projection(mut self, projection: ProjectionKind) -> Self {
+        self.model.projection = projection;
+        self
+    }
+
+    pub fn with_roll(mut self, roll: f32) -> Self {
+        self.model.set_roll(roll);
         self
     }
 
     pub fn with_vertical_fov(mut self, vertical_fov: f32) -> Self {
-        self.model.vertical_fov = vertical_fov;
+        self.model.projection = ProjectionKind::Perspective { vertical_fov };
+        self
+    }
+
+    pub fn with_orthographic(mut self, height: f32) -> Self {
+        self.model.projection = ProjectionKind::Orthographic { height };
+        self
+    }
+
+    pub fn with_control_mode(mut self, control_mode: CameraControlMode) -> Self {
+        self.model.control_mode = control_mode;
+        self
+    }
+
+    pub fn with_orbit_target(mut self, target: Vec3) -> Self {
+        self.model.control_mode = match self.model.control_mode {
+            CameraControlMode::Orbit { distance, .. } => {
+                CameraControlMode::Orbit { target, distance }
+            }
+            CameraControlMode::Fly => CameraControlMode::Orbit {
+                target,
+                distance: CameraControlMode::DEFAULT_ORBIT_DISTANCE,
+            },
+        };
+        self
+    }
+
+    pub fn with_orbit_distance(mut self, distance: f32) -> Self {
+        self.model.control_mode = match self.model.control_mode {
+            CameraControlMode::Orbit { target, .. } => {
+                CameraControlMode::Orbit { target, distance }
+            }
+            CameraControlMode::Fly => CameraControlMode::Orbit {
+                target: Vec3::ZERO,
+                distance,
+            },
+        };
         self
     }
 
@@ -287,14 +1290,207 @@ impl<T, U> CameraBuilder<T, U> {
         self
     }
 
+    /// Set the factor of each speed step, defaults to 1.25.
+    pub fn with_speed_factor(mut self, speed_factor: f32) -> Self {
+        self.model.speed_factor = speed_factor;
+        self
+    }
+
+    /// Set the range the speed steps are clamped to, defaults to 0.1 to 100.
+    pub fn with_speed_range(mut self, min_speed: f32, max_speed: f32) -> Self {
+        self.model.min_speed = min_speed;
+        self.model.max_speed = max_speed;
+        self
+    }
+
+    pub fn with_acceleration(mut self, acceleration: f32) -> Self {
+        self.model.acceleration = acceleration;
+        self
+    }
+
+    pub fn with_damping(mut self, damping: f32) -> Self {
+        self.model.damping = damping;
+        self
+    }
+
+    /// Set both [`CameraModel::sensitivity_x`] and
+    /// [`CameraModel::sensitivity_y`].
     pub fn with_mouse_sensitivity(mut self, mouse_sensitivity: f32) -> Self {
-        self.model.mouse_sensitivity = mouse_sensitivity;
+        self.model.sensitivity_x = mouse_sensitivity;
+        self.model.sensitivity_y = mouse_sensitivity;
+        self
+    }
+
+    pub fn with_sensitivity_x(mut self, sensitivity_x: f32) -> Self {
+        self.model.sensitivity_x = sensitivity_x;
+        self
+    }
+
+    pub fn with_sensitivity_y(mut self, sensitivity_y: f32) -> Self {
+        self.model.sensitivity_y = sensitivity_y;
+        self
+    }
+
+    pub fn with_invert_y(mut self, invert_y: bool) -> Self {
+        self.model.invert_y = invert_y;
+        self
+    }
+
+    pub fn with_gamepad_sensitivity(mut self, gamepad_sensitivity: f32) -> Self {
+        self.model.gamepad_sensitivity = gamepad_sensitivity;
+        self
+    }
+
+    pub fn with_zoom_sensitivity(mut self, zoom_sensitivity: f32) -> Self {
+        self.model.zoom_sensitivity = zoom_sensitivity;
+        self
+    }
+
+    pub fn with_bounds(mut self, min: Vec3, max: Vec3) -> Self {
+        self.model.bounds = Some((min, max));
+        self
+    }
+
+    /// Set the minimum seconds between each [`CameraMovedSignal`], defaults
+    /// to [`Camera::DEFAULT_SIGNAL_INTERVAL`].
+    pub fn with_signal_interval(mut self, signal_interval: f32) -> Self {
+        self.signal_interval = signal_interval.max(0.0);
         self
     }
 }
 
 impl<'a> CameraBuilder<builder::WithDevice<'a>, builder::WithAspectRatio> {
-    pub fn build(self) -> Camera {
-        Camera::new(self.device.0, self.aspect_ratio.0, self.model)
+    pub fn build(mut self) -> Camera {
+        if let Some(target) = self.look_at {
+            self.model.look_at(target);
+        }
+
+        Camera::new(
+            self.device.0,
+            self.aspect_ratio.0,
+            self.model,
+            self.signal_interval,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::systems::handlers;
+
+    #[test]
+    fn near_plane_maps_to_zero_depth() {
+        for projection in [
+            ProjectionKind::Perspective {
+                vertical_fov: 60f32.to_radians(),
+            },
+            ProjectionKind::Orthographic { height: 10.0 },
+        ] {
+            let model = CameraModel {
+                projection,
+                ..Default::default()
+            };
+            let matrix = model.projection_matrix(1.5);
+
+            let near = matrix.project_point3(vec3(0.0, 0.0, -model.z_near));
+            assert!(
+                (near.z - 0.0).abs() < 1e-5,
+                "{projection:?} near depth {}",
+                near.z
+            );
+
+            let far = matrix.project_point3(vec3(0.0, 0.0, -model.z_far));
+            assert!(
+                (far.z - 1.0).abs() < 1e-5,
+                "{projection:?} far depth {}",
+                far.z
+            );
+        }
+    }
+
+    #[test]
+    fn large_orthographic_height_is_invertible() {
+        let model = CameraModel {
+            projection: ProjectionKind::Orthographic { height: 2000.0 },
+            ..Default::default()
+        };
+        let buffer = CameraModelBuffer::new(
+            model.view_matrix(),
+            model.projection_matrix(1.0),
+            model.position,
+        );
+
+        let identity = buffer.view_projection * buffer.inverse_view_projection;
+        assert!(identity.abs_diff_eq(Mat4::IDENTITY, 1e-4));
+    }
+
+    #[test]
+    fn disabled_update_does_not_move() {
+        let Some((device, _)) = handlers::test_device() else {
+            return;
+        };
+        let mut camera = CameraBuilder::new()
+            .with_device(&device)
+            .with_aspect_ratio(1.0)
+            .with_model(CameraModel {
+                velocity: Vec3::X,
+                ..Default::default()
+            })
+            .build();
+        let position = camera.model().position;
+
+        let input = WinitInputHelper::new();
+        let actions = ActionMap::new();
+        let touch = TouchGestures::default();
+        let gamepad = GamepadInput::new();
+        let update = |camera: &mut Camera| {
+            camera.update(0.1, &input, &actions, Vec2::ZERO, &touch, &gamepad)
+        };
+
+        camera.set_enabled(false);
+        update(&mut camera);
+        assert_eq!(camera.model().position, position);
+
+        camera.set_enabled(true);
+        update(&mut camera);
+        assert_ne!(camera.model().position, position);
+    }
+
+    #[test]
+    fn identity_orientation_looks_forward_on_the_horizontal_plane() {
+        for (up_axis, forward) in [(UpAxis::Y, Vec3::NEG_Z), (UpAxis::Z, Vec3::Y)] {
+            let model = CameraModel {
+                orientation: Quat::IDENTITY,
+                up_axis,
+                ..Default::default()
+            };
+
+            assert!(model.forward().abs_diff_eq(forward, 1e-6));
+            assert!(model.up().abs_diff_eq(up_axis.vector(), 1e-6));
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn model_round_trips_through_json() {
+        let model = CameraModel {
+            position: vec3(1.0, 2.0, 3.0),
+            orientation: Quat::from_rotation_y(0.5),
+            up_axis: UpAxis::Z,
+            projection: ProjectionKind::Orthographic { height: 10.0 },
+            control_mode: CameraControlMode::Orbit {
+                target: Vec3::ZERO,
+                distance: 4.0,
+            },
+            speed: 2.5,
+            invert_y: true,
+            bounds: Some((Vec3::splat(-5.0), Vec3::splat(5.0))),
+            ..Default::default()
+        };
+
+        let json = serde_json::to_string(&model).unwrap();
+        let deserialized = serde_json::from_str::<CameraModel>(&json).unwrap();
+        assert_eq!(deserialized, model);
     }
 }
@@ -0,0 +1,307 @@
+use crate::systems::{
+    self,
+    handlers::{Display, Pyramid},
+    ShaderError,
+};
+
+/// Handler for the compute vertex animation of the pyramid.
+///
+/// The vertex buffer of the pyramid is bound as the read only rest pose, and
+/// the deformed vertices are written to a separate buffer, so the deformation
+/// does not accumulate over the frames. Render the pyramid with
+/// [`Compute::vertex_buffer`] to draw the deformed vertices.
+///
+/// The normals are not recomputed, so the lighting follows the rest pose.
+pub struct Compute {
+    params: ComputeParams,
+    is_enabled: bool,
+    time: f32,
+
+    params_buffer: wgpu::Buffer,
+    deformed_buffer: wgpu::Buffer,
+
+    bind_group: wgpu::BindGroup,
+    pipeline: wgpu::ComputePipeline,
+}
+
+impl Compute {
+    pub const WORKGROUP_SIZE: u32 = 64;
+
+    pub fn new(
+        device: &wgpu::Device,
+        pyramid: &Pyramid,
+        params: ComputeParams,
+        is_enabled: bool,
+    ) -> Result<Self, ShaderError> {
+        log::debug!("Creating compute params buffer");
+        let params_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Compute Params Buffer"),
+            size: std::mem::size_of::<ComputeParamsBuffer>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        log::debug!("Creating compute deformed vertex buffer");
+        let deformed_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Compute Deformed Vertex Buffer"),
+            size: pyramid.model_buffer().size(),
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::VERTEX,
+            mapped_at_creation: false,
+        });
+
+        log::debug!("Creating compute bind group layout");
+        let storage_entry = |binding, read_only| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage { read_only },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Compute Bind Group Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                storage_entry(1, true),
+                storage_entry(2, false),
+            ],
+        });
+
+        log::debug!("Creating compute bind group");
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Compute Bind Group"),
+            layout: &bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: params_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: pyramid.model_buffer().as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: deformed_buffer.as_entire_binding(),
+                },
+            ],
+        });
+
+        log::debug!("Creating compute pipeline");
+        let shader = systems::create_shader_module(
+            device,
+            "pyramid_deform.wgsl",
+            include_str!("../shaders/pyramid_deform.wgsl"),
+        )?;
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Compute Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("Compute Pipeline"),
+            layout: Some(&pipeline_layout),
+            module: &shader,
+            entry_point: Some("compute_main"),
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+            cache: None,
+        });
+
+        log::info!("Compute handler initialized");
+
+        Ok(Self {
+            params,
+            is_enabled,
+            time: 0.0,
+
+            params_buffer,
+            deformed_buffer,
+
+            bind_group,
+            pipeline,
+        })
+    }
+
+    pub fn params(&self) -> &ComputeParams {
+        &self.params
+    }
+
+    pub fn set_params(&mut self, params: ComputeParams) {
+        self.params = params;
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.is_enabled
+    }
+
+    pub fn set_enabled(&mut self, is_enabled: bool) {
+        self.is_enabled = is_enabled;
+    }
+
+    /// The deformed vertices to render the pyramid with, [`None`] when
+    /// disabled to render the rest pose.
+    pub fn vertex_buffer(&self) -> Option<&wgpu::Buffer> {
+        self.is_enabled.then_some(&self.deformed_buffer)
+    }
+
+    /// Advance the animation.
+    pub fn update(&mut self, dt: f32) {
+        if self.is_enabled {
+            self.time += dt;
+        }
+    }
+
+    /// Dispatch the deformation of the vertices of `pyramid`.
+    ///
+    /// The buffers of `pyramid` should be written with
+    /// [`Pyramid::write_buffers`] first, so a new model is deformed in the same
+    /// frame. The pass is expected in a submission before the render pass
+    /// reading [`Compute::vertex_buffer`], wgpu then synchronizes the storage
+    /// writes with the vertex reads.
+    pub fn compute(&self, display: &Display, pass: &mut wgpu::ComputePass, pyramid: &Pyramid) {
+        if !self.is_enabled {
+            return;
+        }
+
+        let vertex_count = pyramid.vertex_count() as u32;
+        display.write_buffer(
+            &self.params_buffer,
+            0,
+            ComputeParamsBuffer::new(self.time, &self.params, vertex_count).as_bytes(),
+        );
+
+        pass.set_pipeline(&self.pipeline);
+        pass.set_bind_group(0, &self.bind_group, &[]);
+        pass.dispatch_workgroups(vertex_count.div_ceil(Self::WORKGROUP_SIZE), 1, 1);
+    }
+}
+
+/// The parameters of the sway of the pyramid.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ComputeParams {
+    /// The horizontal offset per unit of height.
+    pub amplitude: f32,
+    /// The phase difference per unit of height in radians.
+    pub frequency: f32,
+    /// The speed of the sway in radians per second.
+    pub speed: f32,
+}
+
+impl Default for ComputeParams {
+    fn default() -> Self {
+        Self {
+            amplitude: 0.1,
+            frequency: 4.0,
+            speed: 2.0,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct ComputeParamsBuffer {
+    time: f32,
+    amplitude: f32,
+    frequency: f32,
+    speed: f32,
+    vertex_count: u32,
+    vertex_stride: u32,
+    _padding: [u32; 2],
+}
+
+impl ComputeParamsBuffer {
+    fn new(time: f32, params: &ComputeParams, vertex_count: u32) -> Self {
+        Self {
+            time,
+            amplitude: params.amplitude,
+            frequency: params.frequency,
+            speed: params.speed,
+            vertex_count,
+            vertex_stride: (Pyramid::VERTEX_SIZE / std::mem::size_of::<f32>()) as u32,
+            _padding: [0; 2],
+        }
+    }
+
+    fn as_bytes(&self) -> &[u8] {
+        bytemuck::cast_slice(std::slice::from_ref(self))
+    }
+}
+
+/// Builder of [`Compute`].
+pub struct ComputeBuilder<T, U> {
+    device: T,
+    pyramid: U,
+    params: ComputeParams,
+    is_enabled: bool,
+}
+
+pub mod builder {
+    use crate::systems::handlers::Pyramid;
+
+    pub struct NoDevice;
+    pub struct WithDevice<'a>(pub &'a wgpu::Device);
+
+    pub struct NoPyramid;
+    pub struct WithPyramid<'a>(pub &'a Pyramid);
+}
+
+impl ComputeBuilder<builder::NoDevice, builder::NoPyramid> {
+    pub fn new() -> Self {
+        Self {
+            device: builder::NoDevice,
+            pyramid: builder::NoPyramid,
+            params: ComputeParams::default(),
+            is_enabled: false,
+        }
+    }
+}
+
+impl<T, U> ComputeBuilder<T, U> {
+    pub fn with_device(self, device: &wgpu::Device) -> ComputeBuilder<builder::WithDevice, U> {
+        ComputeBuilder {
+            device: builder::WithDevice(device),
+            pyramid: self.pyramid,
+            params: self.params,
+            is_enabled: self.is_enabled,
+        }
+    }
+
+    pub fn with_pyramid(self, pyramid: &Pyramid) -> ComputeBuilder<T, builder::WithPyramid> {
+        ComputeBuilder {
+            device: self.device,
+            pyramid: builder::WithPyramid(pyramid),
+            params: self.params,
+            is_enabled: self.is_enabled,
+        }
+    }
+
+    /// Set the parameters of the sway.
+    pub fn with_params(mut self, params: ComputeParams) -> Self {
+        self.params = params;
+        self
+    }
+
+    /// Set whether the animation is enabled, defaults to false.
+    pub fn with_enabled(mut self, is_enabled: bool) -> Self {
+        self.is_enabled = is_enabled;
+        self
+    }
+}
+
+impl ComputeBuilder<builder::WithDevice<'_>, builder::WithPyramid<'_>> {
+    /// Build the compute handler, fails if the shader fails to compile.
+    pub fn build(self) -> Result<Compute, ShaderError> {
+        Compute::new(self.device.0, self.pyramid.0, self.params, self.is_enabled)
+    }
+}
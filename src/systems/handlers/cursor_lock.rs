@@ -1,9 +1,10 @@
 use std::sync::Arc;
 
+use glam::*;
 use winit::{
     error::ExternalError,
-    event::{MouseButton, WindowEvent},
-    keyboard::KeyCode,
+    event::{DeviceEvent, ElementState, KeyEvent, MouseButton, WindowEvent},
+    keyboard::{KeyCode, PhysicalKey},
     window::{CursorGrabMode, Window},
 };
 use winit_input_helper::WinitInputHelper;
@@ -11,19 +12,63 @@ use winit_input_helper::WinitInputHelper;
 use crate::systems::Error;
 
 /// Handler for cursor locking.
+///
+/// The visibility of the cursor is independent of the lock, with one flag for
+/// each of the unlocked and locked state.
+///
+/// Clicking on the window locks the cursor, and the toggle key locks or
+/// releases it. Everything is driven from [`CursorLock::window_event`], since
+/// on the web the pointer lock is only granted from a user gesture.
+///
+/// While locked, the raw device motion is accumulated from
+/// [`CursorLock::device_event`], which is free of the pointer acceleration
+/// and not capped at the screen edges.
 pub struct CursorLock {
     window: Arc<Window>,
+    state: CursorLockState,
     should_lock_cursor: bool,
-    is_cursor_locked: bool,
+    grab_mode: CursorGrabMode,
+    is_cursor_visible: bool,
+    is_cursor_visible_when_locked: bool,
 }
 
 impl CursorLock {
-    pub fn new(window: Arc<Window>, should_lock_cursor: bool) -> Self {
+    pub fn new(
+        window: Arc<Window>,
+        should_lock_cursor: bool,
+        toggle_key: KeyCode,
+        is_cursor_visible: bool,
+        is_cursor_visible_when_locked: bool,
+        is_raw_motion: bool,
+    ) -> Self {
+        window.set_cursor_visible(is_cursor_visible);
+
         Self {
             window,
+            state: CursorLockState::new(toggle_key, is_raw_motion),
             should_lock_cursor,
-            is_cursor_locked: false,
+            grab_mode: CursorGrabMode::None,
+            is_cursor_visible,
+            is_cursor_visible_when_locked,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.state.is_enabled
+    }
+
+    /// Enable or disable the handler, e.g. while a UI modal needs the cursor.
+    ///
+    /// Disabling releases the lock, and a disabled handler ignores the events
+    /// and reports no motion.
+    pub fn set_enabled(&mut self, is_enabled: bool) -> Result<(), Error> {
+        self.state.set_enabled(is_enabled);
+
+        if !is_enabled {
+            self.release_lock()?;
         }
+
+        Ok(())
     }
 
     pub fn should_lock_cursor(&self) -> bool {
@@ -34,81 +79,263 @@ impl CursorLock {
         self.should_lock_cursor = should_lock_cursor;
 
         // Unlock cursor if should lock cursor is false and cursor is locked
-        if !should_lock_cursor && self.is_cursor_locked {
+        if !should_lock_cursor && self.state.is_cursor_locked {
             self.set_cursor_locked(false)?;
         }
 
         Ok(())
     }
 
+    pub fn toggle_key(&self) -> KeyCode {
+        self.state.toggle_key
+    }
+
+    pub fn set_toggle_key(&mut self, toggle_key: KeyCode) {
+        self.state.toggle_key = toggle_key;
+    }
+
     pub fn is_cursor_locked(&self) -> bool {
-        self.is_cursor_locked
+        self.state.is_cursor_locked
+    }
+
+    /// Lock the cursor, does nothing if it should not be locked.
+    ///
+    /// On the web, this must be called while handling a user gesture like a
+    /// click or key press, otherwise the browser rejects the pointer lock.
+    pub fn request_lock(&mut self) -> Result<(), Error> {
+        if !self.should_lock_cursor || self.state.is_cursor_locked {
+            return Ok(());
+        }
+
+        self.set_cursor_locked(true)
+    }
+
+    /// Release the cursor lock, restoring the unlocked cursor visibility.
+    pub fn release_lock(&mut self) -> Result<(), Error> {
+        if !self.state.is_cursor_locked {
+            return Ok(());
+        }
+
+        self.set_cursor_locked(false)
+    }
+
+    /// The grab mode of the lock.
+    ///
+    /// This is [`CursorGrabMode::Confined`] where locking is not supported, in
+    /// which the cursor can still move within the window, and
+    /// [`CursorGrabMode::None`] when unlocked.
+    pub fn grab_mode(&self) -> CursorGrabMode {
+        self.grab_mode
+    }
+
+    /// Whether the cursor is visible when unlocked.
+    pub fn is_cursor_visible(&self) -> bool {
+        self.is_cursor_visible
+    }
+
+    /// Show or hide the cursor when unlocked, which is restored when the lock
+    /// is released.
+    pub fn set_visible(&mut self, visible: bool) {
+        self.is_cursor_visible = visible;
+        self.apply_cursor_visible();
+    }
+
+    /// Whether the cursor is visible when locked.
+    pub fn is_cursor_visible_when_locked(&self) -> bool {
+        self.is_cursor_visible_when_locked
+    }
+
+    /// Show or hide the cursor when locked.
+    pub fn set_visible_when_locked(&mut self, visible: bool) {
+        self.is_cursor_visible_when_locked = visible;
+        self.apply_cursor_visible();
+    }
+
+    /// Whether the raw device motion is used when locked.
+    pub fn is_raw_motion(&self) -> bool {
+        self.state.is_raw_motion
+    }
+
+    pub fn set_raw_motion(&mut self, is_raw_motion: bool) {
+        self.state.is_raw_motion = is_raw_motion;
+    }
+
+    /// The mouse motion of the frame in pixels, zero when disabled.
+    ///
+    /// This is the raw device motion when locked and raw motion is enabled,
+    /// otherwise [`WinitInputHelper::mouse_diff`].
+    pub fn mouse_motion(&self, input: &WinitInputHelper) -> Vec2 {
+        self.state.mouse_motion(input)
     }
 
     /// Lock or unlock the cursor.
     fn set_cursor_locked(&mut self, locked: bool) -> Result<(), Error> {
         if locked {
-            match self.window.set_cursor_grab(CursorGrabMode::Locked) {
+            // Locked is not supported on Windows and X11, Confined is not
+            // supported on the web and macOS
+            self.grab_mode = match self.window.set_cursor_grab(CursorGrabMode::Locked) {
                 Err(ExternalError::NotSupported(_)) => {
                     self.window.set_cursor_grab(CursorGrabMode::Confined)?;
+                    CursorGrabMode::Confined
                 }
-                Ok(_) => {}
+                Ok(_) => CursorGrabMode::Locked,
                 Err(e) => return Err(Error::DisplayLockCursor(e)),
-            }
+            };
         } else {
             match self.window.set_cursor_grab(CursorGrabMode::None) {
                 Ok(_) => {}
                 Err(e) => return Err(Error::DisplayLockCursor(e)),
             }
+            self.grab_mode = CursorGrabMode::None;
         }
 
-        self.window.set_cursor_visible(!locked);
-
-        self.is_cursor_locked = locked;
+        self.state.is_cursor_locked = locked;
+        self.apply_cursor_visible();
         Ok(())
     }
 
+    /// Set the cursor visibility of the current lock state.
+    fn apply_cursor_visible(&self) {
+        self.window
+            .set_cursor_visible(match self.state.is_cursor_locked {
+                true => self.is_cursor_visible_when_locked,
+                false => self.is_cursor_visible,
+            });
+    }
+
     pub fn window_event(&mut self, event: &WindowEvent) {
-        if let WindowEvent::Focused(false) = event {
-            match self.set_cursor_locked(false) {
-                Ok(_) => {}
-                Err(e) => log::warn!("Unable to unlock cursor on window unfocused: {e:?}"),
-            }
+        let result = match self.state.window_event(event) {
+            Some(LockRequest::Lock) => self.request_lock(),
+            Some(LockRequest::Release) => self.release_lock(),
+            None => return,
+        };
+
+        if let Err(e) = result {
+            log::warn!("Unable to lock or release cursor on {event:?}: {e:?}");
         }
     }
 
-    pub fn update(&mut self, input: &mut WinitInputHelper) {
-        // Focus window
-        if !self.is_cursor_locked()
-            && self.should_lock_cursor()
-            && input.mouse_pressed(MouseButton::Left)
-            && input.cursor().is_some()
-        {
-            match self.set_cursor_locked(true) {
-                Ok(_) => {}
-                Err(e) => log::warn!("Unable to lock cursor on cursor grabbed: {e:?}"),
-            }
+    pub fn device_event(&mut self, event: &DeviceEvent) {
+        self.state.device_event(event);
+    }
+
+    /// Take the motion accumulated since the last frame.
+    pub fn update(&mut self) {
+        self.state.update();
+    }
+}
+
+/// The lock change requested by a window event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LockRequest {
+    Lock,
+    Release,
+}
+
+/// The state of [`CursorLock`] which does not need the window, so the event
+/// handling works without one.
+#[derive(Debug)]
+struct CursorLockState {
+    is_enabled: bool,
+    toggle_key: KeyCode,
+    is_cursor_locked: bool,
+    is_raw_motion: bool,
+    pending_motion: Vec2,
+    motion: Vec2,
+}
+
+impl CursorLockState {
+    fn new(toggle_key: KeyCode, is_raw_motion: bool) -> Self {
+        Self {
+            is_enabled: true,
+            toggle_key,
+            is_cursor_locked: false,
+            is_raw_motion,
+            pending_motion: Vec2::ZERO,
+            motion: Vec2::ZERO,
         }
+    }
 
-        // Manually unlock cursor
-        if self.is_cursor_locked()
-            && [KeyCode::Escape, KeyCode::Tab]
-                .into_iter()
-                .any(|k| input.key_pressed(k))
-        {
-            match self.set_cursor_locked(false) {
-                Ok(_) => {}
-                Err(e) => log::warn!("Unable to unlock cursor on escape pressed: {e:?}"),
+    fn set_enabled(&mut self, is_enabled: bool) {
+        self.is_enabled = is_enabled;
+
+        if !is_enabled {
+            self.pending_motion = Vec2::ZERO;
+            self.motion = Vec2::ZERO;
+        }
+    }
+
+    fn mouse_motion(&self, input: &WinitInputHelper) -> Vec2 {
+        if !self.is_enabled {
+            return Vec2::ZERO;
+        }
+
+        match self.is_raw_motion && self.is_cursor_locked {
+            true => self.motion,
+            false => Vec2::from(input.mouse_diff()),
+        }
+    }
+
+    fn window_event(&self, event: &WindowEvent) -> Option<LockRequest> {
+        if !self.is_enabled {
+            return None;
+        }
+
+        match event {
+            WindowEvent::Focused(false) => Some(LockRequest::Release),
+            // Click to lock
+            WindowEvent::MouseInput {
+                state: ElementState::Pressed,
+                button: MouseButton::Left,
+                ..
+            } => Some(LockRequest::Lock),
+            // Toggle key to lock or release
+            WindowEvent::KeyboardInput {
+                event:
+                    KeyEvent {
+                        physical_key: PhysicalKey::Code(code),
+                        state: ElementState::Pressed,
+                        repeat: false,
+                        ..
+                    },
+                ..
+            } if *code == self.toggle_key => match self.is_cursor_locked {
+                true => Some(LockRequest::Release),
+                false => Some(LockRequest::Lock),
+            },
+            _ => None,
+        }
+    }
+
+    fn device_event(&mut self, event: &DeviceEvent) {
+        if !self.is_enabled {
+            return;
+        }
+
+        if let DeviceEvent::MouseMotion { delta: (x, y) } = event {
+            if self.is_cursor_locked {
+                self.pending_motion += Vec2::new(*x as f32, *y as f32);
             }
         }
     }
+
+    fn update(&mut self) {
+        if !self.is_enabled {
+            return;
+        }
+
+        self.motion = std::mem::take(&mut self.pending_motion);
+    }
 }
 
 /// Builder of [`CursorLock`].
 pub struct CursorLockBuilder<T> {
     window: T,
     should_lock_cursor: bool,
-    is_cursor_locked: bool,
+    toggle_key: KeyCode,
+    is_cursor_visible: bool,
+    is_cursor_visible_when_locked: bool,
+    is_raw_motion: bool,
 }
 
 pub mod builder {
@@ -123,7 +350,10 @@ impl CursorLockBuilder<builder::NoWindow> {
         Self {
             window: builder::NoWindow,
             should_lock_cursor: false,
-            is_cursor_locked: false,
+            toggle_key: KeyCode::Escape,
+            is_cursor_visible: true,
+            is_cursor_visible_when_locked: false,
+            is_raw_motion: true,
         }
     }
 }
@@ -133,7 +363,10 @@ impl<T> CursorLockBuilder<T> {
         CursorLockBuilder {
             window: builder::WithWindow(window),
             should_lock_cursor: self.should_lock_cursor,
-            is_cursor_locked: self.is_cursor_locked,
+            toggle_key: self.toggle_key,
+            is_cursor_visible: self.is_cursor_visible,
+            is_cursor_visible_when_locked: self.is_cursor_visible_when_locked,
+            is_raw_motion: self.is_raw_motion,
         }
     }
 
@@ -141,10 +374,69 @@ impl<T> CursorLockBuilder<T> {
         self.should_lock_cursor = should_lock_cursor;
         self
     }
+
+    /// Set the key to lock or release the cursor, defaults to
+    /// [`KeyCode::Escape`].
+    pub fn with_toggle_key(mut self, toggle_key: KeyCode) -> Self {
+        self.toggle_key = toggle_key;
+        self
+    }
+
+    /// Set whether the cursor is visible when unlocked, defaults to true.
+    pub fn with_cursor_visible(mut self, is_cursor_visible: bool) -> Self {
+        self.is_cursor_visible = is_cursor_visible;
+        self
+    }
+
+    /// Set whether the cursor is visible when locked, defaults to false.
+    pub fn with_cursor_visible_when_locked(mut self, is_cursor_visible_when_locked: bool) -> Self {
+        self.is_cursor_visible_when_locked = is_cursor_visible_when_locked;
+        self
+    }
+
+    /// Set whether the raw device motion is used when locked, defaults to true.
+    pub fn with_raw_motion(mut self, is_raw_motion: bool) -> Self {
+        self.is_raw_motion = is_raw_motion;
+        self
+    }
 }
 
 impl CursorLockBuilder<builder::WithWindow> {
     pub fn build(self) -> CursorLock {
-        CursorLock::new(self.window.0, self.should_lock_cursor)
+        CursorLock::new(
+            self.window.0,
+            self.should_lock_cursor,
+            self.toggle_key,
+            self.is_cursor_visible,
+            self.is_cursor_visible_when_locked,
+            self.is_raw_motion,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn disabled_state_ignores_events() {
+        let mut state = CursorLockState::new(KeyCode::Escape, true);
+        state.is_cursor_locked = true;
+
+        let input = WinitInputHelper::new();
+        let motion = DeviceEvent::MouseMotion { delta: (3.0, 4.0) };
+        let unfocused = WindowEvent::Focused(false);
+
+        state.set_enabled(false);
+        state.device_event(&motion);
+        state.update();
+        assert_eq!(state.window_event(&unfocused), None);
+        assert_eq!(state.mouse_motion(&input), Vec2::ZERO);
+
+        state.set_enabled(true);
+        state.device_event(&motion);
+        state.update();
+        assert_eq!(state.window_event(&unfocused), Some(LockRequest::Release));
+        assert_eq!(state.mouse_motion(&input), vec2(3.0, 4.0));
     }
 }
@@ -1,103 +1,515 @@
-use std::sync::Arc;
+use std::{
+    cell::RefCell,
+    sync::{mpsc, Arc},
+};
 
-use winit::{dpi::PhysicalSize, window::Window};
+use thiserror::Error;
+use wgpu::util::DeviceExt;
+use winit::{
+    dpi::{PhysicalPosition, PhysicalSize},
+    event::WindowEvent,
+    window::Window,
+};
 use winit_input_helper::WinitInputHelper;
 
-use crate::systems::RgbColor;
+use crate::systems::{
+    Bloom, Downsample, EngineOutSignal, FrameCapturedSignal, Fxaa, RgbColor, Texture,
+};
 
 /// Handler for the display.
 pub struct Display {
-    surface: wgpu::Surface<'static>,
+    target: DisplayTarget,
+    instance: wgpu::Instance,
     queue: wgpu::Queue,
     device: wgpu::Device,
+    adapter_info: wgpu::AdapterInfo,
+    max_anisotropy: u16,
     config: wgpu::SurfaceConfiguration,
+    present_modes: Vec<wgpu::PresentMode>,
 
     size: PhysicalSize<u32>,
+    render_scale: f32,
+    render_size: PhysicalSize<u32>,
+    scale_factor: f64,
+    target_aspect: Option<f32>,
     clear_color: RgbColor,
+    load_op: ColorLoadOp,
+    background: Background,
+    gradient: Option<Gradient>,
+    sample_count: u32,
+    msaa_texture_view: Option<wgpu::TextureView>,
+    depth_texture_view: wgpu::TextureView,
+    hdr: Option<Hdr>,
+    fxaa: Option<Fxaa>,
+    downsample: Option<Downsample>,
 
-    // This is needed because surface points to the window
-    #[allow(dead_code)]
-    window: Arc<Window>,
+    can_capture: bool,
+    is_capture_requested: bool,
+    pending_capture: Option<PendingCapture>,
+
+    pick_request: Option<PhysicalPosition<u32>>,
+    pending_pick: Option<PendingPick>,
+
+    gpu_timer: Option<GpuTimer>,
+
+    frame_index: u64,
+    on_frame_complete: Option<Arc<FrameCompleteCallback>>,
+
+    staging: RefCell<StagingUploads>,
 }
 
+/// The callback of [`Display::set_on_frame_complete`].
+pub type FrameCompleteCallback = dyn Fn(u64) + Send + Sync;
+
 impl Display {
-    pub async fn new(window: Arc<Window>, clear_color: RgbColor) -> Self {
+    pub const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+
+    /// The format of the offscreen texture in headless mode.
+    pub const HEADLESS_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+
+    /// The format of the ID target of the pick pass.
+    pub const PICK_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::R32Uint;
+
+    /// The default chunk size of the staging belt, see
+    /// [`Display::set_staging_chunk_size`].
+    pub const DEFAULT_STAGING_CHUNK_SIZE: wgpu::BufferAddress = 1 << 16;
+
+    /// Features that are requested if the adapter supports them.
+    pub const OPTIONAL_FEATURES: wgpu::Features = wgpu::Features::POLYGON_MODE_LINE
+        .union(wgpu::Features::TIMESTAMP_QUERY)
+        .union(wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES);
+
+    #[allow(clippy::too_many_arguments)]
+    pub async fn new(
+        window: Arc<Window>,
+        clear_color: RgbColor,
+        sample_count: u32,
+        present_mode: Option<wgpu::PresentMode>,
+        power_preference: wgpu::PowerPreference,
+        backends: Option<wgpu::Backends>,
+        hdr: bool,
+        fxaa: bool,
+    ) -> Result<Self, DisplayError> {
         let size = window.inner_size();
 
-        log::debug!("Creating wgpu instance");
-        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
-            backends: match cfg!(target_arch = "wasm32") {
-                true => wgpu::Backends::BROWSER_WEBGPU,
-                false => wgpu::Backends::PRIMARY,
+        Self::new_surface(
+            window.clone().into(),
+            size,
+            |surface| DisplayTarget::Window {
+                surface: Some(surface),
+                window,
             },
-            ..Default::default()
-        });
+            clear_color,
+            sample_count,
+            present_mode,
+            power_preference,
+            backends,
+            hdr,
+            fxaa,
+        )
+        .await
+    }
+
+    /// Create a display rendering to a surface of a raw window handle, for
+    /// embedding in an app not using a winit [`Window`].
+    ///
+    /// The handle is kept alive by the surface. The scale factor is 1.0 and
+    /// scale factor changes are not observed, so the app needs to call
+    /// [`Display::resize`] with the physical size itself.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn new_raw_handle(
+        handle: Box<dyn wgpu::WindowHandle>,
+        size: PhysicalSize<u32>,
+        clear_color: RgbColor,
+        sample_count: u32,
+        present_mode: Option<wgpu::PresentMode>,
+        power_preference: wgpu::PowerPreference,
+        backends: Option<wgpu::Backends>,
+        hdr: bool,
+        fxaa: bool,
+    ) -> Result<Self, DisplayError> {
+        Self::new_surface(
+            wgpu::SurfaceTarget::Window(handle),
+            size,
+            |surface| DisplayTarget::RawHandle {
+                surface: Some(surface),
+            },
+            clear_color,
+            sample_count,
+            present_mode,
+            power_preference,
+            backends,
+            hdr,
+            fxaa,
+        )
+        .await
+    }
+
+    /// Create the surface of `surface_target` and configure it at `size`.
+    #[allow(clippy::too_many_arguments)]
+    async fn new_surface(
+        surface_target: wgpu::SurfaceTarget<'static>,
+        size: PhysicalSize<u32>,
+        target: impl FnOnce(wgpu::Surface<'static>) -> DisplayTarget,
+        clear_color: RgbColor,
+        sample_count: u32,
+        present_mode: Option<wgpu::PresentMode>,
+        power_preference: wgpu::PowerPreference,
+        backends: Option<wgpu::Backends>,
+        hdr: bool,
+        fxaa: bool,
+    ) -> Result<Self, DisplayError> {
+        let instance = Self::create_instance(backends).await;
 
         log::debug!("Creating window surface");
-        let surface = instance.create_surface(window.clone()).unwrap();
+        let surface = instance.create_surface(surface_target)?;
+
+        let (adapter, device, queue) =
+            Self::request_device(&instance, Some(&surface), power_preference).await?;
+
+        let surface_caps = surface.get_capabilities(&adapter);
+        let surface_format = surface_caps
+            .formats
+            .iter()
+            .find(|f| f.is_srgb())
+            .copied()
+            .unwrap_or(surface_caps.formats[0]);
+        let present_mode = match present_mode {
+            Some(present_mode) => {
+                Self::validate_present_mode(present_mode, &surface_caps.present_modes)
+            }
+            None => surface_caps.present_modes[0],
+        };
+        let can_capture = surface_caps.usages.contains(wgpu::TextureUsages::COPY_SRC)
+            && CapturedFrame::is_format_supported(surface_format);
+        let config = wgpu::SurfaceConfiguration {
+            usage: match can_capture {
+                true => wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+                false => wgpu::TextureUsages::RENDER_ATTACHMENT,
+            },
+            format: surface_format,
+            width: size.width.max(1),
+            height: size.height.max(1),
+            present_mode,
+            alpha_mode: surface_caps.alpha_modes[0],
+            view_formats: vec![],
+            desired_maximum_frame_latency: 2,
+        };
+
+        log::debug!("Configuring surface");
+        surface.configure(&device, &config);
+
+        Ok(Self::from_target(
+            target(surface),
+            instance,
+            adapter,
+            device,
+            queue,
+            config,
+            surface_caps.present_modes,
+            clear_color,
+            sample_count,
+            hdr,
+            fxaa,
+            can_capture,
+        ))
+    }
+
+    /// Create a display rendering to an offscreen texture of
+    /// [`Display::HEADLESS_FORMAT`] instead of a window surface.
+    pub async fn new_headless(
+        size: PhysicalSize<u32>,
+        clear_color: RgbColor,
+        sample_count: u32,
+        power_preference: wgpu::PowerPreference,
+        backends: Option<wgpu::Backends>,
+        hdr: bool,
+        fxaa: bool,
+    ) -> Result<Self, DisplayError> {
+        let instance = Self::create_instance(backends).await;
+
+        let (adapter, device, queue) =
+            Self::request_device(&instance, None, power_preference).await?;
+
+        let config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            format: Self::HEADLESS_FORMAT,
+            width: size.width.max(1),
+            height: size.height.max(1),
+            present_mode: wgpu::PresentMode::Fifo,
+            alpha_mode: wgpu::CompositeAlphaMode::Opaque,
+            view_formats: vec![],
+            desired_maximum_frame_latency: 2,
+        };
+
+        log::debug!("Creating headless texture");
+        let texture = Self::create_headless_texture(&device, &config);
+
+        Ok(Self::from_target(
+            DisplayTarget::Headless { texture },
+            instance,
+            adapter,
+            device,
+            queue,
+            config,
+            Vec::new(),
+            clear_color,
+            sample_count,
+            hdr,
+            fxaa,
+            true,
+        ))
+    }
+
+    /// Create the instance of the backends, without WebGPU if the browser
+    /// exposes it but has no adapter for it, so the canvas is not taken by a
+    /// WebGPU context before falling back to WebGL2.
+    async fn create_instance(backends: Option<wgpu::Backends>) -> wgpu::Instance {
+        log::debug!("Creating wgpu instance");
+        wgpu::util::new_instance_with_webgpu_detection(wgpu::InstanceDescriptor {
+            backends: Self::validate_backends(backends),
+            ..Default::default()
+        })
+        .await
+    }
 
+    async fn request_device(
+        instance: &wgpu::Instance,
+        compatible_surface: Option<&wgpu::Surface<'_>>,
+        power_preference: wgpu::PowerPreference,
+    ) -> Result<(wgpu::Adapter, wgpu::Device, wgpu::Queue), DisplayError> {
         log::debug!("Requesting adapter");
         let adapter = instance
             .request_adapter(&wgpu::RequestAdapterOptions {
-                power_preference: wgpu::PowerPreference::None,
-                compatible_surface: Some(&surface),
+                power_preference,
+                compatible_surface,
                 force_fallback_adapter: false,
             })
             .await
-            .expect("request adapter");
+            .ok_or(DisplayError::NoAdapter)?;
+
+        let adapter_info = adapter.get_info();
+        log::info!(
+            "Using adapter {} with the {} backend",
+            adapter_info.name,
+            adapter_info.backend.to_str()
+        );
+        if cfg!(target_arch = "wasm32") && adapter_info.backend == wgpu::Backend::Gl {
+            log::warn!(
+                "WebGPU is unavailable, falling back to WebGL2 with storage buffers and compute disabled"
+            );
+        }
 
         log::debug!("Requesting device");
         let (device, queue) = adapter
             .request_device(
                 &wgpu::DeviceDescriptor {
                     label: Some("Device"),
-                    required_features: wgpu::Features::empty(),
+                    required_features: adapter.features() & Self::OPTIONAL_FEATURES,
                     required_limits: adapter.limits(),
                     memory_hints: wgpu::MemoryHints::default(),
                 },
                 None,
             )
-            .await
-            .unwrap();
+            .await?;
 
-        let surface_caps = surface.get_capabilities(&adapter);
-        let surface_format = surface_caps
-            .formats
-            .iter()
-            .find(|f| f.is_srgb())
-            .copied()
-            .unwrap_or(surface_caps.formats[0]);
-        let config = wgpu::SurfaceConfiguration {
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-            format: surface_format,
-            width: size.width.max(1),
-            height: size.height.max(1),
-            present_mode: surface_caps.present_modes[0],
-            alpha_mode: surface_caps.alpha_modes[0],
-            view_formats: vec![],
-            desired_maximum_frame_latency: 2,
+        Ok((adapter, device, queue))
+    }
+
+    /// Create the render pass attachments shared by all targets.
+    #[allow(clippy::too_many_arguments)]
+    fn from_target(
+        target: DisplayTarget,
+        instance: wgpu::Instance,
+        adapter: wgpu::Adapter,
+        device: wgpu::Device,
+        queue: wgpu::Queue,
+        config: wgpu::SurfaceConfiguration,
+        present_modes: Vec<wgpu::PresentMode>,
+        clear_color: RgbColor,
+        sample_count: u32,
+        hdr: bool,
+        fxaa: bool,
+        can_capture: bool,
+    ) -> Self {
+        let color_format = match hdr {
+            true => Hdr::FORMAT,
+            false => config.format,
         };
+        let sample_count =
+            Self::validate_sample_count(&adapter, &device, color_format, sample_count);
 
-        log::debug!("Configuring surface");
-        surface.configure(&device, &config);
+        log::debug!("Creating MSAA texture with sample count {sample_count}");
+        let msaa_texture_view =
+            Self::create_msaa_texture_view(&device, &config, color_format, sample_count);
+
+        log::debug!("Creating depth texture");
+        let depth_texture_view = Self::create_depth_texture_view(&device, &config, sample_count);
+
+        let hdr = hdr.then(|| {
+            log::debug!("Creating HDR render target");
+            Hdr::new(&device, &config)
+        });
+
+        let fxaa = fxaa.then(|| {
+            log::debug!("Creating FXAA render target");
+            Fxaa::new(&device, &config)
+        });
+
+        let gpu_timer = match device.features().contains(wgpu::Features::TIMESTAMP_QUERY) {
+            true => {
+                log::debug!("Creating GPU timer");
+                Some(GpuTimer::new(&device, &queue))
+            }
+            false => {
+                log::debug!("Timestamp queries are not supported, GPU timing is disabled");
+                None
+            }
+        };
+
+        // WebGL may lack the anisotropic filtering extension, in which case
+        // wgpu ignores the anisotropy of samplers
+        let max_anisotropy = match adapter
+            .get_downlevel_capabilities()
+            .flags
+            .contains(wgpu::DownlevelFlags::ANISOTROPIC_FILTERING)
+        {
+            true => Texture::MAX_ANISOTROPY,
+            false => 1,
+        };
 
         log::info!("Display handler initialized");
 
+        let scale_factor = match &target {
+            DisplayTarget::Window { window, .. } => window.scale_factor(),
+            DisplayTarget::RawHandle { .. } | DisplayTarget::Headless { .. } => 1.0,
+        };
+
         Self {
-            surface,
+            target,
+            instance,
             device,
             queue,
+            adapter_info: adapter.get_info(),
+            max_anisotropy,
+            size: PhysicalSize::new(config.width, config.height),
+            render_scale: 1.0,
+            render_size: PhysicalSize::new(config.width, config.height),
+            scale_factor,
+            target_aspect: None,
             config,
+            present_modes,
 
-            size,
             clear_color,
+            load_op: ColorLoadOp::Clear,
+            background: Background::ClearColor,
+            gradient: None,
+            sample_count,
+            msaa_texture_view,
+            depth_texture_view,
+            hdr,
+            fxaa,
+            downsample: None,
 
-            window,
+            can_capture,
+            is_capture_requested: false,
+            pending_capture: None,
+
+            pick_request: None,
+            pending_pick: None,
+
+            gpu_timer,
+
+            frame_index: 0,
+            on_frame_complete: None,
+
+            staging: RefCell::new(StagingUploads::new(Self::DEFAULT_STAGING_CHUNK_SIZE)),
+        }
+    }
+
+    /// The window surface, [`None`] in headless mode or when suspended.
+    pub fn surface(&self) -> Option<&wgpu::Surface> {
+        match &self.target {
+            DisplayTarget::Window { surface, .. } | DisplayTarget::RawHandle { surface } => {
+                surface.as_ref()
+            }
+            DisplayTarget::Headless { .. } => None,
+        }
+    }
+
+    pub fn is_headless(&self) -> bool {
+        matches!(self.target, DisplayTarget::Headless { .. })
+    }
+
+    /// Whether the window surface is dropped by [`Display::suspend`].
+    pub fn is_suspended(&self) -> bool {
+        matches!(
+            self.target,
+            DisplayTarget::Window { surface: None, .. }
+                | DisplayTarget::RawHandle { surface: None }
+        )
+    }
+
+    /// Drop the window surface, [`Display::render`] is skipped until
+    /// [`Display::resume`].
+    ///
+    /// The device and queue are kept, so the other handlers are unaffected.
+    pub fn suspend(&mut self) {
+        if let DisplayTarget::Window { surface, .. } | DisplayTarget::RawHandle { surface } =
+            &mut self.target
+        {
+            log::debug!("Dropping window surface");
+            *surface = None;
+        }
+    }
+
+    /// Recreate the window surface for `window`.
+    pub fn resume(&mut self, window: Arc<Window>) {
+        if self.is_headless() {
+            log::warn!("Resume is not used in headless mode");
+            return;
         }
+
+        log::debug!("Recreating window surface");
+        let surface = match self.instance.create_surface(window.clone()) {
+            Ok(surface) => surface,
+            Err(e) => {
+                log::error!("Unable to create window surface: {e}");
+                return;
+            }
+        };
+        surface.configure(&self.device, &self.config);
+
+        self.target = DisplayTarget::Window {
+            surface: Some(surface),
+            window,
+        };
     }
 
-    pub fn surface(&self) -> &wgpu::Surface {
-        &self.surface
+    /// Recreate the window surface for a raw window handle, see
+    /// [`Display::new_raw_handle`].
+    pub fn resume_raw_handle(&mut self, handle: Box<dyn wgpu::WindowHandle>) {
+        if self.is_headless() {
+            log::warn!("Resume is not used in headless mode");
+            return;
+        }
+
+        log::debug!("Recreating raw handle surface");
+        let surface = match self
+            .instance
+            .create_surface(wgpu::SurfaceTarget::Window(handle))
+        {
+            Ok(surface) => surface,
+            Err(e) => {
+                log::error!("Unable to create window surface: {e}");
+                return;
+            }
+        };
+        surface.configure(&self.device, &self.config);
+
+        self.target = DisplayTarget::RawHandle {
+            surface: Some(surface),
+        };
     }
 
     pub fn queue(&self) -> &wgpu::Queue {
@@ -112,108 +524,2174 @@ impl Display {
         &self.config
     }
 
-    pub fn resize(&mut self, size: PhysicalSize<u32>) {
-        if size.width > 0 && size.height > 0 {
-            self.size = size;
-            self.config.width = size.width;
-            self.config.height = size.height;
-            self.surface.configure(&self.device, &self.config);
+    pub fn adapter_info(&self) -> &wgpu::AdapterInfo {
+        &self.adapter_info
+    }
+
+    /// The backend of the adapter, e.g. to avoid features unsupported on
+    /// [`wgpu::Backend::Gl`] such as storage buffers and compute shaders.
+    pub fn backend(&self) -> wgpu::Backend {
+        self.adapter_info.backend
+    }
+
+    /// The highest anisotropic filtering level supported by the adapter, 1 if
+    /// unsupported, see [`Texture::clamp_anisotropy`].
+    pub fn max_anisotropy(&self) -> u16 {
+        self.max_anisotropy
+    }
+
+    /// The limits of the device.
+    pub fn limits(&self) -> wgpu::Limits {
+        self.device.limits()
+    }
+
+    /// The features enabled on the device, see [`Display::OPTIONAL_FEATURES`].
+    pub fn features(&self) -> wgpu::Features {
+        self.device.features()
+    }
+
+    pub fn present_mode(&self) -> wgpu::PresentMode {
+        self.config.present_mode
+    }
+
+    /// Set the present mode and reconfigure the surface.
+    ///
+    /// Falls back to [`wgpu::PresentMode::Fifo`] if not supported.
+    pub fn set_present_mode(&mut self, present_mode: wgpu::PresentMode) {
+        if self.is_headless() {
+            log::warn!("Present mode is not used in headless mode");
+            return;
         }
+
+        self.config.present_mode = Self::validate_present_mode(present_mode, &self.present_modes);
+        self.configure_target();
     }
 
-    pub fn aspect_ratio(&self) -> f32 {
-        self.size.width as f32 / self.size.height as f32
+    pub fn clear_color(&self) -> RgbColor {
+        self.clear_color
     }
 
-    pub fn update(&mut self, input: &WinitInputHelper) {
-        if let Some(size) = input.window_resized() {
-            self.resize(size);
+    /// Set the clear color, which takes effect from the next
+    /// [`Display::render`].
+    pub fn set_clear_color(&mut self, clear_color: RgbColor) {
+        self.clear_color = clear_color;
+    }
+
+    pub fn background(&self) -> Background {
+        self.background
+    }
+
+    /// Set the background drawn behind the scene, which takes effect from the
+    /// next [`Display::render`].
+    ///
+    /// The gradient pipeline is created the first time a gradient is set.
+    pub fn set_background(&mut self, background: Background) {
+        self.background = background;
+
+        if let Background::Gradient { top, bottom } = background {
+            match self.gradient.as_ref() {
+                Some(gradient) => gradient.set_colors(&self.queue, top, bottom),
+                None => {
+                    log::debug!("Creating gradient background");
+                    self.gradient = Some(Gradient::new(
+                        &self.device,
+                        self.color_format(),
+                        self.sample_count,
+                        top,
+                        bottom,
+                    ));
+                }
+            }
         }
     }
 
-    pub fn render(&mut self, render: impl FnOnce(&mut Display, &mut wgpu::RenderPass)) {
-        let texture = self.surface.get_current_texture().unwrap();
-        let texture_view = texture
-            .texture
-            .create_view(&wgpu::TextureViewDescriptor::default());
+    pub fn load_op(&self) -> ColorLoadOp {
+        self.load_op
+    }
 
-        // Create encoder
-        let mut encoder = self
-            .device
-            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
-                label: Some("Render Encoder"),
-            });
+    /// Set how the color target starts each frame, which takes effect from the
+    /// next [`Display::render`], e.g. [`ColorLoadOp::Load`] to accumulate
+    /// trails.
+    ///
+    /// The surface texture is not guaranteed to keep the previous frame, so
+    /// [`ColorLoadOp::Load`] is ignored unless the render pass draws into an
+    /// offscreen target, see [`Display::has_offscreen_target`]. If the
+    /// offscreen target is removed later, the frames are cleared again.
+    pub fn set_load_op(&mut self, load_op: ColorLoadOp) {
+        match (load_op, self.has_offscreen_target()) {
+            (ColorLoadOp::Load, false) => {
+                log::warn!("Load op requires an offscreen target, e.g. MSAA or HDR, ignoring");
+            }
+            _ => self.load_op = load_op,
+        }
+    }
 
-        // Render pass
-        {
-            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: Some("Render Pass"),
-                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &texture_view,
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color {
-                            r: self.clear_color.r() as f64,
-                            g: self.clear_color.g() as f64,
-                            b: self.clear_color.b() as f64,
-                            a: 1.0,
-                        }),
-                        store: wgpu::StoreOp::Store,
-                    },
-                })],
-                depth_stencil_attachment: None,
-                occlusion_query_set: None,
-                timestamp_writes: None,
-            });
+    /// Whether the render pass draws into a texture kept between the frames
+    /// instead of the surface texture.
+    ///
+    /// This is the case with MSAA, HDR, FXAA, a render scale other than 1, or
+    /// in headless mode.
+    pub fn has_offscreen_target(&self) -> bool {
+        self.msaa_texture_view.is_some()
+            || self.hdr.is_some()
+            || self.fxaa.is_some()
+            || self.downsample.is_some()
+            || self.is_headless()
+    }
 
-            render(self, &mut render_pass);
+    /// The MSAA sample count, 1 means MSAA is disabled.
+    pub fn sample_count(&self) -> u32 {
+        self.sample_count
+    }
+
+    /// The format of the depth texture attached to the render pass.
+    pub fn depth_format(&self) -> wgpu::TextureFormat {
+        Self::DEPTH_FORMAT
+    }
+
+    /// The format of the color attachment of the render pass, which is the
+    /// HDR format if HDR is enabled, or the surface format otherwise.
+    pub fn color_format(&self) -> wgpu::TextureFormat {
+        match self.hdr {
+            Some(..) => Hdr::FORMAT,
+            None => self.config.format,
         }
+    }
 
-        // Submit render pass
-        self.queue.submit(std::iter::once(encoder.finish()));
-        self.device.poll(wgpu::Maintain::Wait);
-        texture.present();
+    pub fn is_hdr(&self) -> bool {
+        self.hdr.is_some()
     }
-}
 
-/// Builder of [`Display`].
-pub struct DisplayBuilder<T> {
-    window: T,
-    clear_color: RgbColor,
-}
+    /// The exposure applied before tonemapping, [`None`] if HDR is disabled.
+    pub fn exposure(&self) -> Option<f32> {
+        self.hdr.as_ref().map(|hdr| hdr.exposure)
+    }
 
-pub mod builder {
-    use super::*;
+    pub fn set_exposure(&mut self, exposure: f32) {
+        match self.hdr.as_mut() {
+            Some(hdr) => hdr.set_exposure(&self.queue, exposure),
+            None => log::warn!("Exposure is only used when HDR is enabled"),
+        }
+    }
 
-    pub struct NoWindow;
-    pub struct WithWindow(pub Arc<Window>);
-}
+    /// The brightness above which pixels bloom, [`None`] if HDR is disabled.
+    pub fn bloom_threshold(&self) -> Option<f32> {
+        self.hdr.as_ref().map(|hdr| hdr.bloom.threshold())
+    }
 
-impl DisplayBuilder<builder::NoWindow> {
-    pub fn new() -> Self {
-        Self {
-            window: builder::NoWindow,
-            clear_color: RgbColor::BLACK,
+    /// The strength of the bloom added back, [`None`] if HDR is disabled.
+    pub fn bloom_intensity(&self) -> Option<f32> {
+        self.hdr.as_ref().map(|hdr| hdr.bloom.intensity())
+    }
+
+    /// Sets the bloom threshold and intensity, an intensity of zero disables
+    /// bloom.
+    pub fn set_bloom(&mut self, threshold: f32, intensity: f32) {
+        match self.hdr.as_mut() {
+            Some(hdr) => {
+                hdr.bloom.set_threshold(&self.queue, threshold);
+                hdr.bloom.set_intensity(intensity);
+            }
+            None => log::warn!("Bloom is only used when HDR is enabled"),
         }
     }
-}
 
-impl<T> DisplayBuilder<T> {
-    pub fn with_window(self, window: Arc<Window>) -> DisplayBuilder<builder::WithWindow> {
-        DisplayBuilder {
-            window: builder::WithWindow(window),
-            clear_color: self.clear_color,
+    pub fn is_fxaa(&self) -> bool {
+        self.fxaa.is_some()
+    }
+
+    /// Enable or disable the FXAA pass, creating or dropping its target.
+    pub fn set_fxaa(&mut self, fxaa: bool) {
+        if fxaa == self.is_fxaa() {
+            return;
         }
+
+        self.fxaa = fxaa.then(|| {
+            log::debug!("Creating FXAA render target");
+            Fxaa::new(&self.device, &self.render_config())
+        });
     }
 
-    pub fn with_clear_color(mut self, clear_color: RgbColor) -> Self {
-        self.clear_color = clear_color;
-        self
+    /// The scale of the render size relative to the surface size.
+    pub fn render_scale(&self) -> f32 {
+        self.render_scale
     }
-}
 
-impl DisplayBuilder<builder::WithWindow> {
-    pub async fn build(self) -> Display {
-        Display::new(self.window.0, self.clear_color).await
+    /// Set the scale of the render size relative to the surface size,
+    /// recreating the render targets.
+    ///
+    /// The scene is rendered at the scaled size and filtered onto the surface,
+    /// e.g. 2 supersamples each pixel 4 times independent of MSAA. The scale is
+    /// clamped so the targets fit in the maximum texture dimension, and
+    /// non-positive or non-finite scales are ignored.
+    pub fn set_render_scale(&mut self, render_scale: f32) {
+        if !(render_scale.is_finite() && render_scale > 0.0) {
+            log::warn!("Invalid render scale {render_scale}, ignoring");
+            return;
+        }
+
+        if render_scale == self.render_scale {
+            return;
+        }
+
+        self.render_scale = render_scale;
+        self.resize_render_targets();
+    }
+
+    /// The size of the targets the passes draw into, which is the surface size
+    /// scaled by [`Display::render_scale`].
+    pub fn render_size(&self) -> PhysicalSize<u32> {
+        self.render_size
+    }
+
+    pub fn resize(&mut self, size: PhysicalSize<u32>) {
+        if size.width > 0 && size.height > 0 {
+            self.size = size;
+            self.config.width = size.width;
+            self.config.height = size.height;
+            self.configure_target();
+            self.resize_render_targets();
+        }
+    }
+
+    /// Recreate the targets of the passes at the scaled surface size.
+    fn resize_render_targets(&mut self) {
+        self.render_size = self.scaled_size();
+        let render_config = self.render_config();
+
+        self.msaa_texture_view = Self::create_msaa_texture_view(
+            &self.device,
+            &render_config,
+            self.color_format(),
+            self.sample_count,
+        );
+        self.depth_texture_view =
+            Self::create_depth_texture_view(&self.device, &render_config, self.sample_count);
+        if let Some(hdr) = self.hdr.as_mut() {
+            hdr.resize(&self.device, &render_config);
+        }
+        if let Some(fxaa) = self.fxaa.as_mut() {
+            fxaa.resize(&self.device, &render_config);
+        }
+
+        match (self.render_size == self.size, self.downsample.as_mut()) {
+            (true, _) => self.downsample = None,
+            (false, Some(downsample)) => downsample.resize(&self.device, &render_config),
+            (false, None) => {
+                log::debug!(
+                    "Creating downsample render target of size {}x{}",
+                    self.render_size.width,
+                    self.render_size.height
+                );
+                self.downsample = Some(Downsample::new(&self.device, &render_config));
+            }
+        }
+    }
+
+    /// The surface size scaled by the render scale, clamped to the maximum
+    /// texture dimension.
+    fn scaled_size(&self) -> PhysicalSize<u32> {
+        let max_dimension = self.device.limits().max_texture_dimension_2d;
+        let max_scale = max_dimension as f32 / self.size.width.max(self.size.height) as f32;
+        let scale = match self.render_scale > max_scale {
+            true => {
+                log::warn!(
+                    "Render scale {} exceeds the maximum texture dimension {max_dimension}, \
+                    clamping to {max_scale}",
+                    self.render_scale
+                );
+                max_scale
+            }
+            false => self.render_scale,
+        };
+
+        PhysicalSize::new(
+            ((self.size.width as f32 * scale) as u32).clamp(1, max_dimension),
+            ((self.size.height as f32 * scale) as u32).clamp(1, max_dimension),
+        )
+    }
+
+    /// The surface configuration with the render size, for the targets of the
+    /// passes.
+    fn render_config(&self) -> wgpu::SurfaceConfiguration {
+        wgpu::SurfaceConfiguration {
+            width: self.render_size.width,
+            height: self.render_size.height,
+            ..self.config.clone()
+        }
+    }
+
+    /// The sample count if the color and depth targets support it, otherwise
+    /// the highest of 4 and 1 below it that they support.
+    ///
+    /// Counts other than 1 and 4 are only usable with
+    /// [`wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES`], without it
+    /// the guaranteed format features are checked instead of the adapter ones.
+    fn validate_sample_count(
+        adapter: &wgpu::Adapter,
+        device: &wgpu::Device,
+        color_format: wgpu::TextureFormat,
+        sample_count: u32,
+    ) -> u32 {
+        let features = device.features();
+        let is_supported = |count: u32| {
+            [color_format, Self::DEPTH_FORMAT]
+                .into_iter()
+                .all(|format| {
+                    let flags = match features
+                        .contains(wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES)
+                    {
+                        true => adapter.get_texture_format_features(format).flags,
+                        false => format.guaranteed_format_features(features).flags,
+                    };
+                    flags.sample_count_supported(count)
+                })
+        };
+
+        if is_supported(sample_count) {
+            return sample_count;
+        }
+
+        let fallback = [4, 1]
+            .into_iter()
+            .find(|&count| count < sample_count && is_supported(count))
+            .unwrap_or(1);
+        log::warn!("Sample count {sample_count} is not supported, falling back to {fallback}");
+        fallback
+    }
+
+    /// The backends available on the current platform, WebGPU then WebGL2 on
+    /// the web.
+    fn platform_backends() -> wgpu::Backends {
+        match cfg!(target_arch = "wasm32") {
+            true => wgpu::Backends::BROWSER_WEBGPU | wgpu::Backends::GL,
+            false => wgpu::Backends::PRIMARY,
+        }
+    }
+
+    /// Mask the requested backends with what the platform supports, falls back
+    /// to the platform default if none remain.
+    fn validate_backends(backends: Option<wgpu::Backends>) -> wgpu::Backends {
+        let Some(backends) = backends else {
+            return Self::platform_backends();
+        };
+
+        let supported = match cfg!(target_arch = "wasm32") {
+            true => backends & (wgpu::Backends::BROWSER_WEBGPU | wgpu::Backends::GL),
+            false => backends,
+        };
+
+        match supported.is_empty() {
+            true => {
+                log::warn!("Backends {backends:?} not supported, falling back to default");
+                Self::platform_backends()
+            }
+            false => supported,
+        }
+    }
+
+    /// Request to capture the next rendered frame.
+    ///
+    /// The frame is sent as a [`FrameCapturedSignal`] in [`Display::signal`]
+    /// once it is read back from the GPU.
+    pub fn capture_frame(&mut self) {
+        match self.can_capture {
+            true => self.is_capture_requested = true,
+            false => log::warn!("Frame capture is not supported by the surface"),
+        }
+    }
+
+    /// Take the captured frame if it has been read back from the GPU.
+    pub fn take_captured_frame(&mut self) -> Option<CapturedFrame> {
+        match self.pending_capture.as_ref()?.rx.try_recv() {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => {
+                log::error!("Unable to map frame capture buffer: {e}");
+                self.pending_capture = None;
+                return None;
+            }
+            Err(mpsc::TryRecvError::Empty) => return None,
+            Err(mpsc::TryRecvError::Disconnected) => {
+                log::error!("Frame capture buffer mapping was dropped");
+                self.pending_capture = None;
+                return None;
+            }
+        }
+
+        self.pending_capture.take().map(PendingCapture::read)
+    }
+
+    /// Request to pick the ID under `position` in the next
+    /// [`Display::render_pick`].
+    ///
+    /// The ID is read back from the GPU after a frame or two, take it with
+    /// [`Display::take_picked`].
+    pub fn request_pick(&mut self, position: PhysicalPosition<f64>) {
+        let x = position.x.max(0.0) as u32;
+        let y = position.y.max(0.0) as u32;
+        if x >= self.config.width || y >= self.config.height {
+            log::warn!("Pick position {position:?} is outside the display");
+            return;
+        }
+
+        // The ID target is of the render size
+        let scale = |value: u32, render: u32, surface: u32| {
+            (value as u64 * render as u64 / surface as u64) as u32
+        };
+        self.pick_request = Some(PhysicalPosition::new(
+            scale(x, self.render_size.width, self.config.width),
+            scale(y, self.render_size.height, self.config.height),
+        ));
+    }
+
+    /// Take the picked ID if it has been read back from the GPU.
+    ///
+    /// The outer [`None`] means there is no pick ready, the inner [`None`]
+    /// means nothing is under the position.
+    pub fn take_picked(&mut self) -> Option<Option<u32>> {
+        match self.pending_pick.as_ref()?.rx.try_recv() {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => {
+                log::error!("Unable to map pick buffer: {e}");
+                self.pending_pick = None;
+                return None;
+            }
+            Err(mpsc::TryRecvError::Empty) => return None,
+            Err(mpsc::TryRecvError::Disconnected) => {
+                log::error!("Pick buffer mapping was dropped");
+                self.pending_pick = None;
+                return None;
+            }
+        }
+
+        self.pending_pick.take().map(PendingPick::read)
+    }
+
+    pub fn signal(&mut self, tx: &mpsc::Sender<EngineOutSignal>) {
+        if let Some(frame) = self.take_captured_frame() {
+            tx.send(FrameCapturedSignal::out_signal(frame)).unwrap();
+        }
+    }
+
+    /// The GPU time of the render pass of a recent frame in milliseconds.
+    ///
+    /// The timestamps are read back asynchronously, so this lags a frame or
+    /// two behind. This is [`None`] if timestamp queries are not supported,
+    /// e.g. on WebGL, or before the first readback.
+    pub fn last_gpu_time_ms(&self) -> Option<f32> {
+        self.gpu_timer.as_ref()?.last_time_ms
+    }
+
+    /// The index of the next frame of [`Display::render`], counting the frames
+    /// submitted to the GPU from 0.
+    pub fn frame_index(&self) -> u64 {
+        self.frame_index
+    }
+
+    /// Set the callback called with the [`Display::frame_index`] of each frame
+    /// once the GPU finished it, e.g. to free resources used by the frame.
+    ///
+    /// This uses [`wgpu::Queue::on_submitted_work_done`]. On native, the
+    /// callback is called on the thread of [`Display::render`] in the device
+    /// poll at its end, so the frame is finished when it returns. On the web,
+    /// the callback is called from the browser event loop after the queue is
+    /// done, which is some time after [`Display::render`] returns.
+    pub fn set_on_frame_complete(&mut self, callback: impl Fn(u64) + Send + Sync + 'static) {
+        self.on_frame_complete = Some(Arc::new(callback));
+    }
+
+    /// Remove the callback of [`Display::set_on_frame_complete`], the frames
+    /// already submitted are still called back.
+    pub fn clear_on_frame_complete(&mut self) {
+        self.on_frame_complete = None;
+    }
+
+    /// The aspect ratio to render the scene at, which is the target aspect if
+    /// set, or the aspect ratio of the physical size of the surface.
+    pub fn aspect_ratio(&self) -> f32 {
+        self.target_aspect
+            .unwrap_or_else(|| self.surface_aspect_ratio())
+    }
+
+    /// The aspect ratio of the physical size of the surface.
+    pub fn surface_aspect_ratio(&self) -> f32 {
+        self.size.width as f32 / self.size.height as f32
+    }
+
+    pub fn target_aspect(&self) -> Option<f32> {
+        self.target_aspect
+    }
+
+    /// Set the fixed aspect ratio to render the scene at, [`None`] to fill the
+    /// surface.
+    ///
+    /// The scene is drawn in the largest centered [`Display::viewport`] of the
+    /// aspect, and the bars around it are left with the clear color.
+    /// Non-positive or non-finite aspects are ignored.
+    pub fn set_target_aspect(&mut self, target_aspect: Option<f32>) {
+        match target_aspect {
+            Some(aspect) if !(aspect.is_finite() && aspect > 0.0) => {
+                log::warn!("Invalid target aspect {aspect}, ignoring");
+            }
+            _ => self.target_aspect = target_aspect,
+        }
+    }
+
+    /// Split [`Display::viewport`] into the number of side by side columns of
+    /// equal width, from left to right.
+    pub fn split_viewports(&self, count: usize) -> Vec<Viewport> {
+        let viewport = self.viewport();
+        let width = viewport.width / count.max(1) as f32;
+
+        (0..count.max(1))
+            .map(|i| Viewport {
+                x: viewport.x + width * i as f32,
+                width,
+                ..viewport
+            })
+            .collect()
+    }
+
+    /// The region of the render target the scene is drawn in, in pixels of
+    /// [`Display::render_size`].
+    ///
+    /// This is the whole target without a target aspect, and is recomputed
+    /// from the current size, so it follows resizes and render scale changes.
+    pub fn viewport(&self) -> Viewport {
+        let width = self.render_size.width as f32;
+        let height = self.render_size.height as f32;
+
+        match self.target_aspect {
+            Some(aspect) if width / height > aspect => Viewport {
+                x: (width - height * aspect) / 2.0,
+                y: 0.0,
+                width: height * aspect,
+                height,
+            },
+            Some(aspect) => Viewport {
+                x: 0.0,
+                y: (height - width / aspect) / 2.0,
+                width,
+                height: width / aspect,
+            },
+            None => Viewport {
+                x: 0.0,
+                y: 0.0,
+                width,
+                height,
+            },
+        }
+    }
+
+    /// The number of physical pixels per logical pixel, always 1.0 in headless
+    /// mode.
+    pub fn scale_factor(&self) -> f64 {
+        self.scale_factor
+    }
+
+    /// Handles the scale factor changes, which change the physical size of the
+    /// window without necessarily sending a resize event.
+    pub fn window_event(&mut self, event: &WindowEvent) {
+        if let WindowEvent::ScaleFactorChanged { scale_factor, .. } = event {
+            log::debug!("Scale factor changed to {scale_factor}");
+            self.scale_factor = *scale_factor;
+
+            if let DisplayTarget::Window { window, .. } = &self.target {
+                let size = window.inner_size();
+                self.resize(size);
+            }
+        }
+    }
+
+    pub fn update(&mut self, input: &WinitInputHelper) {
+        if let Some(size) = input.window_resized() {
+            self.resize(size);
+        }
+    }
+
+    /// Write `data` to `buffer` at `offset` through the staging belt.
+    ///
+    /// The upload is recorded in a separate encoder submitted before the next
+    /// pass of the display, or the next [`Display::submit`]. Like
+    /// [`wgpu::Queue::write_buffer`], the offset and the size of `data` must be
+    /// multiples of [`wgpu::COPY_BUFFER_ALIGNMENT`].
+    pub fn write_buffer(&self, buffer: &wgpu::Buffer, offset: wgpu::BufferAddress, data: &[u8]) {
+        let Some(size) = wgpu::BufferSize::new(data.len() as wgpu::BufferAddress) else {
+            return;
+        };
+
+        let mut staging = self.staging.borrow_mut();
+        let StagingUploads { belt, encoder, .. } = &mut *staging;
+        let encoder = encoder.get_or_insert_with(|| {
+            self.device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                    label: Some("Staging Encoder"),
+                })
+        });
+
+        belt.write_buffer(encoder, buffer, offset, size, &self.device)
+            .copy_from_slice(data);
+    }
+
+    /// Submit `encoder` after the uploads of [`Display::write_buffer`].
+    ///
+    /// The belt is finished before and recalled after the submission, its
+    /// chunks are then reused once mapped again by the next device poll.
+    pub fn submit(&self, encoder: wgpu::CommandEncoder) {
+        let mut staging = self.staging.borrow_mut();
+        let uploads = staging.encoder.take().map(|uploads| {
+            staging.belt.finish();
+            uploads.finish()
+        });
+
+        self.queue
+            .submit(uploads.into_iter().chain(std::iter::once(encoder.finish())));
+        staging.belt.recall();
+    }
+
+    pub fn staging_chunk_size(&self) -> wgpu::BufferAddress {
+        self.staging.borrow().chunk_size
+    }
+
+    /// Set the chunk size of the staging belt, submitting the pending uploads.
+    ///
+    /// Writes are packed into chunks of this size, and a write larger than it
+    /// gets a chunk of its own. It is best a bit above the bytes written per
+    /// frame, so a single chunk is used each frame. Smaller sizes allocate more
+    /// chunks on frames with many writes, larger ones waste memory.
+    pub fn set_staging_chunk_size(&mut self, chunk_size: wgpu::BufferAddress) {
+        let staging = self.staging.get_mut();
+        if let Some(uploads) = staging.encoder.take() {
+            staging.belt.finish();
+            self.queue.submit(std::iter::once(uploads.finish()));
+            staging.belt.recall();
+        }
+
+        *staging = StagingUploads::new(chunk_size);
+    }
+
+    /// Run a compute pass, submitted before the next [`Display::render`].
+    ///
+    /// wgpu orders the submissions and synchronizes the buffers written here
+    /// with their reads in the later passes.
+    pub fn compute(&self, compute: impl FnOnce(&Display, &mut wgpu::ComputePass)) {
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Compute Encoder"),
+            });
+
+        {
+            let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("Compute Pass"),
+                timestamp_writes: None,
+            });
+
+            compute(self, &mut compute_pass);
+        }
+
+        self.submit(encoder);
+    }
+
+    /// Render a depth only pass into `view`, submitted before the next
+    /// [`Display::render`].
+    pub fn render_shadow(
+        &self,
+        view: &wgpu::TextureView,
+        render: impl FnOnce(&Display, &mut wgpu::RenderPass),
+    ) {
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Shadow Encoder"),
+            });
+
+        {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Shadow Pass"),
+                color_attachments: &[],
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                    view,
+                    depth_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(1.0),
+                        store: wgpu::StoreOp::Store,
+                    }),
+                    stencil_ops: None,
+                }),
+                occlusion_query_set: None,
+                timestamp_writes: None,
+            });
+
+            render(self, &mut render_pass);
+        }
+
+        self.submit(encoder);
+    }
+
+    /// Render the ID pass if a pick is requested.
+    ///
+    /// Handlers write their ID plus one to a [`Display::PICK_FORMAT`] target
+    /// cleared to 0 for nothing picked, depth tested with
+    /// [`Display::depth_format`] without multisampling. The pixel under the
+    /// position is read back asynchronously.
+    pub fn render_pick(&mut self, render: impl FnOnce(&Display, &mut wgpu::RenderPass)) {
+        let Some(position) = self.pick_request.take() else {
+            return;
+        };
+
+        if self.pending_pick.is_some() {
+            log::debug!("Previous pick is still pending, skipping");
+            return;
+        }
+
+        let size = wgpu::Extent3d {
+            width: self.render_size.width,
+            height: self.render_size.height,
+            depth_or_array_layers: 1,
+        };
+        let id_texture = self.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Pick ID Texture"),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: Self::PICK_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let id_view = id_texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let depth_view = self
+            .device
+            .create_texture(&wgpu::TextureDescriptor {
+                label: Some("Pick Depth Texture"),
+                size,
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: Self::DEPTH_FORMAT,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+                view_formats: &[],
+            })
+            .create_view(&wgpu::TextureViewDescriptor::default());
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Pick Encoder"),
+            });
+
+        {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Pick Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &id_view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                    view: &depth_view,
+                    depth_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(1.0),
+                        store: wgpu::StoreOp::Discard,
+                    }),
+                    stencil_ops: None,
+                }),
+                occlusion_query_set: None,
+                timestamp_writes: None,
+            });
+
+            // Keep the IDs aligned with the letterboxed scene
+            if self.target_aspect.is_some() {
+                self.viewport().set(&mut render_pass);
+            }
+
+            render(self, &mut render_pass);
+        }
+
+        let pick = PendingPick::new(&self.device, &mut encoder, &id_texture, position);
+        self.submit(encoder);
+        pick.map();
+        self.pending_pick = Some(pick);
+    }
+
+    /// Render a frame to the target and present it.
+    ///
+    /// A lost or outdated surface is reconfigured and a timed out surface is
+    /// retried in the next frame, the frame is skipped without calling `render`
+    /// in both cases. Running out of memory is returned as a fatal error.
+    pub fn render(
+        &mut self,
+        render: impl FnOnce(&mut Display, &mut wgpu::RenderPass),
+    ) -> Result<(), DisplayError> {
+        if self.is_suspended() {
+            return Ok(());
+        }
+
+        // Time the render pass unless all the readbacks are still in flight
+        if let Some(gpu_timer) = self.gpu_timer.as_mut() {
+            gpu_timer.poll();
+        }
+        let is_timed = self.gpu_timer.as_ref().is_some_and(GpuTimer::is_ready);
+
+        let surface_texture = match self.surface().map(|surface| surface.get_current_texture()) {
+            None => None,
+            Some(Ok(surface_texture)) => Some(surface_texture),
+            Some(Err(e @ (wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated))) => {
+                log::warn!("Surface texture unavailable ({e}), reconfiguring and skipping frame");
+                self.reconfigure();
+                return Ok(());
+            }
+            Some(Err(wgpu::SurfaceError::Timeout)) => {
+                log::warn!("Surface texture timed out, skipping frame");
+                return Ok(());
+            }
+            Some(Err(wgpu::SurfaceError::OutOfMemory)) => {
+                log::error!("Surface texture out of memory");
+                return Err(DisplayError::SurfaceOutOfMemory);
+            }
+        };
+        let texture_view = match (&surface_texture, &self.target) {
+            (Some(surface_texture), _) => &surface_texture.texture,
+            (None, DisplayTarget::Headless { texture }) => texture,
+            (None, DisplayTarget::Window { .. } | DisplayTarget::RawHandle { .. }) => {
+                unreachable!("window has surface texture")
+            }
+        }
+        .create_view(&wgpu::TextureViewDescriptor::default());
+
+        // Create encoder
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Render Encoder"),
+            });
+
+        // Render pass
+        {
+            let target_view = match (self.hdr.as_ref(), self.fxaa.as_ref()) {
+                (Some(hdr), _) => &hdr.texture_view,
+                (None, Some(fxaa)) => fxaa.texture_view(),
+                (None, None) => self.output_view(&texture_view),
+            };
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Render Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: self.msaa_texture_view.as_ref().unwrap_or(target_view),
+                    resolve_target: self.msaa_texture_view.as_ref().map(|_| target_view),
+                    ops: wgpu::Operations {
+                        load: match (self.load_op, self.has_offscreen_target()) {
+                            (ColorLoadOp::Load, true) => wgpu::LoadOp::Load,
+                            _ => wgpu::LoadOp::Clear(wgpu::Color {
+                                r: self.clear_color.r() as f64,
+                                g: self.clear_color.g() as f64,
+                                b: self.clear_color.b() as f64,
+                                a: 1.0,
+                            }),
+                        },
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                    view: &self.depth_texture_view,
+                    depth_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(1.0),
+                        store: wgpu::StoreOp::Store,
+                    }),
+                    stencil_ops: None,
+                }),
+                occlusion_query_set: None,
+                timestamp_writes: self
+                    .gpu_timer
+                    .as_ref()
+                    .filter(|_| is_timed)
+                    .map(GpuTimer::timestamp_writes),
+            });
+
+            if self.target_aspect.is_some() {
+                self.viewport().set(&mut render_pass);
+            }
+
+            if let (Background::Gradient { .. }, Some(gradient)) =
+                (self.background, self.gradient.as_ref())
+            {
+                gradient.render(&mut render_pass);
+            }
+
+            render(self, &mut render_pass);
+        }
+
+        // The passes draw at the render size, downsampled onto the surface last
+        let output_view = self.output_view(&texture_view);
+
+        // Bloom and tonemap pass
+        if let Some(hdr) = self.hdr.as_ref() {
+            let ldr_view = match self.fxaa.as_ref() {
+                Some(fxaa) => fxaa.texture_view(),
+                None => output_view,
+            };
+            hdr.render(&mut encoder, ldr_view);
+        }
+
+        // FXAA pass
+        if let Some(fxaa) = self.fxaa.as_ref() {
+            fxaa.render(&mut encoder, output_view);
+        }
+
+        // Downsample pass
+        if let Some(downsample) = self.downsample.as_ref() {
+            downsample.render(&mut encoder, &texture_view);
+        }
+
+        // Resolve the timestamps
+        if let (true, Some(gpu_timer)) = (is_timed, self.gpu_timer.as_ref()) {
+            gpu_timer.resolve(&mut encoder);
+        }
+
+        // Copy frame for capture
+        let capture = match std::mem::take(&mut self.is_capture_requested) {
+            true => Some(PendingCapture::new(
+                &self.device,
+                &mut encoder,
+                match (&surface_texture, &self.target) {
+                    (Some(surface_texture), _) => &surface_texture.texture,
+                    (None, DisplayTarget::Headless { texture }) => texture,
+                    (None, DisplayTarget::Window { .. } | DisplayTarget::RawHandle { .. }) => {
+                        unreachable!("window has surface texture")
+                    }
+                },
+                self.config.format,
+            )),
+            false => None,
+        };
+
+        // Submit render pass
+        self.submit(encoder);
+
+        if let Some(callback) = self.on_frame_complete.clone() {
+            let frame_index = self.frame_index;
+            self.queue
+                .on_submitted_work_done(move || callback(frame_index));
+        }
+        self.frame_index += 1;
+
+        if let (true, Some(gpu_timer)) = (is_timed, self.gpu_timer.as_mut()) {
+            gpu_timer.map();
+        }
+
+        if let Some(capture) = capture {
+            capture.map();
+            self.pending_capture = Some(capture);
+        }
+
+        self.device.poll(wgpu::Maintain::Wait);
+        if let Some(surface_texture) = surface_texture {
+            surface_texture.present();
+        }
+
+        Ok(())
+    }
+
+    /// The LDR target of the last pass before the downsample, or the surface
+    /// at the render scale of 1.
+    fn output_view<'a>(&'a self, surface_view: &'a wgpu::TextureView) -> &'a wgpu::TextureView {
+        match self.downsample.as_ref() {
+            Some(downsample) => downsample.texture_view(),
+            None => surface_view,
+        }
+    }
+
+    /// Render a frame drawing the scene once in each of the viewports, e.g.
+    /// from [`Display::split_viewports`] for split screen.
+    ///
+    /// `render` is called with the index of the viewport after it is set, and
+    /// should bind the camera of that viewport. See [`Display::render`] for the
+    /// surface errors.
+    pub fn render_viewports(
+        &mut self,
+        viewports: &[Viewport],
+        mut render: impl FnMut(&mut Display, &mut wgpu::RenderPass, usize),
+    ) -> Result<(), DisplayError> {
+        self.render(|display, render_pass| {
+            for (i, viewport) in viewports.iter().enumerate() {
+                viewport.set(render_pass);
+                render(display, render_pass, i);
+            }
+        })
+    }
+
+    /// Reconfigure the surface with the current configuration, or recreate the
+    /// texture in headless mode.
+    ///
+    /// This is done on [`Display::render`] when the surface is lost or
+    /// outdated, and can be called to recover from such states manually.
+    pub fn reconfigure(&mut self) {
+        log::debug!("Reconfiguring display target");
+        self.configure_target();
+    }
+
+    /// Reconfigure the surface, or recreate the texture in headless mode.
+    fn configure_target(&mut self) {
+        match &mut self.target {
+            DisplayTarget::Window { surface, .. } | DisplayTarget::RawHandle { surface } => {
+                if let Some(surface) = surface {
+                    surface.configure(&self.device, &self.config);
+                }
+            }
+            DisplayTarget::Headless { texture } => {
+                *texture = Self::create_headless_texture(&self.device, &self.config);
+            }
+        }
+    }
+
+    fn validate_present_mode(
+        present_mode: wgpu::PresentMode,
+        supported: &[wgpu::PresentMode],
+    ) -> wgpu::PresentMode {
+        match supported.contains(&present_mode) {
+            true => present_mode,
+            false => {
+                log::warn!("Present mode {present_mode:?} is not supported, falling back to Fifo");
+                wgpu::PresentMode::Fifo
+            }
+        }
+    }
+
+    /// Create the multisampled color texture view, [`None`] if the sample count
+    /// is 1.
+    fn create_msaa_texture_view(
+        device: &wgpu::Device,
+        config: &wgpu::SurfaceConfiguration,
+        format: wgpu::TextureFormat,
+        sample_count: u32,
+    ) -> Option<wgpu::TextureView> {
+        if sample_count <= 1 {
+            return None;
+        }
+
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("MSAA Texture"),
+            size: wgpu::Extent3d {
+                width: config.width,
+                height: config.height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        });
+
+        Some(texture.create_view(&wgpu::TextureViewDescriptor::default()))
+    }
+
+    fn create_headless_texture(
+        device: &wgpu::Device,
+        config: &wgpu::SurfaceConfiguration,
+    ) -> wgpu::Texture {
+        device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Headless Texture"),
+            size: wgpu::Extent3d {
+                width: config.width,
+                height: config.height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: config.format,
+            usage: config.usage,
+            view_formats: &[],
+        })
+    }
+
+    /// Create the depth texture view, matching the sample count of the color
+    /// attachment.
+    fn create_depth_texture_view(
+        device: &wgpu::Device,
+        config: &wgpu::SurfaceConfiguration,
+        sample_count: u32,
+    ) -> wgpu::TextureView {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Depth Texture"),
+            size: wgpu::Extent3d {
+                width: config.width,
+                height: config.height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count,
+            dimension: wgpu::TextureDimension::D2,
+            format: Self::DEPTH_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        });
+
+        texture.create_view(&wgpu::TextureViewDescriptor::default())
+    }
+}
+
+/// The staging belt of [`Display::write_buffer`] and the encoder of its
+/// pending uploads.
+struct StagingUploads {
+    belt: wgpu::util::StagingBelt,
+    chunk_size: wgpu::BufferAddress,
+    encoder: Option<wgpu::CommandEncoder>,
+}
+
+impl StagingUploads {
+    fn new(chunk_size: wgpu::BufferAddress) -> Self {
+        Self {
+            belt: wgpu::util::StagingBelt::new(chunk_size),
+            chunk_size,
+            encoder: None,
+        }
+    }
+}
+
+/// The target [`Display`] renders to.
+enum DisplayTarget {
+    Window {
+        /// [`None`] when suspended.
+        surface: Option<wgpu::Surface<'static>>,
+
+        // This is needed because surface points to the window
+        #[allow(dead_code)]
+        window: Arc<Window>,
+    },
+    /// The surface keeps the handle alive.
+    RawHandle {
+        /// [`None`] when suspended.
+        surface: Option<wgpu::Surface<'static>>,
+    },
+    Headless {
+        texture: wgpu::Texture,
+    },
+}
+
+/// A region of the surface in physical pixels, see [`Display::viewport`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Viewport {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Viewport {
+    pub fn aspect_ratio(&self) -> f32 {
+        self.width / self.height
+    }
+
+    /// Set the viewport of the render pass to the region with the full depth
+    /// range, and the scissor rectangle to the pixels inside it.
+    pub fn set(&self, render_pass: &mut wgpu::RenderPass) {
+        render_pass.set_viewport(self.x, self.y, self.width, self.height, 0.0, 1.0);
+
+        // Floor both edges so the rectangle never exceeds the surface
+        let (x, y) = (self.x.floor(), self.y.floor());
+        render_pass.set_scissor_rect(
+            x as u32,
+            y as u32,
+            ((self.x + self.width).floor() - x) as u32,
+            ((self.y + self.height).floor() - y) as u32,
+        );
+    }
+}
+
+/// How the color target starts each frame of [`Display::render`], see
+/// [`Display::set_load_op`].
+///
+/// The depth is always cleared, so the scene drawn over the previous frame is
+/// depth tested on its own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorLoadOp {
+    /// Clear to [`Display::clear_color`].
+    #[default]
+    Clear,
+    /// Keep the previous frame, which is only covered where the scene or the
+    /// background draws, so a [`Background::Gradient`] or a skybox hides it.
+    Load,
+}
+
+/// The background drawn behind the scene in [`Display::render`].
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Background {
+    /// Only the clear color.
+    #[default]
+    ClearColor,
+    /// A vertical gradient from the top to the bottom of the screen, drawn
+    /// over the clear color.
+    Gradient { top: RgbColor, bottom: RgbColor },
+}
+
+/// The fullscreen triangle drawing [`Background::Gradient`].
+///
+/// It is drawn at the far plane with depth writes, so the scene always draws in
+/// front regardless of the order.
+struct Gradient {
+    buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    pipeline: wgpu::RenderPipeline,
+}
+
+impl Gradient {
+    fn new(
+        device: &wgpu::Device,
+        color_format: wgpu::TextureFormat,
+        sample_count: u32,
+        top: RgbColor,
+        bottom: RgbColor,
+    ) -> Self {
+        let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Gradient Background Buffer"),
+            contents: bytemuck::cast_slice(&Self::colors_uniform(top, bottom)),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Gradient Background Bind Group Layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Gradient Background Bind Group"),
+            layout: &bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: buffer.as_entire_binding(),
+            }],
+        });
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Gradient Background Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("../shaders/background.wgsl").into()),
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Gradient Background Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Gradient Background Render Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vertex_main"),
+                buffers: &[],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fragment_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: color_format,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: Display::DEPTH_FORMAT,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::Always,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState {
+                count: sample_count,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+            cache: None,
+        });
+
+        Self {
+            buffer,
+            bind_group,
+            pipeline,
+        }
+    }
+
+    fn set_colors(&self, queue: &wgpu::Queue, top: RgbColor, bottom: RgbColor) {
+        queue.write_buffer(
+            &self.buffer,
+            0,
+            bytemuck::cast_slice(&Self::colors_uniform(top, bottom)),
+        );
+    }
+
+    fn render(&self, render_pass: &mut wgpu::RenderPass) {
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+
+    fn colors_uniform(top: RgbColor, bottom: RgbColor) -> [[f32; 4]; 2] {
+        [top.extend(1.0).to_array(), bottom.extend(1.0).to_array()]
+    }
+}
+
+/// The HDR render target and the pass tonemapping it onto the surface.
+struct Hdr {
+    texture_view: wgpu::TextureView,
+    bloom: Bloom,
+    sampler: wgpu::Sampler,
+    exposure: f32,
+    exposure_buffer: wgpu::Buffer,
+    bind_group_layout: wgpu::BindGroupLayout,
+    bind_group: wgpu::BindGroup,
+    pipeline: wgpu::RenderPipeline,
+}
+
+impl Hdr {
+    const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+
+    fn new(device: &wgpu::Device, config: &wgpu::SurfaceConfiguration) -> Self {
+        let exposure = 1.0;
+
+        let texture_view = Self::create_texture_view(device, config);
+
+        // Disabled until an intensity is set
+        let bloom = Bloom::new(device, config, &texture_view, 1.0, 0.0);
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("HDR Sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        let exposure_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("HDR Exposure Buffer"),
+            contents: bytemuck::bytes_of(&Self::exposure_uniform(exposure)),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("HDR Bind Group Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        });
+
+        let bind_group = Self::create_bind_group(
+            device,
+            &bind_group_layout,
+            &texture_view,
+            &sampler,
+            &exposure_buffer,
+        );
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Tonemap Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("../shaders/tonemap.wgsl").into()),
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Tonemap Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Tonemap Render Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vertex_main"),
+                buffers: &[],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fragment_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: config.format,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        Self {
+            texture_view,
+            bloom,
+            sampler,
+            exposure,
+            exposure_buffer,
+            bind_group_layout,
+            bind_group,
+            pipeline,
+        }
+    }
+
+    fn resize(&mut self, device: &wgpu::Device, config: &wgpu::SurfaceConfiguration) {
+        self.texture_view = Self::create_texture_view(device, config);
+        self.bloom.resize(device, config, &self.texture_view);
+        self.bind_group = Self::create_bind_group(
+            device,
+            &self.bind_group_layout,
+            &self.texture_view,
+            &self.sampler,
+            &self.exposure_buffer,
+        );
+    }
+
+    fn set_exposure(&mut self, queue: &wgpu::Queue, exposure: f32) {
+        self.exposure = exposure;
+        queue.write_buffer(
+            &self.exposure_buffer,
+            0,
+            bytemuck::bytes_of(&Self::exposure_uniform(exposure)),
+        );
+    }
+
+    /// Add the bloom, then tonemap the HDR target onto the surface with ACES.
+    fn render(&self, encoder: &mut wgpu::CommandEncoder, surface_view: &wgpu::TextureView) {
+        self.bloom.render(encoder, &self.texture_view);
+
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Tonemap Render Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: surface_view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            occlusion_query_set: None,
+            timestamp_writes: None,
+        });
+
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+
+    /// The exposure padded to the 16 bytes uniform alignment.
+    fn exposure_uniform(exposure: f32) -> [f32; 4] {
+        [exposure, 0.0, 0.0, 0.0]
+    }
+
+    fn create_texture_view(
+        device: &wgpu::Device,
+        config: &wgpu::SurfaceConfiguration,
+    ) -> wgpu::TextureView {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("HDR Texture"),
+            size: wgpu::Extent3d {
+                width: config.width,
+                height: config.height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: Self::FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+
+        texture.create_view(&wgpu::TextureViewDescriptor::default())
+    }
+
+    fn create_bind_group(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        texture_view: &wgpu::TextureView,
+        sampler: &wgpu::Sampler,
+        exposure_buffer: &wgpu::Buffer,
+    ) -> wgpu::BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("HDR Bind Group"),
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(texture_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(sampler),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: exposure_buffer.as_entire_binding(),
+                },
+            ],
+        })
+    }
+}
+
+/// A captured frame in RGBA8 pixels.
+#[derive(Clone)]
+pub struct CapturedFrame {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+// The pixels are megabytes, only their length is printed
+impl std::fmt::Debug for CapturedFrame {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CapturedFrame")
+            .field("width", &self.width)
+            .field("height", &self.height)
+            .field("pixels", &format_args!("[u8; {}]", self.pixels.len()))
+            .finish()
+    }
+}
+
+impl CapturedFrame {
+    fn is_format_supported(format: wgpu::TextureFormat) -> bool {
+        matches!(
+            format,
+            wgpu::TextureFormat::Rgba8Unorm
+                | wgpu::TextureFormat::Rgba8UnormSrgb
+                | wgpu::TextureFormat::Bgra8Unorm
+                | wgpu::TextureFormat::Bgra8UnormSrgb
+        )
+    }
+}
+
+/// A pick being read back from the GPU.
+struct PendingPick {
+    buffer: wgpu::Buffer,
+    tx: mpsc::Sender<Result<(), wgpu::BufferAsyncError>>,
+    rx: mpsc::Receiver<Result<(), wgpu::BufferAsyncError>>,
+}
+
+impl PendingPick {
+    /// Encode the copy of the single pixel at `position` into a new buffer.
+    fn new(
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        texture: &wgpu::Texture,
+        position: PhysicalPosition<u32>,
+    ) -> Self {
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Pick Buffer"),
+            size: std::mem::size_of::<u32>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        encoder.copy_texture_to_buffer(
+            wgpu::ImageCopyTexture {
+                texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d {
+                    x: position.x,
+                    y: position.y,
+                    z: 0,
+                },
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::ImageCopyBuffer {
+                buffer: &buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: None,
+                    rows_per_image: None,
+                },
+            },
+            wgpu::Extent3d {
+                width: 1,
+                height: 1,
+                depth_or_array_layers: 1,
+            },
+        );
+
+        let (tx, rx) = mpsc::channel();
+
+        Self { buffer, tx, rx }
+    }
+
+    /// Start mapping the buffer, this must be called after the copy is
+    /// submitted.
+    fn map(&self) {
+        let tx = self.tx.clone();
+        self.buffer
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, move |result| {
+                let _ = tx.send(result);
+            });
+    }
+
+    /// Read the mapped ID, [`None`] if it is 0.
+    fn read(self) -> Option<u32> {
+        let id = bytemuck::pod_read_unaligned::<u32>(&self.buffer.slice(..).get_mapped_range());
+        self.buffer.unmap();
+
+        id.checked_sub(1)
+    }
+}
+
+/// Timestamp queries around the render pass, read back through a ring of
+/// buffers so a readback in flight does not stall the next frames.
+struct GpuTimer {
+    query_set: wgpu::QuerySet,
+    resolve_buffer: wgpu::Buffer,
+    readbacks: Vec<GpuTimerReadback>,
+    next: usize,
+    /// The nanoseconds per timestamp tick.
+    period: f32,
+    last_time_ms: Option<f32>,
+}
+
+struct GpuTimerReadback {
+    buffer: wgpu::Buffer,
+    is_pending: bool,
+    tx: mpsc::Sender<Result<(), wgpu::BufferAsyncError>>,
+    rx: mpsc::Receiver<Result<(), wgpu::BufferAsyncError>>,
+}
+
+impl GpuTimer {
+    /// The beginning and end of the render pass.
+    const QUERY_COUNT: u32 = 2;
+
+    const SIZE: wgpu::BufferAddress = Self::QUERY_COUNT as wgpu::BufferAddress
+        * std::mem::size_of::<u64>() as wgpu::BufferAddress;
+
+    /// The number of frames that can be read back at once.
+    const READBACK_COUNT: usize = 3;
+
+    fn new(device: &wgpu::Device, queue: &wgpu::Queue) -> Self {
+        let query_set = device.create_query_set(&wgpu::QuerySetDescriptor {
+            label: Some("GPU Timer Query Set"),
+            ty: wgpu::QueryType::Timestamp,
+            count: Self::QUERY_COUNT,
+        });
+
+        let resolve_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("GPU Timer Resolve Buffer"),
+            size: Self::SIZE,
+            usage: wgpu::BufferUsages::QUERY_RESOLVE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+
+        let readbacks = (0..Self::READBACK_COUNT)
+            .map(|_| {
+                let (tx, rx) = mpsc::channel();
+                GpuTimerReadback {
+                    buffer: device.create_buffer(&wgpu::BufferDescriptor {
+                        label: Some("GPU Timer Readback Buffer"),
+                        size: Self::SIZE,
+                        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+                        mapped_at_creation: false,
+                    }),
+                    is_pending: false,
+                    tx,
+                    rx,
+                }
+            })
+            .collect();
+
+        Self {
+            query_set,
+            resolve_buffer,
+            readbacks,
+            next: 0,
+            period: queue.get_timestamp_period(),
+            last_time_ms: None,
+        }
+    }
+
+    /// Whether the next readback buffer is free to time a frame.
+    fn is_ready(&self) -> bool {
+        !self.readbacks[self.next].is_pending
+    }
+
+    fn timestamp_writes(&self) -> wgpu::RenderPassTimestampWrites {
+        wgpu::RenderPassTimestampWrites {
+            query_set: &self.query_set,
+            beginning_of_pass_write_index: Some(0),
+            end_of_pass_write_index: Some(1),
+        }
+    }
+
+    /// Encode the resolve of the timestamps into the next readback buffer.
+    fn resolve(&self, encoder: &mut wgpu::CommandEncoder) {
+        encoder.resolve_query_set(
+            &self.query_set,
+            0..Self::QUERY_COUNT,
+            &self.resolve_buffer,
+            0,
+        );
+        encoder.copy_buffer_to_buffer(
+            &self.resolve_buffer,
+            0,
+            &self.readbacks[self.next].buffer,
+            0,
+            Self::SIZE,
+        );
+    }
+
+    /// Start mapping the next readback buffer, this must be called after the
+    /// resolve is submitted.
+    fn map(&mut self) {
+        let readback = &mut self.readbacks[self.next];
+        let tx = readback.tx.clone();
+        readback
+            .buffer
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, move |result| {
+                let _ = tx.send(result);
+            });
+        readback.is_pending = true;
+
+        self.next = (self.next + 1) % self.readbacks.len();
+    }
+
+    /// Read the mapped readback buffers into [`GpuTimer::last_time_ms`].
+    fn poll(&mut self) {
+        for readback in self.readbacks.iter_mut().filter(|r| r.is_pending) {
+            match readback.rx.try_recv() {
+                Ok(Ok(())) => {
+                    let timestamps: [u64; 2] =
+                        bytemuck::pod_read_unaligned(&readback.buffer.slice(..).get_mapped_range());
+                    readback.buffer.unmap();
+                    readback.is_pending = false;
+
+                    // The end may be before the beginning if the timestamps
+                    // are reset in between, e.g. on a power state change
+                    if let Some(ticks) = timestamps[1].checked_sub(timestamps[0]) {
+                        self.last_time_ms = Some(ticks as f32 * self.period * 1e-6);
+                    }
+                }
+                Ok(Err(e)) => {
+                    log::warn!("Failed to map GPU timer buffer: {e}");
+                    readback.is_pending = false;
+                }
+                Err(mpsc::TryRecvError::Empty) => {}
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    log::warn!("GPU timer buffer map channel disconnected");
+                    readback.is_pending = false;
+                }
+            }
+        }
+    }
+}
+
+/// A frame being copied from the GPU.
+struct PendingCapture {
+    buffer: wgpu::Buffer,
+    width: u32,
+    height: u32,
+    padded_bytes_per_row: u32,
+    is_bgra: bool,
+    tx: mpsc::Sender<Result<(), wgpu::BufferAsyncError>>,
+    rx: mpsc::Receiver<Result<(), wgpu::BufferAsyncError>>,
+}
+
+impl PendingCapture {
+    const BYTES_PER_PIXEL: u32 = 4;
+
+    /// Encode the copy from the texture into a new buffer.
+    fn new(
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        texture: &wgpu::Texture,
+        format: wgpu::TextureFormat,
+    ) -> Self {
+        let width = texture.width();
+        let height = texture.height();
+
+        // Rows must be aligned to 256 bytes
+        let unpadded_bytes_per_row = width * Self::BYTES_PER_PIXEL;
+        let padded_bytes_per_row = unpadded_bytes_per_row
+            .div_ceil(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT)
+            * wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Frame Capture Buffer"),
+            size: (padded_bytes_per_row * height) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        encoder.copy_texture_to_buffer(
+            texture.as_image_copy(),
+            wgpu::ImageCopyBuffer {
+                buffer: &buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_bytes_per_row),
+                    rows_per_image: Some(height),
+                },
+            },
+            texture.size(),
+        );
+
+        let (tx, rx) = mpsc::channel();
+
+        Self {
+            buffer,
+            width,
+            height,
+            padded_bytes_per_row,
+            is_bgra: matches!(
+                format,
+                wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb
+            ),
+            tx,
+            rx,
+        }
+    }
+
+    /// Start mapping the buffer, this must be called after the copy is
+    /// submitted.
+    fn map(&self) {
+        let tx = self.tx.clone();
+        self.buffer
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, move |result| {
+                let _ = tx.send(result);
+            });
+    }
+
+    /// Read the mapped buffer into a [`CapturedFrame`], removing the row
+    /// padding.
+    fn read(self) -> CapturedFrame {
+        let unpadded_bytes_per_row = (self.width * Self::BYTES_PER_PIXEL) as usize;
+
+        let mut pixels = Vec::with_capacity(unpadded_bytes_per_row * self.height as usize);
+        {
+            let data = self.buffer.slice(..).get_mapped_range();
+            for row in data.chunks(self.padded_bytes_per_row as usize) {
+                pixels.extend_from_slice(&row[..unpadded_bytes_per_row]);
+            }
+        }
+        self.buffer.unmap();
+
+        if self.is_bgra {
+            pixels
+                .chunks_exact_mut(4)
+                .for_each(|pixel| pixel.swap(0, 2));
+        }
+
+        CapturedFrame {
+            width: self.width,
+            height: self.height,
+            pixels,
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum DisplayError {
+    #[error("no compatible adapter found")]
+    NoAdapter,
+    #[error("failed to request device: {0}")]
+    RequestDevice(#[from] wgpu::RequestDeviceError),
+    #[error("failed to create surface: {0}")]
+    SurfaceCreation(#[from] wgpu::CreateSurfaceError),
+    #[error("surface out of memory")]
+    SurfaceOutOfMemory,
+}
+
+/// Builder of [`Display`].
+pub struct DisplayBuilder<T> {
+    window: T,
+    clear_color: RgbColor,
+    sample_count: u32,
+    present_mode: Option<wgpu::PresentMode>,
+    power_preference: wgpu::PowerPreference,
+    backends: Option<wgpu::Backends>,
+    hdr: bool,
+    fxaa: bool,
+    render_scale: f32,
+    background: Background,
+    target_aspect: Option<f32>,
+}
+
+pub mod builder {
+    use super::*;
+
+    pub struct NoWindow;
+    pub struct WithWindow(pub Arc<Window>);
+    pub struct WithRawHandle(pub Box<dyn wgpu::WindowHandle>, pub PhysicalSize<u32>);
+    pub struct WithHeadless(pub PhysicalSize<u32>);
+}
+
+impl DisplayBuilder<builder::NoWindow> {
+    pub fn new() -> Self {
+        Self {
+            window: builder::NoWindow,
+            clear_color: RgbColor::BLACK,
+            sample_count: 1,
+            present_mode: None,
+            power_preference: wgpu::PowerPreference::None,
+            backends: None,
+            hdr: false,
+            fxaa: false,
+            render_scale: 1.0,
+            background: Background::ClearColor,
+            target_aspect: None,
+        }
+    }
+}
+
+impl<T> DisplayBuilder<T> {
+    pub fn with_window(self, window: Arc<Window>) -> DisplayBuilder<builder::WithWindow> {
+        DisplayBuilder {
+            window: builder::WithWindow(window),
+            clear_color: self.clear_color,
+            sample_count: self.sample_count,
+            present_mode: self.present_mode,
+            power_preference: self.power_preference,
+            backends: self.backends,
+            hdr: self.hdr,
+            fxaa: self.fxaa,
+            render_scale: self.render_scale,
+            background: self.background,
+            target_aspect: self.target_aspect,
+        }
+    }
+
+    /// Render to a surface of a raw window handle of the given physical size
+    /// instead of a winit [`Window`], see [`Display::new_raw_handle`].
+    ///
+    /// The handle is any [`wgpu::WindowHandle`], i.e. implementing
+    /// `HasWindowHandle` and `HasDisplayHandle`, and `Send` and `Sync` off the
+    /// web.
+    pub fn with_raw_handle(
+        self,
+        handle: impl wgpu::WindowHandle + 'static,
+        size: PhysicalSize<u32>,
+    ) -> DisplayBuilder<builder::WithRawHandle> {
+        DisplayBuilder {
+            window: builder::WithRawHandle(Box::new(handle), size),
+            clear_color: self.clear_color,
+            sample_count: self.sample_count,
+            present_mode: self.present_mode,
+            power_preference: self.power_preference,
+            backends: self.backends,
+            hdr: self.hdr,
+            fxaa: self.fxaa,
+            render_scale: self.render_scale,
+            background: self.background,
+            target_aspect: self.target_aspect,
+        }
+    }
+
+    /// Render to an offscreen texture of the given size instead of a window.
+    pub fn with_headless(self, width: u32, height: u32) -> DisplayBuilder<builder::WithHeadless> {
+        DisplayBuilder {
+            window: builder::WithHeadless(PhysicalSize::new(width, height)),
+            clear_color: self.clear_color,
+            sample_count: self.sample_count,
+            present_mode: self.present_mode,
+            power_preference: self.power_preference,
+            backends: self.backends,
+            hdr: self.hdr,
+            fxaa: self.fxaa,
+            render_scale: self.render_scale,
+            background: self.background,
+            target_aspect: self.target_aspect,
+        }
+    }
+
+    pub fn with_clear_color(mut self, clear_color: RgbColor) -> Self {
+        self.clear_color = clear_color;
+        self
+    }
+
+    /// Render the scene at a fixed aspect ratio with letterboxing, see
+    /// [`Display::set_target_aspect`].
+    pub fn with_target_aspect(mut self, target_aspect: Option<f32>) -> Self {
+        self.target_aspect = target_aspect;
+        self
+    }
+
+    /// Draw a vertical gradient behind the scene instead of only the clear
+    /// color, see [`Display::set_background`].
+    pub fn with_gradient_background(mut self, top: RgbColor, bottom: RgbColor) -> Self {
+        self.background = Background::Gradient { top, bottom };
+        self
+    }
+
+    /// Set the MSAA sample count, falls back to 1 if not supported.
+    pub fn with_sample_count(mut self, sample_count: u32) -> Self {
+        self.sample_count = sample_count;
+        self
+    }
+
+    /// Set the present mode, falls back to [`wgpu::PresentMode::Fifo`] if not
+    /// supported.
+    pub fn with_present_mode(mut self, present_mode: wgpu::PresentMode) -> Self {
+        self.present_mode = Some(present_mode);
+        self
+    }
+
+    pub fn with_power_preference(mut self, power_preference: wgpu::PowerPreference) -> Self {
+        self.power_preference = power_preference;
+        self
+    }
+
+    /// Set the backends to request adapter from, backends not supported on the
+    /// platform are ignored.
+    pub fn with_backends(mut self, backends: wgpu::Backends) -> Self {
+        self.backends = Some(backends);
+        self
+    }
+
+    /// Render into an HDR target which is tonemapped onto the surface, use
+    /// [`Display::color_format`] for the pipelines drawing in the render pass.
+    pub fn with_hdr(mut self, hdr: bool) -> Self {
+        self.hdr = hdr;
+        self
+    }
+
+    /// Anti-alias the final LDR image with FXAA, useful where MSAA is not
+    /// supported.
+    pub fn with_fxaa(mut self, fxaa: bool) -> Self {
+        self.fxaa = fxaa;
+        self
+    }
+
+    /// Render at the surface size scaled by `render_scale`, defaults to 1, see
+    /// [`Display::set_render_scale`].
+    pub fn with_render_scale(mut self, render_scale: f32) -> Self {
+        self.render_scale = render_scale;
+        self
+    }
+}
+
+impl DisplayBuilder<builder::WithWindow> {
+    pub async fn build(self) -> Result<Display, DisplayError> {
+        Display::new(
+            self.window.0,
+            self.clear_color,
+            self.sample_count,
+            self.present_mode,
+            self.power_preference,
+            self.backends,
+            self.hdr,
+            self.fxaa,
+        )
+        .await
+        .map(|mut display| {
+            display.set_background(self.background);
+            display.set_target_aspect(self.target_aspect);
+            display.set_render_scale(self.render_scale);
+            display
+        })
+    }
+
+    /// Build the display, panics if it fails.
+    pub async fn build_or_panic(self) -> Display {
+        self.build()
+            .await
+            .unwrap_or_else(|e| panic!("Failed to build display: {e}"))
+    }
+}
+
+impl DisplayBuilder<builder::WithRawHandle> {
+    pub async fn build(self) -> Result<Display, DisplayError> {
+        Display::new_raw_handle(
+            self.window.0,
+            self.window.1,
+            self.clear_color,
+            self.sample_count,
+            self.present_mode,
+            self.power_preference,
+            self.backends,
+            self.hdr,
+            self.fxaa,
+        )
+        .await
+        .map(|mut display| {
+            display.set_background(self.background);
+            display.set_target_aspect(self.target_aspect);
+            display.set_render_scale(self.render_scale);
+            display
+        })
+    }
+
+    /// Build the display, panics if it fails.
+    pub async fn build_or_panic(self) -> Display {
+        self.build()
+            .await
+            .unwrap_or_else(|e| panic!("Failed to build display: {e}"))
+    }
+}
+
+impl DisplayBuilder<builder::WithHeadless> {
+    pub async fn build(self) -> Result<Display, DisplayError> {
+        Display::new_headless(
+            self.window.0,
+            self.clear_color,
+            self.sample_count,
+            self.power_preference,
+            self.backends,
+            self.hdr,
+            self.fxaa,
+        )
+        .await
+        .map(|mut display| {
+            display.set_background(self.background);
+            display.set_target_aspect(self.target_aspect);
+            display.set_render_scale(self.render_scale);
+            display
+        })
+    }
+
+    /// Build the display, panics if it fails.
+    pub async fn build_or_panic(self) -> Display {
+        self.build()
+            .await
+            .unwrap_or_else(|e| panic!("Failed to build display: {e}"))
     }
 }
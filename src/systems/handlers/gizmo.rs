@@ -0,0 +1,455 @@
+use glam::*;
+use wgpu::util::DeviceExt;
+
+use crate::systems::{
+    handlers::{Display, RenderPass},
+    RgbColor,
+};
+
+/// Handler for the gizmo showing the local X, Y, and Z axes of an object.
+///
+/// The axes are drawn as lines from the origin of [`Gizmo::transform`],
+/// generated in the vertex shader.
+pub struct Gizmo {
+    model: GizmoModel,
+    transform: Mat4,
+    is_enabled: bool,
+    is_on_top: bool,
+
+    model_buffer: wgpu::Buffer,
+    depth_pipeline: wgpu::RenderPipeline,
+    on_top_pipeline: wgpu::RenderPipeline,
+
+    model_bind_group: wgpu::BindGroup,
+
+    is_model_dirty: bool,
+}
+
+impl Gizmo {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        device: &wgpu::Device,
+        surface_config: &wgpu::SurfaceConfiguration,
+        camera_bind_group_layout: &wgpu::BindGroupLayout,
+        sample_count: u32,
+        color_format: Option<wgpu::TextureFormat>,
+        depth_format: Option<wgpu::TextureFormat>,
+        model: GizmoModel,
+        is_enabled: bool,
+        is_on_top: bool,
+    ) -> Self {
+        let transform = Mat4::IDENTITY;
+
+        log::debug!("Creating gizmo model buffer");
+        let model_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Gizmo Model Buffer"),
+            contents: model.buffer(transform).as_bytes(),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        log::debug!("Creating gizmo model bind group layout");
+        let model_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("Gizmo Model Bind Group Layout"),
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                }],
+            });
+
+        log::debug!("Creating gizmo model bind group");
+        let model_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Gizmo Model Bind Group"),
+            layout: &model_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: model_buffer.as_entire_binding(),
+            }],
+        });
+
+        log::debug!("Creating gizmo shader");
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Gizmo Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("../shaders/gizmo.wgsl").into()),
+        });
+
+        log::debug!("Creating gizmo pipeline layout");
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Gizmo Pipeline Layout"),
+            bind_group_layouts: &[camera_bind_group_layout, &model_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        // The depth test is disabled instead of dropping the depth attachment,
+        // since the pipeline must match the render pass
+        let create_pipeline = |label, depth_compare, depth_write_enabled| {
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some(label),
+                layout: Some(&pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: Some("vertex_main"),
+                    buffers: &[],
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point: Some("fragment_main"),
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format: color_format.unwrap_or(surface_config.format),
+                        blend: Some(wgpu::BlendState::REPLACE),
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                }),
+                primitive: wgpu::PrimitiveState {
+                    topology: wgpu::PrimitiveTopology::LineList,
+                    strip_index_format: None,
+                    front_face: wgpu::FrontFace::Ccw,
+                    cull_mode: None,
+                    polygon_mode: wgpu::PolygonMode::Fill,
+                    unclipped_depth: false,
+                    conservative: false,
+                },
+                depth_stencil: depth_format.map(|format| wgpu::DepthStencilState {
+                    format,
+                    depth_write_enabled,
+                    depth_compare,
+                    stencil: wgpu::StencilState::default(),
+                    bias: wgpu::DepthBiasState::default(),
+                }),
+                multisample: wgpu::MultisampleState {
+                    count: sample_count,
+                    mask: !0,
+                    alpha_to_coverage_enabled: false,
+                },
+                multiview: None,
+                cache: None,
+            })
+        };
+
+        log::debug!("Creating gizmo render pipelines");
+        let depth_pipeline = create_pipeline(
+            "Gizmo Depth Render Pipeline",
+            wgpu::CompareFunction::LessEqual,
+            true,
+        );
+        let on_top_pipeline = create_pipeline(
+            "Gizmo On Top Render Pipeline",
+            wgpu::CompareFunction::Always,
+            false,
+        );
+
+        log::info!("Gizmo handler initialized");
+
+        Self {
+            model,
+            transform,
+            is_enabled,
+            is_on_top,
+
+            model_buffer,
+            depth_pipeline,
+            on_top_pipeline,
+
+            model_bind_group,
+
+            is_model_dirty: false,
+        }
+    }
+
+    pub fn model(&self) -> &GizmoModel {
+        &self.model
+    }
+
+    pub fn set_model(&mut self, model: GizmoModel) {
+        self.model = model;
+        self.is_model_dirty = true;
+    }
+
+    /// The transform of the object the axes are drawn for.
+    pub fn transform(&self) -> Mat4 {
+        self.transform
+    }
+
+    /// Set the transform of the object, e.g. a matrix of
+    /// [`crate::systems::handlers::Pyramid::object_matrices`].
+    pub fn set_transform(&mut self, transform: Mat4) {
+        if transform != self.transform {
+            self.transform = transform;
+            self.is_model_dirty = true;
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.is_enabled
+    }
+
+    pub fn set_enabled(&mut self, is_enabled: bool) {
+        self.is_enabled = is_enabled;
+    }
+
+    /// Whether the axes are drawn over the geometry instead of occluded by it.
+    pub fn is_on_top(&self) -> bool {
+        self.is_on_top
+    }
+
+    pub fn set_on_top(&mut self, is_on_top: bool) {
+        self.is_on_top = is_on_top;
+    }
+
+    /// Render the axes, after the geometry to be drawn on top of it.
+    pub fn render(
+        &mut self,
+        queue: &wgpu::Queue,
+        render_pass: &mut wgpu::RenderPass,
+        camera_bind_group: &wgpu::BindGroup,
+    ) {
+        if !self.is_enabled {
+            return;
+        }
+
+        // Update buffers if dirty
+        if self.is_model_dirty {
+            queue.write_buffer(
+                &self.model_buffer,
+                0,
+                self.model.buffer(self.transform).as_bytes(),
+            );
+            self.is_model_dirty = false;
+        }
+
+        // Render
+        render_pass.set_pipeline(match self.is_on_top {
+            true => &self.on_top_pipeline,
+            false => &self.depth_pipeline,
+        });
+        render_pass.set_bind_group(0, camera_bind_group, &[]);
+        render_pass.set_bind_group(1, &self.model_bind_group, &[]);
+        render_pass.draw(0..6, 0..1);
+    }
+}
+
+impl RenderPass for Gizmo {
+    fn order(&self) -> i32 {
+        500
+    }
+
+    fn draw(
+        &mut self,
+        render_pass: &mut wgpu::RenderPass,
+        display: &Display,
+        camera_bind_group: &wgpu::BindGroup,
+    ) {
+        self.render(display.queue(), render_pass, camera_bind_group);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GizmoModel {
+    /// The length of the axes in the local space of the object.
+    pub length: f32,
+    /// The colors of the X, Y, and Z axes.
+    pub colors: [RgbColor; 3],
+}
+
+impl GizmoModel {
+    fn buffer(&self, transform: Mat4) -> GizmoModelBuffer {
+        GizmoModelBuffer {
+            transform,
+            colors: self.colors.map(|color| color.extend(1.0)),
+            length: self.length,
+            _padding: [0.0; 3],
+        }
+    }
+}
+
+impl Default for GizmoModel {
+    fn default() -> Self {
+        Self {
+            length: 1.5,
+            colors: [RgbColor::RED, RgbColor::LIME, RgbColor::BLUE],
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct GizmoModelBuffer {
+    transform: Mat4,
+    colors: [Vec4; 3],
+    length: f32,
+    _padding: [f32; 3],
+}
+
+impl GizmoModelBuffer {
+    fn as_bytes(&self) -> &[u8] {
+        bytemuck::cast_slice(std::slice::from_ref(self))
+    }
+}
+
+/// Builder of [`Gizmo`].
+pub struct GizmoBuilder<T, U, V> {
+    device: T,
+    surface_config: U,
+    camera_bind_group_layout: V,
+    sample_count: u32,
+    color_format: Option<wgpu::TextureFormat>,
+    depth_format: Option<wgpu::TextureFormat>,
+    model: GizmoModel,
+    is_enabled: bool,
+    is_on_top: bool,
+}
+
+pub mod builder {
+    pub struct NoDevice;
+    pub struct WithDevice<'a>(pub &'a wgpu::Device);
+
+    pub struct NoSurfaceConfig;
+    pub struct WithSurfaceConfig<'a>(pub &'a wgpu::SurfaceConfiguration);
+
+    pub struct NoCameraBindGroupLayout;
+    pub struct WithCameraBindGroupLayout<'a>(pub &'a wgpu::BindGroupLayout);
+}
+
+impl GizmoBuilder<builder::NoDevice, builder::NoSurfaceConfig, builder::NoCameraBindGroupLayout> {
+    pub fn new() -> Self {
+        Self {
+            device: builder::NoDevice,
+            surface_config: builder::NoSurfaceConfig,
+            camera_bind_group_layout: builder::NoCameraBindGroupLayout,
+            sample_count: 1,
+            color_format: None,
+            depth_format: None,
+            model: GizmoModel::default(),
+            is_enabled: false,
+            is_on_top: false,
+        }
+    }
+}
+
+impl<T, U, V> GizmoBuilder<T, U, V> {
+    pub fn with_device(self, device: &wgpu::Device) -> GizmoBuilder<builder::WithDevice, U, V> {
+        GizmoBuilder {
+            device: builder::WithDevice(device),
+            surface_config: self.surface_config,
+            camera_bind_group_layout: self.camera_bind_group_layout,
+            sample_count: self.sample_count,
+            color_format: self.color_format,
+            depth_format: self.depth_format,
+            model: self.model,
+            is_enabled: self.is_enabled,
+            is_on_top: self.is_on_top,
+        }
+    }
+
+    pub fn with_surface_config(
+        self,
+        surface_config: &wgpu::SurfaceConfiguration,
+    ) -> GizmoBuilder<T, builder::WithSurfaceConfig, V> {
+        GizmoBuilder {
+            device: self.device,
+            surface_config: builder::WithSurfaceConfig(surface_config),
+            camera_bind_group_layout: self.camera_bind_group_layout,
+            sample_count: self.sample_count,
+            color_format: self.color_format,
+            depth_format: self.depth_format,
+            model: self.model,
+            is_enabled: self.is_enabled,
+            is_on_top: self.is_on_top,
+        }
+    }
+
+    pub fn with_camera_bind_group_layout(
+        self,
+        camera_bind_group_layout: &wgpu::BindGroupLayout,
+    ) -> GizmoBuilder<T, U, builder::WithCameraBindGroupLayout> {
+        GizmoBuilder {
+            device: self.device,
+            surface_config: self.surface_config,
+            camera_bind_group_layout: builder::WithCameraBindGroupLayout(camera_bind_group_layout),
+            sample_count: self.sample_count,
+            color_format: self.color_format,
+            depth_format: self.depth_format,
+            model: self.model,
+            is_enabled: self.is_enabled,
+            is_on_top: self.is_on_top,
+        }
+    }
+
+    pub fn with_sample_count(mut self, sample_count: u32) -> Self {
+        self.sample_count = sample_count;
+        self
+    }
+
+    /// Set the format of the render pass color attachment, defaults to the
+    /// surface format.
+    pub fn with_color_format(mut self, color_format: wgpu::TextureFormat) -> Self {
+        self.color_format = Some(color_format);
+        self
+    }
+
+    /// Enable depth testing with the format of the render pass depth
+    /// attachment.
+    pub fn with_depth_format(mut self, depth_format: wgpu::TextureFormat) -> Self {
+        self.depth_format = Some(depth_format);
+        self
+    }
+
+    pub fn with_model(mut self, model: GizmoModel) -> Self {
+        self.model = model;
+        self
+    }
+
+    pub fn with_length(mut self, length: f32) -> Self {
+        self.model.length = length;
+        self
+    }
+
+    /// Set the colors of the X, Y, and Z axes, defaults to red, green, and
+    /// blue.
+    pub fn with_colors(mut self, colors: [RgbColor; 3]) -> Self {
+        self.model.colors = colors;
+        self
+    }
+
+    /// Set whether the gizmo is drawn, defaults to false.
+    pub fn with_enabled(mut self, is_enabled: bool) -> Self {
+        self.is_enabled = is_enabled;
+        self
+    }
+
+    /// Set whether the axes are drawn over the geometry, defaults to false.
+    pub fn with_on_top(mut self, is_on_top: bool) -> Self {
+        self.is_on_top = is_on_top;
+        self
+    }
+}
+
+impl<'a>
+    GizmoBuilder<
+        builder::WithDevice<'a>,
+        builder::WithSurfaceConfig<'a>,
+        builder::WithCameraBindGroupLayout<'a>,
+    >
+{
+    pub fn build(self) -> Gizmo {
+        Gizmo::new(
+            self.device.0,
+            self.surface_config.0,
+            self.camera_bind_group_layout.0,
+            self.sample_count,
+            self.color_format,
+            self.depth_format,
+            self.model,
+            self.is_enabled,
+            self.is_on_top,
+        )
+    }
+}
@@ -0,0 +1,373 @@
+use glam::*;
+use wgpu::util::DeviceExt;
+
+use crate::systems::{
+    handlers::{Display, RenderPass},
+    RgbColor,
+};
+
+/// Handler for the reference grid on the XZ plane.
+///
+/// The grid is drawn on a fullscreen triangle, intersecting the view ray with
+/// the plane in the fragment shader.
+pub struct Grid {
+    model: GridModel,
+
+    model_buffer: wgpu::Buffer,
+    render_pipeline: wgpu::RenderPipeline,
+
+    model_bind_group: wgpu::BindGroup,
+
+    is_model_dirty: bool,
+}
+
+impl Grid {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        device: &wgpu::Device,
+        surface_config: &wgpu::SurfaceConfiguration,
+        camera_bind_group_layout: &wgpu::BindGroupLayout,
+        sample_count: u32,
+        color_format: Option<wgpu::TextureFormat>,
+        depth_format: Option<wgpu::TextureFormat>,
+        model: GridModel,
+    ) -> Self {
+        log::debug!("Creating grid model buffer");
+        let model_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Grid Model Buffer"),
+            contents: model.buffer().as_bytes(),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        log::debug!("Creating grid model bind group layout");
+        let model_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("Grid Model Bind Group Layout"),
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                }],
+            });
+
+        log::debug!("Creating grid model bind group");
+        let model_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Grid Model Bind Group"),
+            layout: &model_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: model_buffer.as_entire_binding(),
+            }],
+        });
+
+        log::debug!("Creating grid shader");
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Grid Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("../shaders/grid.wgsl").into()),
+        });
+
+        log::debug!("Creating grid pipeline layout");
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Grid Pipeline Layout"),
+            bind_group_layouts: &[camera_bind_group_layout, &model_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        log::debug!("Creating grid render pipeline");
+        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Grid Render Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vertex_main"),
+                buffers: &[],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fragment_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: color_format.unwrap_or(surface_config.format),
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: depth_format.map(|format| wgpu::DepthStencilState {
+                format,
+                depth_write_enabled: false,
+                depth_compare: wgpu::CompareFunction::LessEqual,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState {
+                count: sample_count,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+            cache: None,
+        });
+
+        log::info!("Grid handler initialized");
+
+        Self {
+            model,
+
+            model_buffer,
+            render_pipeline,
+
+            model_bind_group,
+
+            is_model_dirty: false,
+        }
+    }
+
+    pub fn model(&self) -> &GridModel {
+        &self.model
+    }
+
+    pub fn set_model(&mut self, model: GridModel) {
+        self.model = model;
+        self.is_model_dirty = true;
+    }
+
+    pub fn render(
+        &mut self,
+        queue: &wgpu::Queue,
+        render_pass: &mut wgpu::RenderPass,
+        camera_bind_group: &wgpu::BindGroup,
+    ) {
+        // Update buffers if dirty
+        if self.is_model_dirty {
+            queue.write_buffer(&self.model_buffer, 0, self.model.buffer().as_bytes());
+            self.is_model_dirty = false;
+        }
+
+        // Render
+        render_pass.set_pipeline(&self.render_pipeline);
+        render_pass.set_bind_group(0, camera_bind_group, &[]);
+        render_pass.set_bind_group(1, &self.model_bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+}
+
+impl RenderPass for Grid {
+    fn order(&self) -> i32 {
+        300
+    }
+
+    fn draw(
+        &mut self,
+        render_pass: &mut wgpu::RenderPass,
+        display: &Display,
+        camera_bind_group: &wgpu::BindGroup,
+    ) {
+        self.render(display.queue(), render_pass, camera_bind_group);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GridModel {
+    /// The distance between grid lines.
+    pub spacing: f32,
+    pub color: RgbColor,
+    pub alpha: f32,
+    /// The distance from the camera at which the grid fully fades out.
+    pub fade_distance: f32,
+}
+
+impl GridModel {
+    fn buffer(&self) -> GridModelBuffer {
+        GridModelBuffer {
+            color: self.color.extend(self.alpha),
+            spacing: self.spacing,
+            fade_distance: self.fade_distance,
+            _padding: [0.0; 2],
+        }
+    }
+}
+
+impl Default for GridModel {
+    fn default() -> Self {
+        Self {
+            spacing: 1.0,
+            color: RgbColor::WHITE,
+            alpha: 0.5,
+            fade_distance: 50.0,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct GridModelBuffer {
+    color: Vec4,
+    spacing: f32,
+    fade_distance: f32,
+    _padding: [f32; 2],
+}
+
+impl GridModelBuffer {
+    fn as_bytes(&self) -> &[u8] {
+        bytemuck::cast_slice(std::slice::from_ref(self))
+    }
+}
+
+/// Builder of [`Grid`].
+pub struct GridBuilder<T, U, V> {
+    device: T,
+    surface_config: U,
+    camera_bind_group_layout: V,
+    sample_count: u32,
+    color_format: Option<wgpu::TextureFormat>,
+    depth_format: Option<wgpu::TextureFormat>,
+    model: GridModel,
+}
+
+pub mod builder {
+    pub struct NoDevice;
+    pub struct WithDevice<'a>(pub &'a wgpu::Device);
+
+    pub struct NoSurfaceConfig;
+    pub struct WithSurfaceConfig<'a>(pub &'a wgpu::SurfaceConfiguration);
+
+    pub struct NoCameraBindGroupLayout;
+    pub struct WithCameraBindGroupLayout<'a>(pub &'a wgpu::BindGroupLayout);
+}
+
+impl GridBuilder<builder::NoDevice, builder::NoSurfaceConfig, builder::NoCameraBindGroupLayout> {
+    pub fn new() -> Self {
+        Self {
+            device: builder::NoDevice,
+            surface_config: builder::NoSurfaceConfig,
+            camera_bind_group_layout: builder::NoCameraBindGroupLayout,
+            sample_count: 1,
+            color_format: None,
+            depth_format: None,
+            model: GridModel::default(),
+        }
+    }
+}
+
+impl<T, U, V> GridBuilder<T, U, V> {
+    pub fn with_device(self, device: &wgpu::Device) -> GridBuilder<builder::WithDevice, U, V> {
+        GridBuilder {
+            device: builder::WithDevice(device),
+            surface_config: self.surface_config,
+            camera_bind_group_layout: self.camera_bind_group_layout,
+            sample_count: self.sample_count,
+            color_format: self.color_format,
+            depth_format: self.depth_format,
+            model: self.model,
+        }
+    }
+
+    pub fn with_surface_config(
+        self,
+        surface_config: &wgpu::SurfaceConfiguration,
+    ) -> GridBuilder<T, builder::WithSurfaceConfig, V> {
+        GridBuilder {
+            device: self.device,
+            surface_config: builder::WithSurfaceConfig(surface_config),
+            camera_bind_group_layout: self.camera_bind_group_layout,
+            sample_count: self.sample_count,
+            color_format: self.color_format,
+            depth_format: self.depth_format,
+            model: self.model,
+        }
+    }
+
+    pub fn with_camera_bind_group_layout(
+        self,
+        camera_bind_group_layout: &wgpu::BindGroupLayout,
+    ) -> GridBuilder<T, U, builder::WithCameraBindGroupLayout> {
+        GridBuilder {
+            device: self.device,
+            surface_config: self.surface_config,
+            camera_bind_group_layout: builder::WithCameraBindGroupLayout(camera_bind_group_layout),
+            sample_count: self.sample_count,
+            color_format: self.color_format,
+            depth_format: self.depth_format,
+            model: self.model,
+        }
+    }
+
+    pub fn with_sample_count(mut self, sample_count: u32) -> Self {
+        self.sample_count = sample_count;
+        self
+    }
+
+    /// Set the format of the render pass color attachment, defaults to the
+    /// surface format.
+    pub fn with_color_format(mut self, color_format: wgpu::TextureFormat) -> Self {
+        self.color_format = Some(color_format);
+        self
+    }
+
+    /// Enable depth testing with the format of the render pass depth
+    /// attachment.
+    pub fn with_depth_format(mut self, depth_format: wgpu::TextureFormat) -> Self {
+        self.depth_format = Some(depth_format);
+        self
+    }
+
+    pub fn with_model(mut self, model: GridModel) -> Self {
+        self.model = model;
+        self
+    }
+
+    pub fn with_spacing(mut self, spacing: f32) -> Self {
+        self.model.spacing = spacing;
+        self
+    }
+
+    pub fn with_color(mut self, color: RgbColor) -> Self {
+        self.model.color = color;
+        self
+    }
+
+    pub fn with_alpha(mut self, alpha: f32) -> Self {
+        self.model.alpha = alpha;
+        self
+    }
+
+    pub fn with_fade_distance(mut self, fade_distance: f32) -> Self {
+        self.model.fade_distance = fade_distance;
+        self
+    }
+}
+
+impl<'a>
+    GridBuilder<
+        builder::WithDevice<'a>,
+        builder::WithSurfaceConfig<'a>,
+        builder::WithCameraBindGroupLayout<'a>,
+    >
+{
+    pub fn build(self) -> Grid {
+        Grid::new(
+            self.device.0,
+            self.surface_config.0,
+            self.camera_bind_group_layout.0,
+            self.sample_count,
+            self.color_format,
+            self.depth_format,
+            self.model,
+        )
+    }
+}
@@ -0,0 +1,424 @@
+use glam::*;
+use wgpu::util::DeviceExt;
+
+use crate::systems::{
+    handlers::{Display, RenderPass},
+    RgbColor,
+};
+
+/// Handler for the ground plane receiving the shadow of the directional light.
+pub struct Ground {
+    model: GroundModel,
+
+    model_buffer: wgpu::Buffer,
+    render_pipeline: wgpu::RenderPipeline,
+
+    model_bind_group: wgpu::BindGroup,
+
+    is_model_dirty: bool,
+}
+
+impl Ground {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        device: &wgpu::Device,
+        surface_config: &wgpu::SurfaceConfiguration,
+        camera_bind_group_layout: &wgpu::BindGroupLayout,
+        light_bind_group_layout: &wgpu::BindGroupLayout,
+        sample_count: u32,
+        color_format: Option<wgpu::TextureFormat>,
+        depth_format: Option<wgpu::TextureFormat>,
+        model: GroundModel,
+    ) -> Self {
+        log::debug!("Creating ground model buffer");
+        let model_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Ground Model Buffer"),
+            contents: model.buffer().as_bytes(),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        log::debug!("Creating ground model bind group layout");
+        let model_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("Ground Model Bind Group Layout"),
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                }],
+            });
+
+        log::debug!("Creating ground model bind group");
+        let model_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Ground Model Bind Group"),
+            layout: &model_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: model_buffer.as_entire_binding(),
+            }],
+        });
+
+        log::debug!("Creating ground shader");
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Ground Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("../shaders/ground.wgsl").into()),
+        });
+
+        log::debug!("Creating ground pipeline layout");
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Ground Pipeline Layout"),
+            bind_group_layouts: &[
+                camera_bind_group_layout,
+                &model_bind_group_layout,
+                light_bind_group_layout,
+            ],
+            push_constant_ranges: &[],
+        });
+
+        log::debug!("Creating ground render pipeline");
+        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Ground Render Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vertex_main"),
+                buffers: &[],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fragment_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: color_format.unwrap_or(surface_config.format),
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: Some(wgpu::Face::Back),
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: depth_format.map(|format| wgpu::DepthStencilState {
+                format,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::LessEqual,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState {
+                count: sample_count,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+            cache: None,
+        });
+
+        log::info!("Ground handler initialized");
+
+        Self {
+            model,
+
+            model_buffer,
+            render_pipeline,
+
+            model_bind_group,
+
+            is_model_dirty: false,
+        }
+    }
+
+    pub fn model(&self) -> &GroundModel {
+        &self.model
+    }
+
+    pub fn set_model(&mut self, model: GroundModel) {
+        self.model = model;
+        self.is_model_dirty = true;
+    }
+
+    pub fn render(
+        &mut self,
+        queue: &wgpu::Queue,
+        render_pass: &mut wgpu::RenderPass,
+        camera_bind_group: &wgpu::BindGroup,
+        light_bind_group: &wgpu::BindGroup,
+    ) {
+        // Update buffers if dirty
+        if self.is_model_dirty {
+            queue.write_buffer(&self.model_buffer, 0, self.model.buffer().as_bytes());
+            self.is_model_dirty = false;
+        }
+
+        // Render
+        render_pass.set_pipeline(&self.render_pipeline);
+        render_pass.set_bind_group(0, camera_bind_group, &[]);
+        render_pass.set_bind_group(1, &self.model_bind_group, &[]);
+        render_pass.set_bind_group(2, light_bind_group, &[]);
+        render_pass.draw(0..6, 0..1);
+    }
+
+    /// The [`RenderPass`] drawing the ground lit by the light of
+    /// `light_bind_group`.
+    pub fn pass<'a>(&'a mut self, light_bind_group: &'a wgpu::BindGroup) -> GroundPass<'a> {
+        GroundPass {
+            ground: self,
+            light_bind_group,
+        }
+    }
+}
+
+/// The [`RenderPass`] of [`Ground`] lit by the light of `light_bind_group`,
+/// see [`Ground::pass`].
+pub struct GroundPass<'a> {
+    ground: &'a mut Ground,
+    light_bind_group: &'a wgpu::BindGroup,
+}
+
+impl RenderPass for GroundPass<'_> {
+    fn order(&self) -> i32 {
+        100
+    }
+
+    fn draw(
+        &mut self,
+        render_pass: &mut wgpu::RenderPass,
+        display: &Display,
+        camera_bind_group: &wgpu::BindGroup,
+    ) {
+        self.ground.render(
+            display.queue(),
+            render_pass,
+            camera_bind_group,
+            self.light_bind_group,
+        );
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GroundModel {
+    /// The width and depth of the plane.
+    pub size: f32,
+    /// The height of the plane on the Y axis.
+    pub height: f32,
+    pub color: RgbColor,
+}
+
+impl GroundModel {
+    fn buffer(&self) -> GroundModelBuffer {
+        GroundModelBuffer {
+            color: self.color.extend(1.0),
+            size: self.size,
+            height: self.height,
+            _padding: [0.0; 2],
+        }
+    }
+}
+
+impl Default for GroundModel {
+    fn default() -> Self {
+        Self {
+            size: 10.0,
+            height: 0.0,
+            color: RgbColor::from_rgb_unchecked(0.5, 0.5, 0.5),
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct GroundModelBuffer {
+    color: Vec4,
+    size: f32,
+    height: f32,
+    _padding: [f32; 2],
+}
+
+impl GroundModelBuffer {
+    fn as_bytes(&self) -> &[u8] {
+        bytemuck::cast_slice(std::slice::from_ref(self))
+    }
+}
+
+/// Builder of [`Ground`].
+pub struct GroundBuilder<T, U, V, W> {
+    device: T,
+    surface_config: U,
+    camera_bind_group_layout: V,
+    light_bind_group_layout: W,
+    sample_count: u32,
+    color_format: Option<wgpu::TextureFormat>,
+    depth_format: Option<wgpu::TextureFormat>,
+    model: GroundModel,
+}
+
+pub mod builder {
+    pub struct NoDevice;
+    pub struct WithDevice<'a>(pub &'a wgpu::Device);
+
+    pub struct NoSurfaceConfig;
+    pub struct WithSurfaceConfig<'a>(pub &'a wgpu::SurfaceConfiguration);
+
+    pub struct NoCameraBindGroupLayout;
+    pub struct WithCameraBindGroupLayout<'a>(pub &'a wgpu::BindGroupLayout);
+
+    pub struct NoLightBindGroupLayout;
+    pub struct WithLightBindGroupLayout<'a>(pub &'a wgpu::BindGroupLayout);
+}
+
+impl
+    GroundBuilder<
+        builder::NoDevice,
+        builder::NoSurfaceConfig,
+        builder::NoCameraBindGroupLayout,
+        builder::NoLightBindGroupLayout,
+    >
+{
+    pub fn new() -> Self {
+        Self {
+            device: builder::NoDevice,
+            surface_config: builder::NoSurfaceConfig,
+            camera_bind_group_layout: builder::NoCameraBindGroupLayout,
+            light_bind_group_layout: builder::NoLightBindGroupLayout,
+            sample_count: 1,
+            color_format: None,
+            depth_format: None,
+            model: GroundModel::default(),
+        }
+    }
+}
+
+impl<T, U, V, W> GroundBuilder<T, U, V, W> {
+    pub fn with_device(self, device: &wgpu::Device) -> GroundBuilder<builder::WithDevice, U, V, W> {
+        GroundBuilder {
+            device: builder::WithDevice(device),
+            surface_config: self.surface_config,
+            camera_bind_group_layout: self.camera_bind_group_layout,
+            light_bind_group_layout: self.light_bind_group_layout,
+            sample_count: self.sample_count,
+            color_format: self.color_format,
+            depth_format: self.depth_format,
+            model: self.model,
+        }
+    }
+
+    pub fn with_surface_config(
+        self,
+        surface_config: &wgpu::SurfaceConfiguration,
+    ) -> GroundBuilder<T, builder::WithSurfaceConfig, V, W> {
+        GroundBuilder {
+            device: self.device,
+            surface_config: builder::WithSurfaceConfig(surface_config),
+            camera_bind_group_layout: self.camera_bind_group_layout,
+            light_bind_group_layout: self.light_bind_group_layout,
+            sample_count: self.sample_count,
+            color_format: self.color_format,
+            depth_format: self.depth_format,
+            model: self.model,
+        }
+    }
+
+    pub fn with_camera_bind_group_layout(
+        self,
+        camera_bind_group_layout: &wgpu::BindGroupLayout,
+    ) -> GroundBuilder<T, U, builder::WithCameraBindGroupLayout, W> {
+        GroundBuilder {
+            device: self.device,
+            surface_config: self.surface_config,
+            camera_bind_group_layout: builder::WithCameraBindGroupLayout(camera_bind_group_layout),
+            light_bind_group_layout: self.light_bind_group_layout,
+            sample_count: self.sample_count,
+            color_format: self.color_format,
+            depth_format: self.depth_format,
+            model: self.model,
+        }
+    }
+
+    pub fn with_light_bind_group_layout(
+        self,
+        light_bind_group_layout: &wgpu::BindGroupLayout,
+    ) -> GroundBuilder<T, U, V, builder::WithLightBindGroupLayout> {
+        GroundBuilder {
+            device: self.device,
+            surface_config: self.surface_config,
+            camera_bind_group_layout: self.camera_bind_group_layout,
+            light_bind_group_layout: builder::WithLightBindGroupLayout(light_bind_group_layout),
+            sample_count: self.sample_count,
+            color_format: self.color_format,
+            depth_format: self.depth_format,
+            model: self.model,
+        }
+    }
+
+    pub fn with_sample_count(mut self, sample_count: u32) -> Self {
+        self.sample_count = sample_count;
+        self
+    }
+
+    /// Set the format of the render pass color attachment, defaults to the
+    /// surface format.
+    pub fn with_color_format(mut self, color_format: wgpu::TextureFormat) -> Self {
+        self.color_format = Some(color_format);
+        self
+    }
+
+    /// Enable depth testing with the format of the render pass depth
+    /// attachment.
+    pub fn with_depth_format(mut self, depth_format: wgpu::TextureFormat) -> Self {
+        self.depth_format = Some(depth_format);
+        self
+    }
+
+    pub fn with_model(mut self, model: GroundModel) -> Self {
+        self.model = model;
+        self
+    }
+
+    pub fn with_size(mut self, size: f32) -> Self {
+        self.model.size = size;
+        self
+    }
+
+    pub fn with_height(mut self, height: f32) -> Self {
+        self.model.height = height;
+        self
+    }
+
+    pub fn with_color(mut self, color: RgbColor) -> Self {
+        self.model.color = color;
+        self
+    }
+}
+
+impl<'a>
+    GroundBuilder<
+        builder::WithDevice<'a>,
+        builder::WithSurfaceConfig<'a>,
+        builder::WithCameraBindGroupLayout<'a>,
+        builder::WithLightBindGroupLayout<'a>,
+    >
+{
+    pub fn build(self) -> Ground {
+        Ground::new(
+            self.device.0,
+            self.surface_config.0,
+            self.camera_bind_group_layout.0,
+            self.light_bind_group_layout.0,
+            self.sample_count,
+            self.color_format,
+            self.depth_format,
+            self.model,
+        )
+    }
+}
@@ -0,0 +1,330 @@
+use glam::*;
+use wgpu::util::DeviceExt;
+
+use crate::systems::RgbColor;
+
+/// Handler for the directional light.
+pub struct Light {
+    model: LightModel,
+
+    model_buffer: wgpu::Buffer,
+    shadow_map_view: wgpu::TextureView,
+
+    bind_group_layout: wgpu::BindGroupLayout,
+    bind_group: wgpu::BindGroup,
+    shadow_bind_group_layout: wgpu::BindGroupLayout,
+    shadow_bind_group: wgpu::BindGroup,
+
+    is_model_dirty: bool,
+}
+
+impl Light {
+    pub const SHADOW_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+
+    pub fn new(device: &wgpu::Device, model: LightModel, shadow_map_size: u32) -> Self {
+        log::debug!("Creating light model buffer");
+        let model_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Light Model Buffer"),
+            contents: model.buffer().as_bytes(),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        log::debug!("Creating shadow map of size {shadow_map_size}x{shadow_map_size}");
+        let shadow_map = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Shadow Map"),
+            size: wgpu::Extent3d {
+                width: shadow_map_size,
+                height: shadow_map_size,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: Self::SHADOW_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        let shadow_map_view = shadow_map.create_view(&wgpu::TextureViewDescriptor::default());
+
+        // Linear filtering of the comparison gives bilinear PCF for free
+        let shadow_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Shadow Map Sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            compare: Some(wgpu::CompareFunction::LessEqual),
+            ..Default::default()
+        });
+
+        let model_layout_entry = |visibility| wgpu::BindGroupLayoutEntry {
+            binding: 0,
+            visibility,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+
+        log::debug!("Creating light model bind group layout");
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Light Model Bind Group Layout"),
+            entries: &[
+                model_layout_entry(wgpu::ShaderStages::FRAGMENT),
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Depth,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Comparison),
+                    count: None,
+                },
+            ],
+        });
+
+        log::debug!("Creating light model bind group");
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Light Model Bind Group"),
+            layout: &bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: model_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(&shadow_map_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::Sampler(&shadow_sampler),
+                },
+            ],
+        });
+
+        // The shadow pass cannot bind the shadow map it renders to
+        log::debug!("Creating light shadow bind group layout");
+        let shadow_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("Light Shadow Bind Group Layout"),
+                entries: &[model_layout_entry(wgpu::ShaderStages::VERTEX)],
+            });
+
+        log::debug!("Creating light shadow bind group");
+        let shadow_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Light Shadow Bind Group"),
+            layout: &shadow_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: model_buffer.as_entire_binding(),
+            }],
+        });
+
+        Self {
+            model,
+
+            model_buffer,
+            shadow_map_view,
+
+            bind_group_layout,
+            bind_group,
+            shadow_bind_group_layout,
+            shadow_bind_group,
+
+            is_model_dirty: false,
+        }
+    }
+
+    /// Light bind group layout.
+    pub fn bind_group_layout(&self) -> &wgpu::BindGroupLayout {
+        &self.bind_group_layout
+    }
+
+    /// Light bind group.
+    ///
+    /// The uniform buffer, see [`LightModelBuffer`] for the layout, followed
+    /// by the shadow map and its comparison sampler.
+    pub fn bind_group(&self) -> &wgpu::BindGroup {
+        &self.bind_group
+    }
+
+    /// Light bind group layout for the shadow pass.
+    pub fn shadow_bind_group_layout(&self) -> &wgpu::BindGroupLayout {
+        &self.shadow_bind_group_layout
+    }
+
+    /// Light bind group for the shadow pass.
+    ///
+    /// Only the uniform buffer, since the shadow map is the depth attachment.
+    pub fn shadow_bind_group(&self) -> &wgpu::BindGroup {
+        &self.shadow_bind_group
+    }
+
+    /// The depth attachment of the shadow pass.
+    pub fn shadow_map_view(&self) -> &wgpu::TextureView {
+        &self.shadow_map_view
+    }
+
+    pub fn model(&self) -> &LightModel {
+        &self.model
+    }
+
+    pub fn set_model(&mut self, model: LightModel) {
+        self.model = model;
+        self.is_model_dirty = true;
+    }
+
+    pub fn render(&mut self, queue: &wgpu::Queue) {
+        if self.is_model_dirty {
+            queue.write_buffer(&self.model_buffer, 0, self.model.buffer().as_bytes());
+            self.is_model_dirty = false;
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LightModel {
+    /// The direction the light travels in, normalized when uploaded.
+    pub direction: Vec3,
+    pub color: RgbColor,
+    /// The fraction of the color applied to surfaces facing away.
+    pub ambient: f32,
+    /// The half size of the shadow volume around the origin.
+    pub shadow_extent: f32,
+}
+
+impl LightModel {
+    /// The orthographic view projection from the light's point of view.
+    pub fn view_projection(&self) -> Mat4 {
+        let direction = self.direction.normalize_or(Vec3::NEG_Y);
+        let up = match direction.abs().dot(Vec3::Y) > 0.99 {
+            true => Vec3::Z,
+            false => Vec3::Y,
+        };
+        let view = Mat4::look_to_rh(-direction * self.shadow_extent * 2.0, direction, up);
+        let projection = Mat4::orthographic_rh(
+            -self.shadow_extent,
+            self.shadow_extent,
+            -self.shadow_extent,
+            self.shadow_extent,
+            0.0,
+            self.shadow_extent * 4.0,
+        );
+
+        projection * view
+    }
+
+    pub fn buffer(&self) -> LightModelBuffer {
+        LightModelBuffer {
+            view_projection: self.view_projection(),
+            direction: self.direction.normalize_or(Vec3::NEG_Y).extend(0.0),
+            color: self.color.extend(1.0),
+            ambient: self.ambient,
+            _padding: [0.0; 3],
+        }
+    }
+}
+
+impl Default for LightModel {
+    fn default() -> Self {
+        Self {
+            direction: vec3(-1.0, -2.0, -1.5),
+            color: RgbColor::WHITE,
+            ambient: 0.2,
+            shadow_extent: 5.0,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct LightModelBuffer {
+    pub view_projection: Mat4,
+    pub direction: Vec4,
+    pub color: Vec4,
+    pub ambient: f32,
+    _padding: [f32; 3],
+}
+
+impl LightModelBuffer {
+    pub fn as_bytes(&self) -> &[u8] {
+        bytemuck::cast_slice(std::slice::from_ref(self))
+    }
+}
+
+/// Builder of [`Light`].
+pub struct LightBuilder<T> {
+    device: T,
+    model: LightModel,
+    shadow_map_size: u32,
+}
+
+pub mod builder {
+    pub struct NoDevice;
+    pub struct WithDevice<'a>(pub &'a wgpu::Device);
+}
+
+impl LightBuilder<builder::NoDevice> {
+    pub fn new() -> Self {
+        Self {
+            device: builder::NoDevice,
+            model: LightModel::default(),
+            shadow_map_size: 2048,
+        }
+    }
+}
+
+impl<T> LightBuilder<T> {
+    pub fn with_device(self, device: &wgpu::Device) -> LightBuilder<builder::WithDevice> {
+        LightBuilder {
+            device: builder::WithDevice(device),
+            model: self.model,
+            shadow_map_size: self.shadow_map_size,
+        }
+    }
+
+    pub fn with_model(mut self, model: LightModel) -> Self {
+        self.model = model;
+        self
+    }
+
+    pub fn with_direction(mut self, direction: Vec3) -> Self {
+        self.model.direction = direction;
+        self
+    }
+
+    pub fn with_color(mut self, color: RgbColor) -> Self {
+        self.model.color = color;
+        self
+    }
+
+    pub fn with_ambient(mut self, ambient: f32) -> Self {
+        self.model.ambient = ambient;
+        self
+    }
+
+    pub fn with_shadow_extent(mut self, shadow_extent: f32) -> Self {
+        self.model.shadow_extent = shadow_extent;
+        self
+    }
+
+    /// Set the width and height of the shadow map, defaults to 2048.
+    pub fn with_shadow_map_size(mut self, shadow_map_size: u32) -> Self {
+        self.shadow_map_size = shadow_map_size;
+        self
+    }
+}
+
+impl LightBuilder<builder::WithDevice<'_>> {
+    pub fn build(self) -> Light {
+        Light::new(self.device.0, self.model, self.shadow_map_size)
+    }
+}
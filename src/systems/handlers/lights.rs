@@ -0,0 +1,280 @@
+use glam::*;
+
+use crate::systems::RgbColor;
+
+/// Handler for the point lights.
+pub struct Lights {
+    lights: Vec<PointLight>,
+
+    is_storage: bool,
+
+    buffer: wgpu::Buffer,
+
+    bind_group_layout: wgpu::BindGroupLayout,
+    bind_group: wgpu::BindGroup,
+
+    is_lights_dirty: bool,
+}
+
+impl Lights {
+    /// The number of lights of the uniform buffer, see
+    /// [`LightsBuilder::with_storage`].
+    pub const MAX_UNIFORM_LIGHTS: usize = 16;
+
+    pub fn new(device: &wgpu::Device, lights: Vec<PointLight>, is_storage: bool) -> Self {
+        log::debug!("Creating point lights bind group layout");
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Point Lights Bind Group Layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: match is_storage {
+                        true => wgpu::BufferBindingType::Storage { read_only: true },
+                        false => wgpu::BufferBindingType::Uniform,
+                    },
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+
+        log::debug!("Creating point lights buffer with {} lights", lights.len());
+        let buffer = Self::create_buffer(device, lights.len(), is_storage);
+        let bind_group = Self::create_bind_group(device, &bind_group_layout, &buffer);
+
+        Self {
+            lights,
+            is_storage,
+
+            buffer,
+
+            bind_group_layout,
+            bind_group,
+
+            is_lights_dirty: true,
+        }
+    }
+
+    /// Point lights bind group layout.
+    pub fn bind_group_layout(&self) -> &wgpu::BindGroupLayout {
+        &self.bind_group_layout
+    }
+
+    /// Point lights bind group.
+    ///
+    /// A single read only storage buffer bind group, a [`PointLightsHeader`]
+    /// followed by the array of [`PointLightBuffer`]. Without storage, it is a
+    /// uniform buffer of [`Lights::MAX_UNIFORM_LIGHTS`] instead.
+    pub fn bind_group(&self) -> &wgpu::BindGroup {
+        &self.bind_group
+    }
+
+    pub fn lights(&self) -> &[PointLight] {
+        &self.lights
+    }
+
+    /// Whether the lights are in a storage buffer, see
+    /// [`LightsBuilder::with_storage`].
+    pub fn is_storage(&self) -> bool {
+        self.is_storage
+    }
+
+    /// Sets the point lights.
+    ///
+    /// The buffer is reuploaded on the next render, and reallocated if it is
+    /// too small. Without storage, only the first
+    /// [`Lights::MAX_UNIFORM_LIGHTS`] are lit.
+    pub fn set_lights(&mut self, lights: &[PointLight]) {
+        if !self.is_storage && lights.len() > Self::MAX_UNIFORM_LIGHTS {
+            log::warn!(
+                "Only {} of {} point lights are supported without storage buffers",
+                Self::MAX_UNIFORM_LIGHTS,
+                lights.len()
+            );
+        }
+
+        self.lights = lights.to_vec();
+        self.is_lights_dirty = true;
+    }
+
+    pub fn render(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) {
+        if !self.is_lights_dirty {
+            return;
+        }
+
+        let count = match self.is_storage {
+            true => self.lights.len(),
+            false => self.lights.len().min(Self::MAX_UNIFORM_LIGHTS),
+        };
+
+        if Self::buffer_size(count) > self.buffer.size() {
+            log::debug!("Reallocating point lights buffer for {count} lights");
+            self.buffer = Self::create_buffer(device, count, self.is_storage);
+            self.bind_group =
+                Self::create_bind_group(device, &self.bind_group_layout, &self.buffer);
+        }
+
+        let header = PointLightsHeader {
+            count: count as u32,
+            _padding: [0; 3],
+        };
+        let lights = self.lights[..count]
+            .iter()
+            .map(PointLight::buffer)
+            .collect::<Vec<_>>();
+
+        queue.write_buffer(&self.buffer, 0, bytemuck::bytes_of(&header));
+        if !lights.is_empty() {
+            queue.write_buffer(
+                &self.buffer,
+                std::mem::size_of::<PointLightsHeader>() as wgpu::BufferAddress,
+                bytemuck::cast_slice(&lights),
+            );
+        }
+
+        self.is_lights_dirty = false;
+    }
+
+    /// The size of the buffer for `count` lights.
+    ///
+    /// Space for at least one light is always allocated, since the runtime
+    /// sized array requires one element.
+    fn buffer_size(count: usize) -> wgpu::BufferAddress {
+        (std::mem::size_of::<PointLightsHeader>()
+            + std::mem::size_of::<PointLightBuffer>() * count.max(1)) as wgpu::BufferAddress
+    }
+
+    /// Create the buffer for `count` lights, or the fixed size uniform buffer
+    /// without storage.
+    fn create_buffer(device: &wgpu::Device, count: usize, is_storage: bool) -> wgpu::Buffer {
+        let (size, usage) = match is_storage {
+            true => (Self::buffer_size(count), wgpu::BufferUsages::STORAGE),
+            false => (
+                Self::buffer_size(Self::MAX_UNIFORM_LIGHTS),
+                wgpu::BufferUsages::UNIFORM,
+            ),
+        };
+
+        device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Point Lights Buffer"),
+            size,
+            usage: usage | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        })
+    }
+
+    fn create_bind_group(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        buffer: &wgpu::Buffer,
+    ) -> wgpu::BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Point Lights Bind Group"),
+            layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: buffer.as_entire_binding(),
+            }],
+        })
+    }
+}
+
+/// A point light, attenuated to zero at its range.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PointLight {
+    pub position: Vec3,
+    pub color: RgbColor,
+    pub range: f32,
+}
+
+impl PointLight {
+    pub fn buffer(&self) -> PointLightBuffer {
+        PointLightBuffer {
+            position: self.position,
+            range: self.range,
+            color: self.color.extend(1.0),
+        }
+    }
+}
+
+impl Default for PointLight {
+    fn default() -> Self {
+        Self {
+            position: vec3(0.0, 2.0, 0.0),
+            color: RgbColor::WHITE,
+            range: 5.0,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct PointLightsHeader {
+    pub count: u32,
+    _padding: [u32; 3],
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct PointLightBuffer {
+    pub position: Vec3,
+    pub range: f32,
+    pub color: Vec4,
+}
+
+/// Builder of [`Lights`].
+pub struct LightsBuilder<T> {
+    device: T,
+    lights: Vec<PointLight>,
+    is_storage: bool,
+}
+
+pub mod builder {
+    pub struct NoDevice;
+    pub struct WithDevice<'a>(pub &'a wgpu::Device);
+}
+
+impl LightsBuilder<builder::NoDevice> {
+    pub fn new() -> Self {
+        Self {
+            device: builder::NoDevice,
+            lights: Vec::new(),
+            is_storage: true,
+        }
+    }
+}
+
+impl<T> LightsBuilder<T> {
+    pub fn with_device(self, device: &wgpu::Device) -> LightsBuilder<builder::WithDevice> {
+        LightsBuilder {
+            device: builder::WithDevice(device),
+            lights: self.lights,
+            is_storage: self.is_storage,
+        }
+    }
+
+    /// Set the point lights, defaults to none.
+    pub fn with_lights(mut self, lights: Vec<PointLight>) -> Self {
+        self.lights = lights;
+        self
+    }
+
+    /// Set whether the lights are in a storage buffer, defaults to true.
+    ///
+    /// Without storage, e.g. on WebGL, the lights are in a uniform buffer of
+    /// at most [`Lights::MAX_UNIFORM_LIGHTS`], and the shaders must declare
+    /// them as such, see
+    /// [`crate::systems::handlers::PyramidBuilder::with_point_lights_storage`].
+    pub fn with_storage(mut self, is_storage: bool) -> Self {
+        self.is_storage = is_storage;
+        self
+    }
+}
+
+impl LightsBuilder<builder::WithDevice<'_>> {
+    pub fn build(self) -> Lights {
+        Lights::new(self.device.0, self.lights, self.is_storage)
+    }
+}
@@ -0,0 +1,739 @@
+use std::collections::HashMap;
+
+use glam::*;
+use thiserror::Error;
+use wgpu::util::DeviceExt;
+
+use crate::systems::{handlers::PyramidModel, Transform};
+
+/// Handler for an arbitrary triangle mesh.
+///
+/// This renders with the same vertex layout and shader as
+/// [`crate::systems::handlers::Pyramid`], so until there is lighting the
+/// normals are visualized as colors.
+pub struct Mesh {
+    transform: Transform,
+    data: MeshData,
+
+    transform_buffer: wgpu::Buffer,
+    vertex_buffer: wgpu::Buffer,
+    index_buffer: wgpu::Buffer,
+    render_pipeline: wgpu::RenderPipeline,
+
+    transform_bind_group: wgpu::BindGroup,
+
+    is_transform_dirty: bool,
+}
+
+impl Mesh {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        device: &wgpu::Device,
+        surface_config: &wgpu::SurfaceConfiguration,
+        camera_bind_group_layout: &wgpu::BindGroupLayout,
+        sample_count: u32,
+        color_format: Option<wgpu::TextureFormat>,
+        depth_format: Option<wgpu::TextureFormat>,
+        transform: Transform,
+        data: MeshData,
+    ) -> Self {
+        log::debug!("Creating mesh transform buffer");
+        let transform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Mesh Transform Buffer"),
+            contents: bytemuck::bytes_of(&transform.matrix()),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        log::debug!("Creating mesh transform bind group layout");
+        let transform_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("Mesh Transform Bind Group Layout"),
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                }],
+            });
+
+        log::debug!("Creating mesh transform bind group");
+        let transform_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Mesh Transform Bind Group"),
+            layout: &transform_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: transform_buffer.as_entire_binding(),
+            }],
+        });
+
+        log::debug!(
+            "Creating mesh vertex buffer with {} vertices",
+            data.vertices.len()
+        );
+        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Mesh Vertex Buffer"),
+            contents: bytemuck::cast_slice(&data.vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+
+        log::debug!(
+            "Creating mesh index buffer with {} indices",
+            data.indices.len()
+        );
+        let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Mesh Index Buffer"),
+            contents: bytemuck::cast_slice(&data.indices),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+
+        log::debug!("Creating mesh shader");
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Mesh Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("../shaders/mesh.wgsl").into()),
+        });
+
+        log::debug!("Creating mesh pipeline layout");
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Mesh Pipeline Layout"),
+            bind_group_layouts: &[camera_bind_group_layout, &transform_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        log::debug!("Creating mesh render pipeline");
+        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Mesh Render Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vertex_main"),
+                buffers: &[MeshVertex::BUFFER_LAYOUT],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fragment_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: color_format.unwrap_or(surface_config.format),
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: Some(wgpu::Face::Back),
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: depth_format.map(|format| wgpu::DepthStencilState {
+                format,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::LessEqual,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState {
+                count: sample_count,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+            cache: None,
+        });
+
+        log::info!("Mesh handler initialized");
+
+        Self {
+            transform,
+            data,
+
+            transform_buffer,
+            vertex_buffer,
+            index_buffer,
+            render_pipeline,
+
+            transform_bind_group,
+
+            is_transform_dirty: false,
+        }
+    }
+
+    /// Returns the transform of the mesh.
+    pub fn transform(&self) -> &Transform {
+        &self.transform
+    }
+
+    /// Sets the transform of the mesh.
+    pub fn set_transform(&mut self, transform: Transform) {
+        self.transform = transform;
+        self.is_transform_dirty = true;
+    }
+
+    /// Returns the data of the mesh.
+    pub fn data(&self) -> &MeshData {
+        &self.data
+    }
+
+    /// The minimum and maximum corners of the axis aligned box around the
+    /// mesh with its transform, e.g. for
+    /// [`crate::systems::handlers::Camera::frame_bounds`], [`None`] if there
+    /// are no vertices.
+    pub fn bounding_box(&self) -> Option<(Vec3, Vec3)> {
+        let (min, max) = self.data.bounding_box()?;
+        Some(transform_bounding_box(self.transform.matrix(), min, max))
+    }
+
+    pub fn render(
+        &mut self,
+        queue: &wgpu::Queue,
+        render_pass: &mut wgpu::RenderPass,
+        camera_bind_group: &wgpu::BindGroup,
+    ) {
+        // Update buffers if dirty
+        if self.is_transform_dirty {
+            queue.write_buffer(
+                &self.transform_buffer,
+                0,
+                bytemuck::bytes_of(&self.transform.matrix()),
+            );
+            self.is_transform_dirty = false;
+        }
+
+        // Render
+        render_pass.set_pipeline(&self.render_pipeline);
+        render_pass.set_bind_group(0, camera_bind_group, &[]);
+        render_pass.set_bind_group(1, &self.transform_bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+        render_pass.draw_indexed(0..self.data.indices.len() as u32, 0, 0..1);
+    }
+}
+
+/// The vertices and indices of a triangle mesh.
+#[derive(Debug, Clone)]
+pub struct MeshData {
+    pub vertices: Vec<MeshVertex>,
+    pub indices: Vec<u32>,
+}
+
+impl MeshData {
+    /// Create mesh data from triangle list positions and indices.
+    ///
+    /// If `normals` is [`None`], flat normals are computed, which requires the
+    /// vertices to be split per face.
+    pub fn new(
+        positions: &[Vec3],
+        normals: Option<&[Vec3]>,
+        indices: &[u32],
+    ) -> Result<Self, MeshError> {
+        if indices.is_empty() {
+            return Err(MeshError::Empty);
+        }
+
+        if indices.len() % 3 != 0 {
+            return Err(MeshError::InvalidIndexCount(indices.len()));
+        }
+
+        if let Some(&index) = indices.iter().find(|&&i| i as usize >= positions.len()) {
+            return Err(MeshError::IndexOutOfBounds {
+                index,
+                vertex_count: positions.len(),
+            });
+        }
+
+        match normals {
+            Some(normals) if normals.len() != positions.len() => {
+                Err(MeshError::NormalCountMismatch {
+                    normal_count: normals.len(),
+                    vertex_count: positions.len(),
+                })
+            }
+            Some(normals) => Ok(Self {
+                vertices: positions
+                    .iter()
+                    .zip(normals)
+                    .map(|(&position, &normal)| MeshVertex::new(position, normal))
+                    .collect(),
+                indices: indices.to_vec(),
+            }),
+            None => {
+                let vertices = indices
+                    .chunks_exact(3)
+                    .flat_map(|face| {
+                        let [a, b, c] = [0, 1, 2].map(|i| positions[face[i] as usize]);
+                        let normal = (b - a).cross(c - a).normalize_or_zero();
+                        [a, b, c].map(|position| MeshVertex::new(position, normal))
+                    })
+                    .collect::<Vec<_>>();
+
+                Ok(Self {
+                    indices: (0..vertices.len() as u32).collect(),
+                    vertices,
+                })
+            }
+        }
+    }
+
+    /// The minimum and maximum corners of the axis aligned box around the
+    /// vertices, without a transform, [`None`] if there are no vertices.
+    pub fn bounding_box(&self) -> Option<(Vec3, Vec3)> {
+        self.vertices
+            .iter()
+            .map(|vertex| (vertex.position, vertex.position))
+            .reduce(|(a_min, a_max), (b_min, b_max)| (a_min.min(b_min), a_max.max(b_max)))
+    }
+
+    /// Recompute the normals with `mode`, discarding the existing normals.
+    ///
+    /// [`NormalMode::Smooth`] welds the vertices at the same position, and
+    /// [`NormalMode::Flat`] splits the vertices per face.
+    pub fn with_normal_mode(self, mode: NormalMode) -> Self {
+        match mode {
+            NormalMode::Flat => {
+                let positions = self
+                    .vertices
+                    .iter()
+                    .map(|vertex| vertex.position)
+                    .collect::<Vec<_>>();
+
+                Self::new(&positions, None, &self.indices).expect("valid mesh data")
+            }
+            NormalMode::Smooth => {
+                // Weld by the exact bits of the position
+                let mut welded = HashMap::new();
+                let mut positions = Vec::new();
+                let indices = self
+                    .indices
+                    .iter()
+                    .map(|&i| {
+                        let position = self.vertices[i as usize].position;
+                        *welded
+                            .entry(position.to_array().map(f32::to_bits))
+                            .or_insert_with(|| {
+                                positions.push(position);
+                                positions.len() as u32 - 1
+                            })
+                    })
+                    .collect::<Vec<_>>();
+
+                let normals = smooth_normals(&positions, &indices);
+
+                Self::new(&positions, Some(&normals), &indices).expect("valid mesh data")
+            }
+        }
+    }
+
+    /// Create mesh data of the built-in pyramid.
+    pub fn from_pyramid_model(model: &PyramidModel) -> Self {
+        let positions = model.positions().collect::<Vec<_>>();
+        let indices = model.indices().map(u32::from).collect::<Vec<_>>();
+
+        Self::new(&positions, None, &indices).expect("valid pyramid model")
+    }
+
+    /// Parse mesh data from the bytes of an OBJ file.
+    ///
+    /// All models in the file are merged into one mesh, and materials are
+    /// ignored.
+    #[cfg(feature = "obj")]
+    pub fn from_obj_bytes(bytes: &[u8]) -> Result<Self, MeshError> {
+        let (models, _) = tobj::load_obj_buf(
+            &mut std::io::BufReader::new(bytes),
+            &tobj::LoadOptions {
+                single_index: true,
+                triangulate: true,
+                ..Default::default()
+            },
+            |_| Err(tobj::LoadError::OpenFileFailed),
+        )?;
+
+        let mut positions = Vec::new();
+        let mut normals = Vec::new();
+        let mut indices = Vec::new();
+        let mut has_normals = true;
+
+        for model in models {
+            let mesh = model.mesh;
+            let offset = positions.len() as u32;
+
+            positions.extend(mesh.positions.chunks_exact(3).map(Vec3::from_slice));
+
+            match mesh.normals.is_empty() {
+                true => has_normals = false,
+                false => normals.extend(mesh.normals.chunks_exact(3).map(Vec3::from_slice)),
+            }
+
+            indices.extend(mesh.indices.into_iter().map(|i| i + offset));
+        }
+
+        Self::new(&positions, has_normals.then_some(&normals[..]), &indices)
+    }
+}
+
+/// The normals of each vertex, averaged from the normals of the faces sharing
+/// it weighted by the face area.
+pub fn smooth_normals(positions: &[Vec3], indices: &[u32]) -> Vec<Vec3> {
+    let mut normals = vec![Vec3::ZERO; positions.len()];
+
+    for face in indices.chunks_exact(3) {
+        let [a, b, c] = [0, 1, 2].map(|i| positions[face[i] as usize]);
+
+        // The length of the cross product is twice the area
+        let normal = (b - a).cross(c - a);
+        face.iter().for_each(|&i| normals[i as usize] += normal);
+    }
+
+    normals
+        .into_iter()
+        .map(|normal| normal.normalize_or_zero())
+        .collect()
+}
+
+/// The axis aligned box around the box from `min` to `max` transformed by
+/// `matrix`, which is larger than the transformed box if it is rotated.
+pub fn transform_bounding_box(matrix: Mat4, min: Vec3, max: Vec3) -> (Vec3, Vec3) {
+    (0..8)
+        .map(|i| {
+            let corner = vec3(
+                if i & 1 == 0 { min.x } else { max.x },
+                if i & 2 == 0 { min.y } else { max.y },
+                if i & 4 == 0 { min.z } else { max.z },
+            );
+            matrix.transform_point3(corner)
+        })
+        .fold(
+            (Vec3::splat(f32::INFINITY), Vec3::splat(f32::NEG_INFINITY)),
+            |(min, max), corner| (min.min(corner), max.max(corner)),
+        )
+}
+
+/// How the normals of a mesh are computed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NormalMode {
+    /// One normal per face, so the faces look faceted.
+    #[default]
+    Flat,
+    /// Normals averaged per shared vertex, so the shading is smooth.
+    Smooth,
+}
+
+impl Default for MeshData {
+    fn default() -> Self {
+        Self::from_pyramid_model(&PyramidModel::default())
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct MeshVertex {
+    pub position: Vec3,
+    pub color: Vec3,
+    pub normal: Vec3,
+}
+
+impl MeshVertex {
+    pub const BUFFER_LAYOUT: wgpu::VertexBufferLayout<'static> = wgpu::VertexBufferLayout {
+        array_stride: std::mem::size_of::<MeshVertex>() as wgpu::BufferAddress,
+        step_mode: wgpu::VertexStepMode::Vertex,
+        attributes: &[
+            wgpu::VertexAttribute {
+                offset: 0,
+                format: wgpu::VertexFormat::Float32x3,
+                shader_location: 0,
+            },
+            wgpu::VertexAttribute {
+                offset: std::mem::size_of::<Vec3>() as wgpu::BufferAddress,
+                format: wgpu::VertexFormat::Float32x3,
+                shader_location: 1,
+            },
+            wgpu::VertexAttribute {
+                offset: (std::mem::size_of::<Vec3>() * 2) as wgpu::BufferAddress,
+                format: wgpu::VertexFormat::Float32x3,
+                shader_location: 2,
+            },
+        ],
+    };
+
+    /// Create a vertex colored by its normal.
+    pub fn new(position: Vec3, normal: Vec3) -> Self {
+        Self {
+            position,
+            color: normal * 0.5 + 0.5,
+            normal,
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum MeshError {
+    #[cfg(feature = "obj")]
+    #[error("failed to parse OBJ: {0}")]
+    Obj(#[from] tobj::LoadError),
+    #[error("mesh has no triangles")]
+    Empty,
+    #[error("mesh index count is not a multiple of 3: {0}")]
+    InvalidIndexCount(usize),
+    #[error("mesh index out of bounds: {index} >= {vertex_count}")]
+    IndexOutOfBounds { index: u32, vertex_count: usize },
+    #[error("mesh normal count does not match vertex count: {normal_count} != {vertex_count}")]
+    NormalCountMismatch {
+        normal_count: usize,
+        vertex_count: usize,
+    },
+}
+
+/// Builder of [`Mesh`].
+pub struct MeshBuilder<T, U, V> {
+    device: T,
+    surface_config: U,
+    camera_bind_group_layout: V,
+    sample_count: u32,
+    color_format: Option<wgpu::TextureFormat>,
+    depth_format: Option<wgpu::TextureFormat>,
+    transform: Transform,
+    data: MeshData,
+    normal_mode: Option<NormalMode>,
+}
+
+pub mod builder {
+    pub struct NoDevice;
+    pub struct WithDevice<'a>(pub &'a wgpu::Device);
+
+    pub struct NoSurfaceConfig;
+    pub struct WithSurfaceConfig<'a>(pub &'a wgpu::SurfaceConfiguration);
+
+    pub struct NoCameraBindGroupLayout;
+    pub struct WithCameraBindGroupLayout<'a>(pub &'a wgpu::BindGroupLayout);
+}
+
+impl MeshBuilder<builder::NoDevice, builder::NoSurfaceConfig, builder::NoCameraBindGroupLayout> {
+    pub fn new() -> Self {
+        Self {
+            device: builder::NoDevice,
+            surface_config: builder::NoSurfaceConfig,
+            camera_bind_group_layout: builder::NoCameraBindGroupLayout,
+            sample_count: 1,
+            color_format: None,
+            depth_format: None,
+            transform: Transform::IDENTITY,
+            data: MeshData::default(),
+            normal_mode: None,
+        }
+    }
+}
+
+impl<T, U, V> MeshBuilder<T, U, V> {
+    pub fn with_device(self, device: &wgpu::Device) -> MeshBuilder<builder::WithDevice, U, V> {
+        MeshBuilder {
+            device: builder::WithDevice(device),
+            surface_config: self.surface_config,
+            camera_bind_group_layout: self.camera_bind_group_layout,
+            sample_count: self.sample_count,
+            color_format: self.color_format,
+            depth_format: self.depth_format,
+            transform: self.transform,
+            data: self.data,
+            normal_mode: self.normal_mode,
+        }
+    }
+
+    pub fn with_surface_config(
+        self,
+        surface_config: &wgpu::SurfaceConfiguration,
+    ) -> MeshBuilder<T, builder::WithSurfaceConfig, V> {
+        MeshBuilder {
+            device: self.device,
+            surface_config: builder::WithSurfaceConfig(surface_config),
+            camera_bind_group_layout: self.camera_bind_group_layout,
+            sample_count: self.sample_count,
+            color_format: self.color_format,
+            depth_format: self.depth_format,
+            transform: self.transform,
+            data: self.data,
+            normal_mode: self.normal_mode,
+        }
+    }
+
+    pub fn with_camera_bind_group_layout(
+        self,
+        camera_bind_group_layout: &wgpu::BindGroupLayout,
+    ) -> MeshBuilder<T, U, builder::WithCameraBindGroupLayout> {
+        MeshBuilder {
+            device: self.device,
+            surface_config: self.surface_config,
+            camera_bind_group_layout: builder::WithCameraBindGroupLayout(camera_bind_group_layout),
+            sample_count: self.sample_count,
+            color_format: self.color_format,
+            depth_format: self.depth_format,
+            transform: self.transform,
+            data: self.data,
+            normal_mode: self.normal_mode,
+        }
+    }
+
+    pub fn with_sample_count(mut self, sample_count: u32) -> Self {
+        self.sample_count = sample_count;
+        self
+    }
+
+    /// Set the format of the render pass color attachment, defaults to the
+    /// surface format.
+    pub fn with_color_format(mut self, color_format: wgpu::TextureFormat) -> Self {
+        self.color_format = Some(color_format);
+        self
+    }
+
+    /// Enable depth testing with the format of the render pass depth
+    /// attachment.
+    pub fn with_depth_format(mut self, depth_format: wgpu::TextureFormat) -> Self {
+        self.depth_format = Some(depth_format);
+        self
+    }
+
+    pub fn with_transform(mut self, transform: Transform) -> Self {
+        self.transform = transform;
+        self
+    }
+
+    pub fn with_data(mut self, data: MeshData) -> Self {
+        self.data = data;
+        self
+    }
+
+    /// Recompute the normals of the data when built, see
+    /// [`MeshData::with_normal_mode`].
+    ///
+    /// If not set, the normals of the data are kept.
+    pub fn with_normal_mode(mut self, normal_mode: NormalMode) -> Self {
+        self.normal_mode = Some(normal_mode);
+        self
+    }
+
+    /// Set the data parsed from the bytes of an OBJ file.
+    #[cfg(feature = "obj")]
+    pub fn with_obj_bytes(mut self, bytes: &[u8]) -> Result<Self, MeshError> {
+        self.data = MeshData::from_obj_bytes(bytes)?;
+        Ok(self)
+    }
+}
+
+impl<'a>
+    MeshBuilder<
+        builder::WithDevice<'a>,
+        builder::WithSurfaceConfig<'a>,
+        builder::WithCameraBindGroupLayout<'a>,
+    >
+{
+    pub fn build(self) -> Mesh {
+        Mesh::new(
+            self.device.0,
+            self.surface_config.0,
+            self.camera_bind_group_layout.0,
+            self.sample_count,
+            self.color_format,
+            self.depth_format,
+            self.transform,
+            match self.normal_mode {
+                Some(mode) => self.data.with_normal_mode(mode),
+                None => self.data,
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A cube from -1 to 1 with the corners indexed by the bits of x, y, and
+    /// z, wound counter clockwise from outside.
+    ///
+    /// The faces touching the (1, 1, 1) corner are split on the diagonal
+    /// through it, so its adjacent faces have equal weights.
+    fn cube() -> (Vec<Vec3>, Vec<u32>) {
+        let positions = (0..8)
+            .map(|i| {
+                vec3(
+                    if i & 1 == 0 { -1.0 } else { 1.0 },
+                    if i & 2 == 0 { -1.0 } else { 1.0 },
+                    if i & 4 == 0 { -1.0 } else { 1.0 },
+                )
+            })
+            .collect();
+        let indices = [
+            [7, 5, 1, 3],
+            [7, 3, 2, 6],
+            [7, 6, 4, 5],
+            [4, 6, 2, 0],
+            [1, 5, 4, 0],
+            [2, 3, 1, 0],
+        ]
+        .into_iter()
+        .flat_map(|[a, b, c, d]| [a, b, c, a, c, d])
+        .collect();
+
+        (positions, indices)
+    }
+
+    #[test]
+    fn smooth_normal_at_cube_corner_averages_adjacent_faces() {
+        let (positions, indices) = cube();
+        let normals = smooth_normals(&positions, &indices);
+
+        let expected = (Vec3::X + Vec3::Y + Vec3::Z).normalize();
+        assert!(normals[7].abs_diff_eq(expected, 1e-6));
+    }
+
+    #[test]
+    fn smooth_normal_mode_welds_cube_corners() {
+        let (positions, indices) = cube();
+        let data = MeshData::new(&positions, None, &indices)
+            .unwrap()
+            .with_normal_mode(NormalMode::Smooth);
+
+        assert_eq!(data.vertices.len(), 8);
+        assert_eq!(data.indices.len(), 36);
+
+        let corner = data
+            .vertices
+            .iter()
+            .find(|vertex| vertex.position == Vec3::ONE)
+            .unwrap();
+        let expected = (Vec3::X + Vec3::Y + Vec3::Z).normalize();
+        assert!(corner.normal.abs_diff_eq(expected, 1e-6));
+    }
+
+    #[test]
+    fn flat_normal_mode_splits_cube_faces() {
+        let (positions, indices) = cube();
+        let normals = smooth_normals(&positions, &indices);
+        let data = MeshData::new(&positions, Some(&normals), &indices)
+            .unwrap()
+            .with_normal_mode(NormalMode::Flat);
+
+        assert_eq!(data.vertices.len(), 36);
+
+        // Every vertex lies on its face, 1 unit along the outward face normal
+        for vertex in &data.vertices {
+            assert!((vertex.normal.length() - 1.0).abs() < 1e-6);
+            assert!((vertex.normal.dot(vertex.position) - 1.0).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn empty_data_has_no_bounding_box() {
+        let data = MeshData {
+            vertices: Vec::new(),
+            indices: Vec::new(),
+        };
+
+        assert!(data.bounding_box().is_none());
+    }
+}
@@ -1,13 +1,60 @@
 #![allow(dead_code)]
 
 mod camera;
+mod compute;
 mod cursor_lock;
 mod display;
+mod gizmo;
+mod grid;
+mod ground;
+mod light;
+mod lights;
+mod mesh;
+mod model;
 mod pyramid;
+mod render_pass;
+mod render_target;
+mod skybox;
 mod time;
+mod touch;
 
 pub use camera::*;
+pub use compute::*;
 pub use cursor_lock::*;
 pub use display::*;
+pub use gizmo::*;
+pub use grid::*;
+pub use ground::*;
+pub use light::*;
+pub use lights::*;
+pub use mesh::*;
+pub use model::*;
 pub use pyramid::*;
+pub use render_pass::*;
+pub use render_target::*;
+pub use skybox::*;
 pub use time::*;
+pub use touch::*;
+
+/// A device of the default adapter for the tests of the handlers, [`None`] if
+/// there is no adapter, e.g. on a machine without a GPU.
+#[cfg(test)]
+pub(crate) fn test_device() -> Option<(wgpu::Device, wgpu::Queue)> {
+    futures::executor::block_on(async {
+        let adapter = wgpu::Instance::default()
+            .request_adapter(&wgpu::RequestAdapterOptions::default())
+            .await?;
+
+        adapter
+            .request_device(
+                &wgpu::DeviceDescriptor {
+                    label: Some("Test Device"),
+                    required_limits: adapter.limits(),
+                    ..Default::default()
+                },
+                None,
+            )
+            .await
+            .ok()
+    })
+}
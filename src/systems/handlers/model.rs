@@ -0,0 +1,577 @@
+use glam::*;
+use thiserror::Error;
+use wgpu::util::DeviceExt;
+
+use crate::systems::{
+    handlers::{self, Display, MeshData, MeshError, MeshVertex, RenderPass},
+    Transform,
+};
+
+/// Handler for a model made of multiple meshes, each drawn with its own model
+/// matrix.
+pub struct Model {
+    transform: Transform,
+    data: ModelData,
+
+    primitives: Vec<ModelPrimitive>,
+    render_pipeline: wgpu::RenderPipeline,
+
+    is_transform_dirty: bool,
+}
+
+impl Model {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        device: &wgpu::Device,
+        surface_config: &wgpu::SurfaceConfiguration,
+        camera_bind_group_layout: &wgpu::BindGroupLayout,
+        sample_count: u32,
+        color_format: Option<wgpu::TextureFormat>,
+        depth_format: Option<wgpu::TextureFormat>,
+        transform: Transform,
+        data: ModelData,
+    ) -> Self {
+        log::debug!("Creating model transform bind group layout");
+        let transform_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("Model Transform Bind Group Layout"),
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                }],
+            });
+
+        log::debug!(
+            "Creating model buffers for {} primitives",
+            data.primitives.len()
+        );
+        let primitives = data
+            .primitives
+            .iter()
+            .map(|primitive| {
+                ModelPrimitive::new(
+                    device,
+                    &transform_bind_group_layout,
+                    transform.matrix() * primitive.matrix,
+                    &primitive.mesh,
+                )
+            })
+            .collect();
+
+        log::debug!("Creating model shader");
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Model Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("../shaders/mesh.wgsl").into()),
+        });
+
+        log::debug!("Creating model pipeline layout");
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Model Pipeline Layout"),
+            bind_group_layouts: &[camera_bind_group_layout, &transform_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        log::debug!("Creating model render pipeline");
+        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Model Render Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vertex_main"),
+                buffers: &[MeshVertex::BUFFER_LAYOUT],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fragment_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: color_format.unwrap_or(surface_config.format),
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: Some(wgpu::Face::Back),
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: depth_format.map(|format| wgpu::DepthStencilState {
+                format,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::LessEqual,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState {
+                count: sample_count,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+            cache: None,
+        });
+
+        log::info!("Model handler initialized");
+
+        Self {
+            transform,
+            data,
+
+            primitives,
+            render_pipeline,
+
+            is_transform_dirty: false,
+        }
+    }
+
+    /// Returns the root transform of the model.
+    pub fn transform(&self) -> &Transform {
+        &self.transform
+    }
+
+    /// Sets the root transform of the model, which is applied on top of the
+    /// node hierarchy.
+    pub fn set_transform(&mut self, transform: Transform) {
+        self.transform = transform;
+        self.is_transform_dirty = true;
+    }
+
+    /// Returns the data of the model.
+    pub fn data(&self) -> &ModelData {
+        &self.data
+    }
+
+    /// The minimum and maximum corners of the axis aligned box around all the
+    /// primitives with their transforms, [`None`] if there are no vertices.
+    ///
+    /// Primitives without vertices are skipped.
+    pub fn bounding_box(&self) -> Option<(Vec3, Vec3)> {
+        let root = self.transform.matrix();
+        self.data
+            .primitives
+            .iter()
+            .filter_map(|primitive| {
+                let (min, max) = primitive.mesh.bounding_box()?;
+                Some(handlers::transform_bounding_box(
+                    root * primitive.matrix,
+                    min,
+                    max,
+                ))
+            })
+            .reduce(|(a_min, a_max), (b_min, b_max)| (a_min.min(b_min), a_max.max(b_max)))
+    }
+
+    pub fn render(
+        &mut self,
+        queue: &wgpu::Queue,
+        render_pass: &mut wgpu::RenderPass,
+        camera_bind_group: &wgpu::BindGroup,
+    ) {
+        // Update buffers if dirty
+        if self.is_transform_dirty {
+            let root = self.transform.matrix();
+            for (primitive, data) in self.primitives.iter().zip(&self.data.primitives) {
+                queue.write_buffer(
+                    &primitive.transform_buffer,
+                    0,
+                    bytemuck::bytes_of(&(root * data.matrix)),
+                );
+            }
+            self.is_transform_dirty = false;
+        }
+
+        // Render
+        render_pass.set_pipeline(&self.render_pipeline);
+        render_pass.set_bind_group(0, camera_bind_group, &[]);
+        for primitive in &self.primitives {
+            render_pass.set_bind_group(1, &primitive.transform_bind_group, &[]);
+            render_pass.set_vertex_buffer(0, primitive.vertex_buffer.slice(..));
+            render_pass
+                .set_index_buffer(primitive.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+            render_pass.draw_indexed(0..primitive.index_count, 0, 0..1);
+        }
+    }
+}
+
+impl RenderPass for Model {
+    fn order(&self) -> i32 {
+        200
+    }
+
+    fn draw(
+        &mut self,
+        render_pass: &mut wgpu::RenderPass,
+        display: &Display,
+        camera_bind_group: &wgpu::BindGroup,
+    ) {
+        self.render(display.queue(), render_pass, camera_bind_group);
+    }
+}
+
+/// The GPU resources of a [`ModelPrimitiveData`].
+struct ModelPrimitive {
+    transform_buffer: wgpu::Buffer,
+    vertex_buffer: wgpu::Buffer,
+    index_buffer: wgpu::Buffer,
+    index_count: u32,
+
+    transform_bind_group: wgpu::BindGroup,
+}
+
+impl ModelPrimitive {
+    fn new(
+        device: &wgpu::Device,
+        transform_bind_group_layout: &wgpu::BindGroupLayout,
+        matrix: Mat4,
+        mesh: &MeshData,
+    ) -> Self {
+        let transform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Model Transform Buffer"),
+            contents: bytemuck::bytes_of(&matrix),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let transform_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Model Transform Bind Group"),
+            layout: transform_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: transform_buffer.as_entire_binding(),
+            }],
+        });
+
+        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Model Vertex Buffer"),
+            contents: bytemuck::cast_slice(&mesh.vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+
+        let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Model Index Buffer"),
+            contents: bytemuck::cast_slice(&mesh.indices),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+
+        Self {
+            transform_buffer,
+            vertex_buffer,
+            index_buffer,
+            index_count: mesh.indices.len() as u32,
+
+            transform_bind_group,
+        }
+    }
+}
+
+/// The primitives of a model, with the node hierarchy flattened.
+#[derive(Debug, Clone, Default)]
+pub struct ModelData {
+    pub primitives: Vec<ModelPrimitiveData>,
+}
+
+impl ModelData {
+    /// The fraction of [`ModelData::load_gltf_bytes`] spent parsing the
+    /// document and buffers before the primitives.
+    #[cfg(feature = "gltf")]
+    pub const GLTF_PARSE_PROGRESS: f32 = 0.1;
+
+    /// Parse model data from the bytes of a `.glb` or `.gltf` file.
+    ///
+    /// Only the default scene, or the first scene if there is no default, is
+    /// loaded. External buffers are not supported.
+    #[cfg(feature = "gltf")]
+    pub fn from_gltf_bytes(bytes: &[u8]) -> Result<Self, ModelError> {
+        let (document, buffers) = Self::parse_gltf(bytes)?;
+
+        let primitives = Self::gltf_primitives(&document)?
+            .into_iter()
+            .map(|(primitive, matrix)| Self::load_gltf_primitive(&primitive, matrix, &buffers))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self { primitives })
+    }
+
+    /// Like [`ModelData::from_gltf_bytes`], but yields to the next
+    /// [`crate::engine::PendingResource::poll`] after parsing and after each
+    /// primitive, reporting the progress through `progress`.
+    ///
+    /// The parsing of the document and buffers is not split, so a large
+    /// file still takes a long first frame, see
+    /// [`ModelData::GLTF_PARSE_PROGRESS`].
+    #[cfg(feature = "gltf")]
+    pub async fn load_gltf_bytes(
+        bytes: &[u8],
+        progress: &crate::engine::LoadProgress,
+    ) -> Result<Self, ModelError> {
+        let (document, buffers) = Self::parse_gltf(bytes)?;
+        let gltf_primitives = Self::gltf_primitives(&document)?;
+        progress.set(Self::GLTF_PARSE_PROGRESS);
+        crate::engine::yield_now().await;
+
+        let count = gltf_primitives.len();
+        let mut primitives = Vec::with_capacity(count);
+        for (i, (primitive, matrix)) in gltf_primitives.into_iter().enumerate() {
+            primitives.push(Self::load_gltf_primitive(&primitive, matrix, &buffers)?);
+
+            let loaded = (i + 1) as f32 / count as f32;
+            progress.set(Self::GLTF_PARSE_PROGRESS + (1.0 - Self::GLTF_PARSE_PROGRESS) * loaded);
+            crate::engine::yield_now().await;
+        }
+
+        Ok(Self { primitives })
+    }
+
+    #[cfg(feature = "gltf")]
+    fn parse_gltf(bytes: &[u8]) -> Result<(gltf::Document, Vec<gltf::buffer::Data>), ModelError> {
+        let gltf::Gltf { document, blob } = gltf::Gltf::from_slice(bytes)?;
+        let buffers = gltf::import_buffers(&document, None, blob)?;
+
+        Ok((document, buffers))
+    }
+
+    /// The primitives of the scene to load with the model matrices of their
+    /// nodes, in the order of a depth first traversal.
+    #[cfg(feature = "gltf")]
+    fn gltf_primitives(
+        document: &gltf::Document,
+    ) -> Result<Vec<(gltf::Primitive<'_>, Mat4)>, ModelError> {
+        let scene = document
+            .default_scene()
+            .or_else(|| document.scenes().next())
+            .ok_or(ModelError::NoScene)?;
+
+        let mut primitives = Vec::new();
+        let mut nodes = scene
+            .nodes()
+            .map(|node| (node, Mat4::IDENTITY))
+            .collect::<Vec<_>>();
+        nodes.reverse();
+        while let Some((node, parent_matrix)) = nodes.pop() {
+            let matrix = parent_matrix * Mat4::from_cols_array_2d(&node.transform().matrix());
+
+            if let Some(mesh) = node.mesh() {
+                primitives.extend(mesh.primitives().map(|primitive| (primitive, matrix)));
+            }
+
+            let first_child = nodes.len();
+            nodes.extend(node.children().map(|child| (child, matrix)));
+            nodes[first_child..].reverse();
+        }
+
+        Ok(primitives)
+    }
+
+    #[cfg(feature = "gltf")]
+    fn load_gltf_primitive(
+        primitive: &gltf::Primitive,
+        matrix: Mat4,
+        buffers: &[gltf::buffer::Data],
+    ) -> Result<ModelPrimitiveData, ModelError> {
+        if primitive.mode() != gltf::mesh::Mode::Triangles {
+            return Err(ModelError::UnsupportedMode(primitive.mode()));
+        }
+
+        let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()].0[..]));
+
+        let positions = reader
+            .read_positions()
+            .ok_or(ModelError::MissingPositions)?
+            .map(Vec3::from)
+            .collect::<Vec<_>>();
+        let normals = reader
+            .read_normals()
+            .map(|normals| normals.map(Vec3::from).collect::<Vec<_>>());
+        let tex_coords = reader
+            .read_tex_coords(0)
+            .map(|tex_coords| tex_coords.into_f32().map(Vec2::from).collect());
+        let indices = match reader.read_indices() {
+            Some(indices) => indices.into_u32().collect(),
+            None => (0..positions.len() as u32).collect::<Vec<_>>(),
+        };
+
+        Ok(ModelPrimitiveData {
+            matrix,
+            mesh: MeshData::new(&positions, normals.as_deref(), &indices)?,
+            tex_coords,
+        })
+    }
+}
+
+/// A primitive of a model.
+#[derive(Debug, Clone)]
+pub struct ModelPrimitiveData {
+    /// The model matrix of the node containing the primitive.
+    pub matrix: Mat4,
+    pub mesh: MeshData,
+    /// The texture coordinates, loaded but not yet used in rendering.
+    pub tex_coords: Option<Vec<Vec2>>,
+}
+
+#[derive(Debug, Error)]
+pub enum ModelError {
+    #[cfg(feature = "gltf")]
+    #[error("failed to parse glTF: {0}")]
+    Gltf(#[from] gltf::Error),
+    #[cfg(feature = "gltf")]
+    #[error("unsupported primitive mode: {0:?}")]
+    UnsupportedMode(gltf::mesh::Mode),
+    #[error("model has no scene")]
+    NoScene,
+    #[error("primitive has no positions")]
+    MissingPositions,
+    #[error("invalid primitive mesh: {0}")]
+    Mesh(#[from] MeshError),
+}
+
+/// Builder of [`Model`].
+pub struct ModelBuilder<T, U, V> {
+    device: T,
+    surface_config: U,
+    camera_bind_group_layout: V,
+    sample_count: u32,
+    color_format: Option<wgpu::TextureFormat>,
+    depth_format: Option<wgpu::TextureFormat>,
+    transform: Transform,
+    data: ModelData,
+}
+
+pub mod builder {
+    pub struct NoDevice;
+    pub struct WithDevice<'a>(pub &'a wgpu::Device);
+
+    pub struct NoSurfaceConfig;
+    pub struct WithSurfaceConfig<'a>(pub &'a wgpu::SurfaceConfiguration);
+
+    pub struct NoCameraBindGroupLayout;
+    pub struct WithCameraBindGroupLayout<'a>(pub &'a wgpu::BindGroupLayout);
+}
+
+impl ModelBuilder<builder::NoDevice, builder::NoSurfaceConfig, builder::NoCameraBindGroupLayout> {
+    pub fn new() -> Self {
+        Self {
+            device: builder::NoDevice,
+            surface_config: builder::NoSurfaceConfig,
+            camera_bind_group_layout: builder::NoCameraBindGroupLayout,
+            sample_count: 1,
+            color_format: None,
+            depth_format: None,
+            transform: Transform::IDENTITY,
+            data: ModelData::default(),
+        }
+    }
+}
+
+impl<T, U, V> ModelBuilder<T, U, V> {
+    pub fn with_device(self, device: &wgpu::Device) -> ModelBuilder<builder::WithDevice, U, V> {
+        ModelBuilder {
+            device: builder::WithDevice(device),
+            surface_config: self.surface_config,
+            camera_bind_group_layout: self.camera_bind_group_layout,
+            sample_count: self.sample_count,
+            color_format: self.color_format,
+            depth_format: self.depth_format,
+            transform: self.transform,
+            data: self.data,
+        }
+    }
+
+    pub fn with_surface_config(
+        self,
+        surface_config: &wgpu::SurfaceConfiguration,
+    ) -> ModelBuilder<T, builder::WithSurfaceConfig, V> {
+        ModelBuilder {
+            device: self.device,
+            surface_config: builder::WithSurfaceConfig(surface_config),
+            camera_bind_group_layout: self.camera_bind_group_layout,
+            sample_count: self.sample_count,
+            color_format: self.color_format,
+            depth_format: self.depth_format,
+            transform: self.transform,
+            data: self.data,
+        }
+    }
+
+    pub fn with_camera_bind_group_layout(
+        self,
+        camera_bind_group_layout: &wgpu::BindGroupLayout,
+    ) -> ModelBuilder<T, U, builder::WithCameraBindGroupLayout> {
+        ModelBuilder {
+            device: self.device,
+            surface_config: self.surface_config,
+            camera_bind_group_layout: builder::WithCameraBindGroupLayout(camera_bind_group_layout),
+            sample_count: self.sample_count,
+            color_format: self.color_format,
+            depth_format: self.depth_format,
+            transform: self.transform,
+            data: self.data,
+        }
+    }
+
+    pub fn with_sample_count(mut self, sample_count: u32) -> Self {
+        self.sample_count = sample_count;
+        self
+    }
+
+    /// Set the format of the render pass color attachment, defaults to the
+    /// surface format.
+    pub fn with_color_format(mut self, color_format: wgpu::TextureFormat) -> Self {
+        self.color_format = Some(color_format);
+        self
+    }
+
+    /// Enable depth testing with the format of the render pass depth
+    /// attachment.
+    pub fn with_depth_format(mut self, depth_format: wgpu::TextureFormat) -> Self {
+        self.depth_format = Some(depth_format);
+        self
+    }
+
+    pub fn with_transform(mut self, transform: Transform) -> Self {
+        self.transform = transform;
+        self
+    }
+
+    pub fn with_data(mut self, data: ModelData) -> Self {
+        self.data = data;
+        self
+    }
+
+    /// Set the data parsed from the bytes of a `.glb` or `.gltf` file.
+    #[cfg(feature = "gltf")]
+    pub fn with_gltf_bytes(mut self, bytes: &[u8]) -> Result<Self, ModelError> {
+        self.data = ModelData::from_gltf_bytes(bytes)?;
+        Ok(self)
+    }
+}
+
+impl<'a>
+    ModelBuilder<
+        builder::WithDevice<'a>,
+        builder::WithSurfaceConfig<'a>,
+        builder::WithCameraBindGroupLayout<'a>,
+    >
+{
+    pub fn build(self) -> Model {
+        Model::new(
+            self.device.0,
+            self.surface_config.0,
+            self.camera_bind_group_layout.0,
+            self.sample_count,
+            self.color_format,
+            self.depth_format,
+            self.transform,
+            self.data,
+        )
+    }
+}
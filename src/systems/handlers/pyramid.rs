@@ -1,151 +1,416 @@
-use std::sync::mpsc;
+use std::{ops::Range, sync::mpsc};
 
 use glam::*;
 use wgpu::util::DeviceExt;
 
-use crate::systems::{EngineOutSignal, PyramidTransformUpdateSignal, RgbColor, Transform};
+#[cfg(feature = "rng")]
+use crate::systems::Rng;
+use crate::systems::{
+    self,
+    handlers::{Display, Light, Lights, RenderPass},
+    EngineOutSignal, Frustum, PyramidTransformUpdateSignal, RgbColor, ShaderError, Texture,
+    TextureData, Transform,
+};
 
 /// Handler for the spinning pyramid.
 pub struct Pyramid {
     transform: PyramidTransform,
     model: PyramidModel,
+    instances: Vec<PyramidInstance>,
+    objects: Vec<Transform>,
+    visible_instances: Vec<PyramidInstanceBuffer>,
+    visible_ranges: Vec<Range<u32>>,
+    pick_transforms: Vec<Transform>,
 
     transform_buffer: wgpu::Buffer,
+    transform_stride: wgpu::BufferAddress,
+    material_buffer: wgpu::Buffer,
     model_buffer: wgpu::Buffer,
     index_buffer: wgpu::Buffer,
-    render_pipeline: wgpu::RenderPipeline,
-
-    transform_bind_group: wgpu::BindGroup,
-
+    instance_buffer: wgpu::Buffer,
+    visible_instance_buffer: wgpu::Buffer,
+    pipeline_layout: wgpu::PipelineLayout,
+    pipeline_config: PyramidPipelineConfig,
+    is_point_lights_storage: bool,
+    fill_pipeline: wgpu::RenderPipeline,
+    line_pipeline: Option<wgpu::RenderPipeline>,
+    shadow_pipeline: wgpu::RenderPipeline,
+    pick_pipeline: wgpu::RenderPipeline,
+    polygon_mode: wgpu::PolygonMode,
+    opacity: f32,
+
+    wireframe: PyramidWireframe,
+    wireframe_buffer: wgpu::Buffer,
+    wireframe_bind_group: wgpu::BindGroup,
+    wireframe_pipeline: wgpu::RenderPipeline,
+
+    texture: Texture,
+    bind_group: wgpu::BindGroup,
+
+    is_enabled: bool,
     is_transform_dirty: bool,
     is_model_dirty: bool,
+    is_wireframe_dirty: bool,
+    is_material_dirty: bool,
+    is_visible_dirty: bool,
 }
 
 impl Pyramid {
+    /// The WGSL source of the render pipelines embedded at compile time,
+    /// without the point lights declared by [`Pyramid::point_lights_source`].
+    pub const SHADER_SOURCE: &'static str = include_str!("../shaders/pyramid.wgsl");
+
+    /// The size in bytes of a vertex in [`Pyramid::model_buffer`], a position,
+    /// color, UV, and normal of tightly packed floats.
+    pub const VERTEX_SIZE: usize = std::mem::size_of::<PyramidVertex>();
+
+    /// The maximum number of objects sharing the transform buffer.
+    pub const MAX_OBJECTS: usize = 16;
+
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         device: &wgpu::Device,
+        queue: &wgpu::Queue,
         surface_config: &wgpu::SurfaceConfiguration,
         camera_bind_group_layout: &wgpu::BindGroupLayout,
+        light_bind_group_layout: &wgpu::BindGroupLayout,
+        point_lights_bind_group_layout: &wgpu::BindGroupLayout,
+        shadow_bind_group_layout: &wgpu::BindGroupLayout,
+        sample_count: u32,
+        color_format: Option<wgpu::TextureFormat>,
+        depth_format: Option<wgpu::TextureFormat>,
+        polygon_mode: wgpu::PolygonMode,
+        front_face: wgpu::FrontFace,
+        cull_mode: Option<wgpu::Face>,
+        blend: wgpu::BlendState,
+        is_depth_write: bool,
+        opacity: f32,
+        wireframe: PyramidWireframe,
         transform: PyramidTransform,
         model: PyramidModel,
-    ) -> Self {
-        let indices = model.indices().collect::<Vec<_>>();
+        instances: Vec<PyramidInstance>,
+        objects: Vec<Transform>,
+        texture: &TextureData,
+        texture_mipmaps: bool,
+        texture_anisotropy: u16,
+        is_point_lights_storage: bool,
+    ) -> Result<Self, ShaderError> {
+        let model = Self::validate_model(model);
+        let objects = Self::validate_objects(objects);
+
+        // Each object has a slot of the transform buffer, bound with a dynamic
+        // offset which must be aligned to the device limit
+        let transform_stride = (std::mem::size_of::<PyramidTransformBuffer>()
+            as wgpu::BufferAddress)
+            .next_multiple_of(
+                device.limits().min_uniform_buffer_offset_alignment as wgpu::BufferAddress,
+            );
 
-        log::debug!("Creating pyramid transform buffer");
+        log::debug!(
+            "Creating pyramid transform buffer with {} slots of {transform_stride} bytes",
+            Self::MAX_OBJECTS
+        );
         let transform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Pyramid Transform Buffer"),
-            contents: transform.buffer().as_bytes(),
+            contents: &transform.slots_buffer(&objects, instances.len(), transform_stride),
             usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
         });
 
-        log::debug!("Creating pyramid transform bind group layout");
-        let transform_bind_group_layout =
-            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-                label: Some("Pyramid Transform Bind Group Layout"),
-                entries: &[wgpu::BindGroupLayoutEntry {
-                    binding: 0,
-                    visibility: wgpu::ShaderStages::VERTEX,
-                    ty: wgpu::BindingType::Buffer {
-                        ty: wgpu::BufferBindingType::Uniform,
-                        has_dynamic_offset: false,
-                        min_binding_size: None,
-                    },
-                    count: None,
-                }],
-            });
-
-        log::debug!("Creating pyramid transform bind group");
-        let transform_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: Some("Pyramid Transform Bind Group"),
-            layout: &transform_bind_group_layout,
-            entries: &[wgpu::BindGroupEntry {
-                binding: 0,
-                resource: transform_buffer.as_entire_binding(),
-            }],
+        log::debug!("Creating pyramid material buffer");
+        let material_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Pyramid Material Buffer"),
+            contents: PyramidMaterialBuffer::new(opacity, &model).as_bytes(),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
         });
 
         log::debug!("Creating pyramid model buffer");
         let model_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Pyramid Model Buffer"),
             contents: model.buffer().as_bytes(),
-            usage: wgpu::BufferUsages::VERTEX,
+            usage: wgpu::BufferUsages::VERTEX
+                | wgpu::BufferUsages::STORAGE
+                | wgpu::BufferUsages::COPY_DST,
         });
 
-        log::debug!("Creating pyramid index buffer: {indices:?}");
+        log::debug!(
+            "Creating pyramid index buffer with {} indices",
+            model.index_count()
+        );
         let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Pyramid Index Buffer"),
-            contents: bytemuck::cast_slice(&indices),
-            usage: wgpu::BufferUsages::INDEX,
+            contents: bytemuck::cast_slice(&model.index_buffer()),
+            usage: wgpu::BufferUsages::INDEX | wgpu::BufferUsages::COPY_DST,
         });
 
-        log::debug!("Creating pyramid shader");
-        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-            label: Some("Pyramid Shader"),
-            source: wgpu::ShaderSource::Wgsl(include_str!("../shaders/pyramid.wgsl").into()),
+        log::debug!(
+            "Creating pyramid instance buffer with {} instances",
+            instances.len()
+        );
+        let instance_buffer = Self::create_instance_buffer(device, &instances);
+        let visible_instance_buffer = Self::create_visible_instance_buffer(device, instances.len());
+
+        log::debug!(
+            "Creating pyramid texture of size {}x{}",
+            texture.width,
+            texture.height
+        );
+        let texture = Texture::new(
+            device,
+            queue,
+            texture,
+            texture_mipmaps,
+            texture_anisotropy,
+            Some("Pyramid Texture"),
+        );
+
+        // The transform and texture share a bind group to stay within the
+        // default limit of 4 bind groups
+        log::debug!("Creating pyramid bind group layout");
+        let [texture_layout_entry, sampler_layout_entry] =
+            Texture::bind_group_layout_entries(1, wgpu::ShaderStages::FRAGMENT);
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Pyramid Bind Group Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: true,
+                        min_binding_size: wgpu::BufferSize::new(std::mem::size_of::<
+                            PyramidTransformBuffer,
+                        >()
+                            as wgpu::BufferAddress),
+                    },
+                    count: None,
+                },
+                texture_layout_entry,
+                sampler_layout_entry,
+                wgpu::BindGroupLayoutEntry {
+                    binding: 3,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
         });
 
+        log::debug!("Creating pyramid bind group");
+        let [texture_entry, sampler_entry] = texture.bind_group_entries(1);
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Pyramid Bind Group"),
+            layout: &bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                        buffer: &transform_buffer,
+                        offset: 0,
+                        size: wgpu::BufferSize::new(
+                            std::mem::size_of::<PyramidTransformBuffer>() as wgpu::BufferAddress
+                        ),
+                    }),
+                },
+                texture_entry,
+                sampler_entry,
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: material_buffer.as_entire_binding(),
+                },
+            ],
+        });
+
+        log::debug!("Creating pyramid shader");
+        let shader = systems::create_shader_module(
+            device,
+            "pyramid.wgsl",
+            &Self::point_lights_source(Self::SHADER_SOURCE, is_point_lights_storage),
+        )?;
+
         log::debug!("Creating pyramid pipeline layout");
         let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
             label: Some("Pyramid Pipeline Layout"),
-            bind_group_layouts: &[camera_bind_group_layout, &transform_bind_group_layout],
+            bind_group_layouts: &[
+                camera_bind_group_layout,
+                &bind_group_layout,
+                light_bind_group_layout,
+                point_lights_bind_group_layout,
+            ],
             push_constant_ranges: &[],
         });
 
-        log::debug!("Creating pyramid render pipeline");
-        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("Pyramid Render Pipeline"),
-            layout: Some(&pipeline_layout),
-            vertex: wgpu::VertexState {
-                module: &shader,
-                entry_point: Some("vertex_main"),
-                buffers: &[PyramidVertex::BUFFER_LAYOUT],
-                compilation_options: wgpu::PipelineCompilationOptions::default(),
-            },
-            fragment: Some(wgpu::FragmentState {
-                module: &shader,
-                entry_point: Some("fragment_main"),
-                targets: &[Some(wgpu::ColorTargetState {
-                    format: surface_config.format,
-                    blend: Some(wgpu::BlendState::REPLACE),
-                    write_mask: wgpu::ColorWrites::ALL,
-                })],
-                compilation_options: wgpu::PipelineCompilationOptions::default(),
-            }),
-            primitive: wgpu::PrimitiveState {
-                topology: wgpu::PrimitiveTopology::TriangleList,
-                strip_index_format: None,
-                front_face: wgpu::FrontFace::Ccw,
-                cull_mode: Some(wgpu::Face::Back),
-                polygon_mode: wgpu::PolygonMode::Fill,
-                unclipped_depth: false,
-                conservative: false,
-            },
-            depth_stencil: None,
-            multisample: wgpu::MultisampleState {
-                count: 1,
-                mask: !0,
-                alpha_to_coverage_enabled: false,
-            },
-            multiview: None,
-            cache: None,
+        log::debug!("Creating pyramid render pipelines");
+        let color_format = color_format.unwrap_or(surface_config.format);
+        let pipeline_config = PyramidPipelineConfig {
+            color_format,
+            depth_format,
+            sample_count,
+            front_face,
+            cull_mode,
+            blend,
+            is_depth_write,
+        };
+        let (fill_pipeline, line_pipeline) =
+            Self::create_render_pipelines(device, &pipeline_layout, &shader, &pipeline_config);
+
+        log::debug!("Creating pyramid shadow pipeline");
+        let shadow_shader = systems::create_shader_module(
+            device,
+            "pyramid_shadow.wgsl",
+            include_str!("../shaders/pyramid_shadow.wgsl"),
+        )?;
+        let shadow_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Pyramid Shadow Pipeline Layout"),
+                bind_group_layouts: &[shadow_bind_group_layout, &bind_group_layout],
+                push_constant_ranges: &[],
+            });
+        let shadow_pipeline =
+            Self::create_shadow_pipeline(device, &shadow_pipeline_layout, &shadow_shader);
+
+        log::debug!("Creating pyramid pick pipeline");
+        let pick_shader = systems::create_shader_module(
+            device,
+            "pyramid_pick.wgsl",
+            include_str!("../shaders/pyramid_pick.wgsl"),
+        )?;
+        let pick_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Pyramid Pick Pipeline Layout"),
+            bind_group_layouts: &[camera_bind_group_layout, &bind_group_layout],
+            push_constant_ranges: &[],
         });
+        let pick_pipeline = Self::create_pick_pipeline(
+            device,
+            &pick_pipeline_layout,
+            &pick_shader,
+            front_face,
+            cull_mode,
+        );
+
+        // The overlay is a second draw with unshared vertices generated in the
+        // shader, so it does not need `wgpu::Features::POLYGON_MODE_LINE`
+        log::debug!("Creating pyramid wireframe buffer");
+        let wireframe_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Pyramid Wireframe Buffer"),
+            contents: wireframe.buffer(&model).as_bytes(),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        log::debug!("Creating pyramid wireframe bind group");
+        let wireframe_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("Pyramid Wireframe Bind Group Layout"),
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                }],
+            });
+        let wireframe_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Pyramid Wireframe Bind Group"),
+            layout: &wireframe_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wireframe_buffer.as_entire_binding(),
+            }],
+        });
+
+        log::debug!("Creating pyramid wireframe pipeline");
+        let wireframe_shader = systems::create_shader_module(
+            device,
+            "pyramid_wireframe.wgsl",
+            include_str!("../shaders/pyramid_wireframe.wgsl"),
+        )?;
+        let wireframe_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Pyramid Wireframe Pipeline Layout"),
+                bind_group_layouts: &[
+                    camera_bind_group_layout,
+                    &bind_group_layout,
+                    &wireframe_bind_group_layout,
+                ],
+                push_constant_ranges: &[],
+            });
+        let wireframe_pipeline = Self::create_wireframe_pipeline(
+            device,
+            &wireframe_pipeline_layout,
+            &wireframe_shader,
+            color_format,
+            depth_format,
+            sample_count,
+            front_face,
+            cull_mode,
+        );
+
+        let polygon_mode = Self::validate_polygon_mode(polygon_mode, line_pipeline.is_some());
 
         log::info!("Pyramid handler initialized");
 
-        Self {
+        let mut pyramid = Self {
             transform,
             model,
+            instances,
+            objects,
+            visible_instances: Vec::new(),
+            visible_ranges: Vec::new(),
+            pick_transforms: Vec::new(),
 
             transform_buffer,
+            transform_stride,
+            material_buffer,
             model_buffer,
             index_buffer,
-            render_pipeline,
-
-            transform_bind_group,
-
+            instance_buffer,
+            visible_instance_buffer,
+            pipeline_layout,
+            pipeline_config,
+            is_point_lights_storage,
+            fill_pipeline,
+            line_pipeline,
+            shadow_pipeline,
+            pick_pipeline,
+            polygon_mode,
+            opacity,
+
+            wireframe,
+            wireframe_buffer,
+            wireframe_bind_group,
+            wireframe_pipeline,
+
+            texture,
+            bind_group,
+
+            is_enabled: true,
             is_transform_dirty: false,
             is_model_dirty: false,
-        }
+            is_wireframe_dirty: false,
+            is_material_dirty: false,
+            is_visible_dirty: false,
+        };
+        pyramid.cull(&[]);
+
+        Ok(pyramid)
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.is_enabled
+    }
+
+    /// Enable or disable the pyramid without dropping its resources.
+    ///
+    /// A disabled pyramid neither rotates in [`Pyramid::update`] nor draws in
+    /// any of the render methods.
+    pub fn set_enabled(&mut self, is_enabled: bool) {
+        self.is_enabled = is_enabled;
     }
 
     /// Returns the transform of the pyramid.
@@ -174,6 +439,14 @@ impl Pyramid {
         self.is_transform_dirty = true;
     }
 
+    /// Sets the transform of the pyramid from a matrix.
+    ///
+    /// The matrix is decomposed with [`Transform::from_matrix`], so the
+    /// translation, rotation, and scale remain editable separately.
+    pub fn set_transform_matrix(&mut self, matrix: Mat4) {
+        self.transform_mut().transform = Transform::from_matrix(matrix);
+    }
+
     /// Returns the model of the pyramid.
     pub fn model(&self) -> &PyramidModel {
         &self.model
@@ -195,77 +468,982 @@ impl Pyramid {
     }
 
     /// Sets the model of the pyramid.
+    ///
+    /// The material values out of range are clamped with a warning.
     pub fn set_model(&mut self, model: PyramidModel) {
-        self.model = model;
+        self.model = Self::validate_model(model);
         self.is_model_dirty = true;
     }
 
-    pub fn update(&mut self, dt: f32) {
-        let rotation = self.transform().auto_rotation_speed * dt;
-        self.transform_mut()
-            .transform
-            .rotate(Quat::from_axis_angle(Vec3::Y, rotation));
-    }
+    /// Returns the instances of the pyramid.
+    pub fn instances(&self) -> &[PyramidInstance] {
+        &self.instances
+    }
+
+    /// Sets the instances of the pyramid, each drawn with its own transform
+    /// and color on top of the pyramid transform.
+    ///
+    /// The instance buffer is reuploaded, and reallocated if it is too small.
+    /// The transform dirty flag is set if the number of instances changed,
+    /// since the pick IDs of the objects depend on it. All the instances are
+    /// visible until the next [`Pyramid::cull`].
+    pub fn set_instances(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        instances: &[PyramidInstance],
+    ) {
+        if instances.len() != self.instances.len() {
+            self.is_transform_dirty = true;
+        }
+        self.instances = instances.to_vec();
+
+        let size = (std::mem::size_of::<PyramidInstanceBuffer>() * instances.len()) as u64;
+        if size > self.instance_buffer.size() {
+            log::debug!(
+                "Reallocating pyramid instance buffer for {} instances",
+                instances.len()
+            );
+            self.instance_buffer = Self::create_instance_buffer(device, instances);
+            self.visible_instance_buffer =
+                Self::create_visible_instance_buffer(device, instances.len());
+        } else if size > 0 {
+            queue.write_buffer(
+                &self.instance_buffer,
+                0,
+                bytemuck::cast_slice(&PyramidInstance::buffer(instances)),
+            );
+        }
+
+        self.cull(&[]);
+    }
+
+    /// Resolve the ID of [`crate::systems::handlers::Display::take_picked`] to
+    /// the instance drawn in the last [`Pyramid::render_pick`], [`None`] if the
+    /// ID is not of the pyramid.
+    pub fn picked_object(&self, id: u32) -> Option<PickedObject> {
+        let transform = self.pick_transforms.get(id as usize)?.clone();
+        Some(PickedObject { id, transform })
+    }
+
+    /// Returns the objects of the pyramid.
+    pub fn objects(&self) -> &[Transform] {
+        &self.objects
+    }
+
+    /// Sets the objects of the pyramid, each a copy of all the instances
+    /// placed by its transform, truncated to [`Pyramid::MAX_OBJECTS`].
+    ///
+    /// This sets the transform dirty flag. All the instances are visible until
+    /// the next [`Pyramid::cull`].
+    pub fn set_objects(&mut self, objects: Vec<Transform>) {
+        self.objects = Self::validate_objects(objects);
+        self.is_transform_dirty = true;
+        self.cull(&[]);
+    }
+
+    /// The transforms of the objects, each applied after the pyramid transform.
+    pub fn object_matrices(&self) -> impl Iterator<Item = Mat4> + '_ {
+        let transform = self.transform.transform.matrix();
+        self.objects
+            .iter()
+            .map(move |object| object.matrix() * transform)
+    }
+
+    /// Skip the instances outside all of the frustums in [`Pyramid::render`],
+    /// compacting the visible ones of each object into the instance buffer.
+    ///
+    /// Each instance is tested with the bounding sphere of the model, scaled
+    /// by the largest scale of its transform. Call this each frame after the
+    /// transform and cameras are updated, with the frustum of every camera
+    /// rendering the pyramid, since they share the instance buffer. Empty
+    /// `frustums` make all the instances visible.
+    ///
+    /// The shadow and pick passes draw all the instances, since instances out
+    /// of view still cast shadows, and the pick IDs are the instance indices.
+    pub fn cull(&mut self, frustums: &[Frustum]) {
+        let radius = self.model.bounding_radius();
+        let transform = self.transform.transform.matrix();
+
+        // The vectors keep their capacity, so this only allocates when there
+        // are more visible instances than ever before
+        self.visible_instances.clear();
+        self.visible_ranges.clear();
+        for object in &self.objects {
+            let object_matrix = object.matrix() * transform;
+            let start = self.visible_instances.len() as u32;
+            self.visible_instances.extend(
+                self.instances
+                    .iter()
+                    .filter(|instance| {
+                        let matrix = object_matrix * instance.transform.matrix();
+                        let center = matrix.transform_point3(Vec3::ZERO);
+                        let scale = [matrix.x_axis, matrix.y_axis, matrix.z_axis]
+                            .map(|axis| axis.truncate().length())
+                            .into_iter()
+                            .fold(0.0, f32::max);
+                        frustums.is_empty()
+                            || frustums
+                                .iter()
+                                .any(|frustum| frustum.contains_sphere(center, radius * scale))
+                    })
+                    .map(PyramidInstance::instance_buffer),
+            );
+            self.visible_ranges
+                .push(start..self.visible_instances.len() as u32);
+        }
+
+        self.is_visible_dirty = true;
+    }
+
+    /// The number of instances drawn by [`Pyramid::render`] across all the
+    /// objects after the last [`Pyramid::cull`].
+    pub fn visible_instance_count(&self) -> usize {
+        self.visible_instances.len()
+    }
+
+    /// The number of vertices shared by the faces of the pyramid.
+    pub fn vertex_count(&self) -> usize {
+        self.model.vertex_count()
+    }
+
+    /// The vertex buffer of the model, also usable as a storage buffer.
+    ///
+    /// The buffer has room for the maximum number of sides, with
+    /// [`Pyramid::vertex_count`] vertices of [`Pyramid::VERTEX_SIZE`] used.
+    pub fn model_buffer(&self) -> &wgpu::Buffer {
+        &self.model_buffer
+    }
+
+    /// The number of indices drawn for the pyramid.
+    pub fn index_count(&self) -> usize {
+        self.model.index_count()
+    }
+
+    pub fn opacity(&self) -> f32 {
+        self.opacity
+    }
+
+    /// Sets the opacity of the pyramid, clamped to 0.0 to 1.0.
+    ///
+    /// This only has an effect if the pyramid is built with a blending
+    /// [`wgpu::BlendState`], see [`PyramidBuilder::with_blend`].
+    pub fn set_opacity(&mut self, opacity: f32) {
+        self.opacity = opacity.clamp(0.0, 1.0);
+        self.is_material_dirty = true;
+    }
+
+    pub fn wireframe(&self) -> &PyramidWireframe {
+        &self.wireframe
+    }
+
+    /// Sets the wireframe overlay drawn on top of the shaded pyramid.
+    pub fn set_wireframe(&mut self, wireframe: PyramidWireframe) {
+        self.wireframe = wireframe;
+        self.is_wireframe_dirty = true;
+    }
+
+    pub fn polygon_mode(&self) -> wgpu::PolygonMode {
+        self.polygon_mode
+    }
+
+    /// Sets the polygon mode of the pyramid.
+    ///
+    /// Falls back to [`wgpu::PolygonMode::Fill`] if the device does not support
+    /// [`wgpu::Features::POLYGON_MODE_LINE`].
+    pub fn set_polygon_mode(&mut self, polygon_mode: wgpu::PolygonMode) {
+        self.polygon_mode = Self::validate_polygon_mode(polygon_mode, self.line_pipeline.is_some());
+    }
+
+    /// Recreate the render pipelines from the WGSL `source`.
+    ///
+    /// If the shader fails to compile or does not match the pipelines, the
+    /// error is returned and the last pipelines are kept. The errors are only
+    /// known synchronously on native, see
+    /// [`systems::capture_validation_error`].
+    pub fn reload_shader(
+        &mut self,
+        device: &wgpu::Device,
+        source: &str,
+    ) -> Result<(), ShaderError> {
+        log::debug!("Reloading pyramid shader");
+        let shader = systems::create_shader_module(
+            device,
+            "pyramid.wgsl",
+            &Self::point_lights_source(source, self.is_point_lights_storage),
+        )?;
+        let ((fill_pipeline, line_pipeline), error) =
+            systems::capture_validation_error(device, || {
+                Self::create_render_pipelines(
+                    device,
+                    &self.pipeline_layout,
+                    &shader,
+                    &self.pipeline_config,
+                )
+            });
+
+        if let Some(e) = error {
+            return Err(ShaderError::Validation {
+                name: "pyramid.wgsl".to_string(),
+                message: e.to_string(),
+            });
+        }
+
+        self.fill_pipeline = fill_pipeline;
+        self.line_pipeline = line_pipeline;
+        log::info!("Pyramid shader reloaded");
+
+        Ok(())
+    }
+
+    /// The pyramid shader `source` with the point lights declared at the end,
+    /// as a storage buffer or without storage as a uniform array of
+    /// [`Lights::MAX_UNIFORM_LIGHTS`], see
+    /// [`crate::systems::handlers::LightsBuilder::with_storage`].
+    ///
+    /// The declaration is appended so the lines of compilation errors are
+    /// still the lines in `source`.
+    fn point_lights_source(source: &str, is_storage: bool) -> String {
+        let (lights, address_space) = match is_storage {
+            true => ("array<PointLight>".to_string(), "storage, read"),
+            false => (
+                format!("array<PointLight, {}>", Lights::MAX_UNIFORM_LIGHTS),
+                "uniform",
+            ),
+        };
+
+        format!(
+            "{source}
+struct PointLights {{
+    count: u32,
+    lights: {lights},
+}}
+@group(3) @binding(0)
+var<{address_space}> point_lights: PointLights;
+"
+        )
+    }
+
+    /// Sets the axis and the speed in radians per second of the auto rotation.
+    pub fn set_auto_rotation(&mut self, axis: Vec3, speed: f32) {
+        let transform = self.transform_mut();
+        transform.auto_rotation_axis = axis;
+        transform.auto_rotation_speed = speed;
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        if !self.is_enabled {
+            return;
+        }
+
+        // Zero speed or axis stops the rotation without dirtying the transform
+        let axis = self.transform().auto_rotation_axis.normalize_or_zero();
+        let speed = self.transform().auto_rotation_speed;
+        if speed == 0.0 || axis == Vec3::ZERO {
+            return;
+        }
+
+        self.transform_mut()
+            .transform
+            .rotate(Quat::from_axis_angle(axis, speed * dt));
+    }
+
+    pub fn signal(&self, tx: &mpsc::Sender<EngineOutSignal>) {
+        if self.is_transform_dirty {
+            tx.send(PyramidTransformUpdateSignal::out_signal(
+                self.transform.clone(),
+            ))
+            .unwrap();
+        }
+    }
+
+    /// Render the pyramid, with the vertices of `vertex_buffer` if given, e.g.
+    /// [`crate::systems::handlers::Compute::vertex_buffer`].
+    pub fn render(
+        &mut self,
+        display: &Display,
+        render_pass: &mut wgpu::RenderPass,
+        camera_bind_group: &wgpu::BindGroup,
+        light_bind_group: &wgpu::BindGroup,
+        point_lights_bind_group: &wgpu::BindGroup,
+        vertex_buffer: Option<&wgpu::Buffer>,
+    ) {
+        if !self.is_enabled {
+            return;
+        }
+
+        self.write_buffers(display);
+
+        if self.instances.is_empty() {
+            return;
+        }
+
+        // Render
+        render_pass.set_pipeline(match (self.polygon_mode, self.line_pipeline.as_ref()) {
+            (wgpu::PolygonMode::Line, Some(line_pipeline)) => line_pipeline,
+            _ => &self.fill_pipeline,
+        });
+        render_pass.set_bind_group(0, camera_bind_group, &[]);
+        render_pass.set_bind_group(2, light_bind_group, &[]);
+        render_pass.set_bind_group(3, point_lights_bind_group, &[]);
+        self.draw(render_pass, vertex_buffer, true);
+
+        // Wireframe overlay
+        if self.wireframe.is_enabled {
+            render_pass.set_pipeline(&self.wireframe_pipeline);
+            render_pass.set_bind_group(2, &self.wireframe_bind_group, &[]);
+            for (offset, instances, instance_count) in self.object_draws(true) {
+                render_pass.set_bind_group(1, &self.bind_group, &[offset]);
+                render_pass.set_vertex_buffer(0, instances);
+                render_pass.draw(0..self.model.index_count() as u32, 0..instance_count);
+            }
+        }
+    }
+
+    /// The [`RenderPass`] drawing the pyramid lit by the lights of
+    /// `light_bind_group` and `point_lights_bind_group`, see
+    /// [`Pyramid::render`].
+    pub fn pass<'a>(
+        &'a mut self,
+        light_bind_group: &'a wgpu::BindGroup,
+        point_lights_bind_group: &'a wgpu::BindGroup,
+        vertex_buffer: Option<&'a wgpu::Buffer>,
+    ) -> PyramidPass<'a> {
+        PyramidPass {
+            pyramid: self,
+            light_bind_group,
+            point_lights_bind_group,
+            vertex_buffer,
+        }
+    }
+
+    /// Render the depth of the pyramid from the light's point of view.
+    pub fn render_shadow(
+        &mut self,
+        display: &Display,
+        render_pass: &mut wgpu::RenderPass,
+        shadow_bind_group: &wgpu::BindGroup,
+        vertex_buffer: Option<&wgpu::Buffer>,
+    ) {
+        if !self.is_enabled {
+            return;
+        }
+
+        self.write_buffers(display);
+
+        if self.instances.is_empty() {
+            return;
+        }
+
+        render_pass.set_pipeline(&self.shadow_pipeline);
+        render_pass.set_bind_group(0, shadow_bind_group, &[]);
+        self.draw(render_pass, vertex_buffer, false);
+    }
+
+    /// Render the instance index of each pixel for picking.
+    ///
+    /// The transforms of the rendered instances are kept for
+    /// [`Pyramid::picked_object`], so a pick read back later resolves to the
+    /// instances it was rendered with, even if they are set in between.
+    pub fn render_pick(
+        &mut self,
+        display: &Display,
+        render_pass: &mut wgpu::RenderPass,
+        camera_bind_group: &wgpu::BindGroup,
+        vertex_buffer: Option<&wgpu::Buffer>,
+    ) {
+        if !self.is_enabled {
+            self.pick_transforms.clear();
+            return;
+        }
+
+        self.write_buffers(display);
+
+        // In the order of the pick IDs, see `PyramidTransform::slots_buffer`
+        self.pick_transforms = self
+            .object_matrices()
+            .flat_map(|object| {
+                self.instances.iter().map(move |instance| {
+                    Transform::from_matrix(object * instance.transform.matrix())
+                })
+            })
+            .collect();
+
+        if self.instances.is_empty() {
+            return;
+        }
+
+        render_pass.set_pipeline(&self.pick_pipeline);
+        render_pass.set_bind_group(0, camera_bind_group, &[]);
+        self.draw(render_pass, vertex_buffer, false);
+    }
+
+    /// Update buffers if dirty, through the staging belt of the display.
+    ///
+    /// This is done by the render methods, call it before to use the buffers
+    /// in an earlier pass, e.g. [`crate::systems::handlers::Compute::compute`].
+    pub fn write_buffers(&mut self, display: &Display) {
+        if self.is_transform_dirty {
+            display.write_buffer(
+                &self.transform_buffer,
+                0,
+                &self.transform.slots_buffer(
+                    &self.objects,
+                    self.instances.len(),
+                    self.transform_stride,
+                ),
+            );
+            self.is_transform_dirty = false;
+        }
+
+        // The material is part of the model
+        if self.is_material_dirty || self.is_model_dirty {
+            display.write_buffer(
+                &self.material_buffer,
+                0,
+                PyramidMaterialBuffer::new(self.opacity, &self.model).as_bytes(),
+            );
+            self.is_material_dirty = false;
+        }
+
+        // The overlay generates the vertices from the model
+        if self.is_wireframe_dirty || self.is_model_dirty {
+            display.write_buffer(
+                &self.wireframe_buffer,
+                0,
+                self.wireframe.buffer(&self.model).as_bytes(),
+            );
+            self.is_wireframe_dirty = false;
+        }
+
+        if self.is_model_dirty {
+            display.write_buffer(&self.model_buffer, 0, self.model.buffer().as_bytes());
+            display.write_buffer(
+                &self.index_buffer,
+                0,
+                bytemuck::cast_slice(&self.model.index_buffer()),
+            );
+            self.is_model_dirty = false;
+        }
+
+        if self.is_visible_dirty {
+            if !self.visible_instances.is_empty() {
+                display.write_buffer(
+                    &self.visible_instance_buffer,
+                    0,
+                    bytemuck::cast_slice(&self.visible_instances),
+                );
+            }
+            self.is_visible_dirty = false;
+        }
+    }
+
+    /// The dynamic offsets of the transform buffer slots of the objects.
+    fn transform_offsets(&self) -> impl Iterator<Item = wgpu::DynamicOffset> + '_ {
+        (0..self.objects.len())
+            .map(|i| (i as wgpu::BufferAddress * self.transform_stride) as wgpu::DynamicOffset)
+    }
+
+    /// The dynamic offset of the transform buffer slot, the instance buffer
+    /// slice, and the instance count of each object with instances to draw.
+    ///
+    /// If `is_culled`, the visible instances of the last [`Pyramid::cull`] are
+    /// drawn, otherwise all of them. The slices are offset instead of using the
+    /// first instance of the draw, which is not supported on all backends.
+    fn object_draws(
+        &self,
+        is_culled: bool,
+    ) -> impl Iterator<Item = (wgpu::DynamicOffset, wgpu::BufferSlice<'_>, u32)> + '_ {
+        let instance_size = std::mem::size_of::<PyramidInstanceBuffer>() as wgpu::BufferAddress;
+        self.transform_offsets()
+            .enumerate()
+            .filter_map(move |(i, offset)| {
+                let (buffer, range) = match is_culled {
+                    true => (
+                        &self.visible_instance_buffer,
+                        self.visible_ranges[i].clone(),
+                    ),
+                    false => (&self.instance_buffer, 0..self.instances.len() as u32),
+                };
+                (!range.is_empty()).then(|| {
+                    let slice = buffer.slice(
+                        range.start as wgpu::BufferAddress * instance_size
+                            ..range.end as wgpu::BufferAddress * instance_size,
+                    );
+                    (offset, slice, range.len() as u32)
+                })
+            })
+    }
+
+    /// Draw each object with `vertex_buffer` in place of the model buffer if
+    /// given, which should have the same layout, see [`Pyramid::object_draws`]
+    /// for `is_culled`.
+    ///
+    /// This binds the bind group of the pyramid at group 1.
+    fn draw(
+        &self,
+        render_pass: &mut wgpu::RenderPass,
+        vertex_buffer: Option<&wgpu::Buffer>,
+        is_culled: bool,
+    ) {
+        // Calculate lengths
+        let model_buffer_len =
+            (std::mem::size_of::<PyramidVertex>() * self.model.vertex_count()) as u64;
+        let index_buffer_len = (std::mem::size_of::<u16>() * self.model.index_count()) as u64;
+
+        render_pass.set_vertex_buffer(
+            0,
+            vertex_buffer
+                .unwrap_or(&self.model_buffer)
+                .slice(..model_buffer_len),
+        );
+        render_pass.set_index_buffer(
+            self.index_buffer.slice(..index_buffer_len),
+            wgpu::IndexFormat::Uint16,
+        );
+        for (offset, instances, instance_count) in self.object_draws(is_culled) {
+            render_pass.set_bind_group(1, &self.bind_group, &[offset]);
+            render_pass.set_vertex_buffer(1, instances);
+            render_pass.draw_indexed(0..self.model.index_count() as u32, 0, 0..instance_count);
+        }
+    }
+
+    fn create_instance_buffer(
+        device: &wgpu::Device,
+        instances: &[PyramidInstance],
+    ) -> wgpu::Buffer {
+        let usage = wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST;
+
+        // Buffer cannot be empty, so allocate space for one instance
+        match instances.is_empty() {
+            true => device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("Pyramid Instance Buffer"),
+                size: std::mem::size_of::<PyramidInstanceBuffer>() as wgpu::BufferAddress,
+                usage,
+                mapped_at_creation: false,
+            }),
+            false => device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Pyramid Instance Buffer"),
+                contents: bytemuck::cast_slice(&PyramidInstance::buffer(instances)),
+                usage,
+            }),
+        }
+    }
+
+    /// Create the buffer of the visible instances, with room for all the
+    /// instances of the maximum number of objects.
+    fn create_visible_instance_buffer(
+        device: &wgpu::Device,
+        instance_count: usize,
+    ) -> wgpu::Buffer {
+        device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Pyramid Visible Instance Buffer"),
+            size: (std::mem::size_of::<PyramidInstanceBuffer>()
+                * Self::MAX_OBJECTS
+                * instance_count.max(1)) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        })
+    }
+
+    fn validate_polygon_mode(
+        polygon_mode: wgpu::PolygonMode,
+        is_line_supported: bool,
+    ) -> wgpu::PolygonMode {
+        match (polygon_mode, is_line_supported) {
+            (wgpu::PolygonMode::Fill, _) | (wgpu::PolygonMode::Line, true) => polygon_mode,
+            _ => {
+                log::warn!("Polygon mode {polygon_mode:?} is not supported, falling back to fill");
+                wgpu::PolygonMode::Fill
+            }
+        }
+    }
+
+    fn create_shadow_pipeline(
+        device: &wgpu::Device,
+        layout: &wgpu::PipelineLayout,
+        shader: &wgpu::ShaderModule,
+    ) -> wgpu::RenderPipeline {
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Pyramid Shadow Render Pipeline"),
+            layout: Some(layout),
+            vertex: wgpu::VertexState {
+                module: shader,
+                entry_point: Some("vertex_main"),
+                buffers: &[
+                    PyramidVertex::BUFFER_LAYOUT,
+                    PyramidInstanceBuffer::BUFFER_LAYOUT,
+                ],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: None,
+            // No culling so that the pyramid casts a shadow from either side
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                cull_mode: None,
+                ..Default::default()
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: Light::SHADOW_FORMAT,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::LessEqual,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState {
+                    constant: 2,
+                    slope_scale: 2.0,
+                    clamp: 0.0,
+                },
+            }),
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        })
+    }
+
+    fn create_pick_pipeline(
+        device: &wgpu::Device,
+        layout: &wgpu::PipelineLayout,
+        shader: &wgpu::ShaderModule,
+        front_face: wgpu::FrontFace,
+        cull_mode: Option<wgpu::Face>,
+    ) -> wgpu::RenderPipeline {
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Pyramid Pick Render Pipeline"),
+            layout: Some(layout),
+            vertex: wgpu::VertexState {
+                module: shader,
+                entry_point: Some("vertex_main"),
+                buffers: &[
+                    PyramidVertex::BUFFER_LAYOUT,
+                    PyramidInstanceBuffer::BUFFER_LAYOUT,
+                ],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: shader,
+                entry_point: Some("fragment_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: Display::PICK_FORMAT,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                front_face,
+                cull_mode,
+                ..Default::default()
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: Display::DEPTH_FORMAT,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::LessEqual,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        })
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn create_wireframe_pipeline(
+        device: &wgpu::Device,
+        layout: &wgpu::PipelineLayout,
+        shader: &wgpu::ShaderModule,
+        color_format: wgpu::TextureFormat,
+        depth_format: Option<wgpu::TextureFormat>,
+        sample_count: u32,
+        front_face: wgpu::FrontFace,
+        cull_mode: Option<wgpu::Face>,
+    ) -> wgpu::RenderPipeline {
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Pyramid Wireframe Render Pipeline"),
+            layout: Some(layout),
+            vertex: wgpu::VertexState {
+                module: shader,
+                entry_point: Some("vertex_main"),
+                buffers: &[PyramidInstanceBuffer::BUFFER_LAYOUT],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: shader,
+                entry_point: Some("fragment_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: color_format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face,
+                cull_mode,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            // Biased towards the camera to not fight with the shaded faces
+            depth_stencil: depth_format.map(|format| wgpu::DepthStencilState {
+                format,
+                depth_write_enabled: false,
+                depth_compare: wgpu::CompareFunction::LessEqual,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState {
+                    constant: -2,
+                    slope_scale: -1.0,
+                    clamp: 0.0,
+                },
+            }),
+            multisample: wgpu::MultisampleState {
+                count: sample_count,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+            cache: None,
+        })
+    }
+
+    /// Clamp the material values into range with a warning.
+    fn validate_model(mut model: PyramidModel) -> PyramidModel {
+        for (name, value) in [
+            ("metallic", &mut model.metallic),
+            ("roughness", &mut model.roughness),
+        ] {
+            if !(0.0..=1.0).contains(value) {
+                let clamped = match value.is_nan() {
+                    true => 0.0,
+                    false => value.clamp(0.0, 1.0),
+                };
+                log::warn!("Pyramid {name} {value} is not in 0.0 to 1.0, clamping to {clamped}");
+                *value = clamped;
+            }
+        }
+
+        model
+    }
+
+    /// Truncate the objects to [`Pyramid::MAX_OBJECTS`] with a warning.
+    fn validate_objects(mut objects: Vec<Transform>) -> Vec<Transform> {
+        if objects.len() > Self::MAX_OBJECTS {
+            log::warn!(
+                "{} pyramid objects exceed the maximum of {}, truncating",
+                objects.len(),
+                Self::MAX_OBJECTS
+            );
+            objects.truncate(Self::MAX_OBJECTS);
+        }
+
+        objects
+    }
+
+    /// Create the fill pipeline, and the line pipeline if supported.
+    fn create_render_pipelines(
+        device: &wgpu::Device,
+        layout: &wgpu::PipelineLayout,
+        shader: &wgpu::ShaderModule,
+        config: &PyramidPipelineConfig,
+    ) -> (wgpu::RenderPipeline, Option<wgpu::RenderPipeline>) {
+        let create = |polygon_mode| {
+            Self::create_render_pipeline(
+                device,
+                layout,
+                shader,
+                config.color_format,
+                config.depth_format,
+                config.sample_count,
+                polygon_mode,
+                config.front_face,
+                config.cull_mode,
+                config.blend,
+                config.is_depth_write,
+            )
+        };
+
+        let fill_pipeline = create(wgpu::PolygonMode::Fill);
+        let line_pipeline = device
+            .features()
+            .contains(wgpu::Features::POLYGON_MODE_LINE)
+            .then(|| create(wgpu::PolygonMode::Line));
+
+        (fill_pipeline, line_pipeline)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn create_render_pipeline(
+        device: &wgpu::Device,
+        layout: &wgpu::PipelineLayout,
+        shader: &wgpu::ShaderModule,
+        color_format: wgpu::TextureFormat,
+        depth_format: Option<wgpu::TextureFormat>,
+        sample_count: u32,
+        polygon_mode: wgpu::PolygonMode,
+        front_face: wgpu::FrontFace,
+        cull_mode: Option<wgpu::Face>,
+        blend: wgpu::BlendState,
+        is_depth_write: bool,
+    ) -> wgpu::RenderPipeline {
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some(&format!("Pyramid {polygon_mode:?} Render Pipeline")),
+            layout: Some(layout),
+            vertex: wgpu::VertexState {
+                module: shader,
+                entry_point: Some("vertex_main"),
+                buffers: &[
+                    PyramidVertex::BUFFER_LAYOUT,
+                    PyramidInstanceBuffer::BUFFER_LAYOUT,
+                ],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: shader,
+                entry_point: Some("fragment_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: color_format,
+                    blend: Some(blend),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face,
+                cull_mode,
+                polygon_mode,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: depth_format.map(|format| wgpu::DepthStencilState {
+                format,
+                depth_write_enabled: is_depth_write,
+                depth_compare: wgpu::CompareFunction::LessEqual,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState {
+                count: sample_count,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+            cache: None,
+        })
+    }
+}
+
+/// The [`RenderPass`] of [`Pyramid`] with the lights and the vertices it is
+/// rendered with, see [`Pyramid::pass`].
+pub struct PyramidPass<'a> {
+    pyramid: &'a mut Pyramid,
+    light_bind_group: &'a wgpu::BindGroup,
+    point_lights_bind_group: &'a wgpu::BindGroup,
+    vertex_buffer: Option<&'a wgpu::Buffer>,
+}
 
-    pub fn signal(&self, tx: &mpsc::Sender<EngineOutSignal>) {
-        if self.is_transform_dirty {
-            tx.send(PyramidTransformUpdateSignal::out_signal(
-                self.transform.clone(),
-            ))
-            .unwrap();
-        }
+impl RenderPass for PyramidPass<'_> {
+    fn order(&self) -> i32 {
+        400
     }
 
-    pub fn render(
+    fn draw(
         &mut self,
-        queue: &wgpu::Queue,
         render_pass: &mut wgpu::RenderPass,
+        display: &Display,
         camera_bind_group: &wgpu::BindGroup,
     ) {
-        // Update buffers if dirty
-        if self.is_transform_dirty {
-            queue.write_buffer(
-                &self.transform_buffer,
-                0,
-                self.transform.buffer().as_bytes(),
-            );
-            self.is_transform_dirty = false;
-        }
+        self.pyramid.render(
+            display,
+            render_pass,
+            camera_bind_group,
+            self.light_bind_group,
+            self.point_lights_bind_group,
+            self.vertex_buffer,
+        );
+    }
+}
 
-        if self.is_model_dirty {
-            queue.write_buffer(&self.model_buffer, 0, self.model.buffer().as_bytes());
-            self.is_model_dirty = false;
-        }
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct PyramidMaterialBuffer {
+    base_color: Vec3,
+    opacity: f32,
+    emissive: Vec3,
+    metallic: f32,
+    roughness: f32,
+    _padding: [f32; 3],
+}
 
-        // Calculate lengths
-        let model_buffer_len =
-            (std::mem::size_of::<PyramidVertex>() * (self.model.side_count + 1)) as u64;
-        let index_buffer_len = (std::mem::size_of::<u16>() * self.model.side_count * 3) as u64;
+impl PyramidMaterialBuffer {
+    fn new(opacity: f32, model: &PyramidModel) -> Self {
+        Self {
+            base_color: *model.base_color,
+            opacity,
+            emissive: *model.emissive,
+            metallic: model.metallic,
+            roughness: model.roughness,
+            _padding: [0.0; 3],
+        }
+    }
 
-        // Render
-        render_pass.set_pipeline(&self.render_pipeline);
-        render_pass.set_bind_group(0, camera_bind_group, &[]);
-        render_pass.set_bind_group(1, &self.transform_bind_group, &[]);
-        render_pass.set_vertex_buffer(0, self.model_buffer.slice(..model_buffer_len));
-        render_pass.set_index_buffer(
-            self.index_buffer.slice(..index_buffer_len),
-            wgpu::IndexFormat::Uint16,
-        );
-        render_pass.draw_indexed(0..self.model.side_count as u32 * 3, 0, 0..1);
+    fn as_bytes(&self) -> &[u8] {
+        bytemuck::cast_slice(std::slice::from_ref(self))
     }
 }
 
+/// The states of the render pipelines, kept to recreate them when the shader
+/// is reloaded.
+#[derive(Debug, Clone, Copy)]
+struct PyramidPipelineConfig {
+    color_format: wgpu::TextureFormat,
+    depth_format: Option<wgpu::TextureFormat>,
+    sample_count: u32,
+    front_face: wgpu::FrontFace,
+    cull_mode: Option<wgpu::Face>,
+    blend: wgpu::BlendState,
+    is_depth_write: bool,
+}
+
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct PyramidTransform {
     pub transform: Transform,
+    /// The axis of the auto rotation, normalized when applied.
+    pub auto_rotation_axis: Vec3,
+    /// The speed of the auto rotation in radians per second.
     pub auto_rotation_speed: f32,
 }
 
 impl PyramidTransform {
-    fn buffer(&self) -> PyramidTransformBuffer {
-        PyramidTransformBuffer {
-            transform: self.transform.matrix(),
+    /// The transform buffer slots of the objects, each `stride` bytes.
+    ///
+    /// The pyramid spins in place and is then placed by each object, and the
+    /// pick IDs of the instances of each object follow those of the previous.
+    fn slots_buffer(&self, objects: &[Transform], instance_count: usize, stride: u64) -> Vec<u8> {
+        let transform = self.transform.matrix();
+
+        let mut buffer = vec![0; Pyramid::MAX_OBJECTS * stride as usize];
+        for (i, object) in objects.iter().enumerate() {
+            let slot = PyramidTransformBuffer {
+                transform: object.matrix() * transform,
+                id_offset: (i * instance_count) as u32,
+                _padding: [0; 3],
+            };
+            let start = i * stride as usize;
+            buffer[start..start + std::mem::size_of::<PyramidTransformBuffer>()]
+                .copy_from_slice(slot.as_bytes());
         }
+
+        buffer
     }
 }
 
@@ -273,6 +1451,7 @@ impl Default for PyramidTransform {
     fn default() -> Self {
         Self {
             transform: Transform::IDENTITY,
+            auto_rotation_axis: Vec3::Y,
             auto_rotation_speed: 1.0,
         }
     }
@@ -282,6 +1461,8 @@ impl Default for PyramidTransform {
 #[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
 struct PyramidTransformBuffer {
     transform: Mat4,
+    id_offset: u32,
+    _padding: [u32; 3],
 }
 
 impl PyramidTransformBuffer {
@@ -290,14 +1471,213 @@ impl PyramidTransformBuffer {
     }
 }
 
+/// An instance picked with [`crate::systems::handlers::Display::request_pick`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct PickedObject {
+    /// The pick ID, the index of the instance in all the objects.
+    pub id: u32,
+    /// The world transform of the instance when it was picked.
+    pub transform: Transform,
+}
+
+/// An instance of the pyramid.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PyramidInstance {
+    pub transform: Transform,
+    pub color: RgbColor,
+}
+
+impl PyramidInstance {
+    /// Scatter the instances on the XZ plane within the radius, with random
+    /// yaw, scale, and hue.
+    ///
+    /// This is deterministic for the seed of `rng`.
+    #[cfg(feature = "rng")]
+    pub fn scatter(rng: &mut Rng, count: usize, radius: f32) -> Vec<Self> {
+        (0..count)
+            .map(|_| {
+                let position = rng.in_disk(radius);
+                Self {
+                    transform: Transform::new(
+                        vec3(position.x, 0.0, position.y),
+                        Quat::from_rotation_y(rng.unit() * std::f32::consts::TAU),
+                        Vec3::splat(rng.range(0.5..1.5)),
+                    ),
+                    color: RgbColor::from_hue_unchecked(rng.unit()),
+                }
+            })
+            .collect()
+    }
+
+    fn buffer(instances: &[Self]) -> Vec<PyramidInstanceBuffer> {
+        instances.iter().map(Self::instance_buffer).collect()
+    }
+
+    fn instance_buffer(&self) -> PyramidInstanceBuffer {
+        PyramidInstanceBuffer {
+            model: self.transform.matrix(),
+            color: self.color.extend(1.0),
+        }
+    }
+}
+
+impl Default for PyramidInstance {
+    fn default() -> Self {
+        Self {
+            transform: Transform::IDENTITY,
+            color: RgbColor::WHITE,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct PyramidInstanceBuffer {
+    model: Mat4,
+    color: Vec4,
+}
+
+impl PyramidInstanceBuffer {
+    const BUFFER_LAYOUT: wgpu::VertexBufferLayout<'static> = wgpu::VertexBufferLayout {
+        array_stride: std::mem::size_of::<PyramidInstanceBuffer>() as wgpu::BufferAddress,
+        step_mode: wgpu::VertexStepMode::Instance,
+        attributes: &wgpu::vertex_attr_array![
+            4 => Float32x4,
+            5 => Float32x4,
+            6 => Float32x4,
+            7 => Float32x4,
+            8 => Float32x4
+        ],
+    };
+}
+
+/// The wireframe overlay of the pyramid.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PyramidWireframe {
+    pub is_enabled: bool,
+    pub color: RgbColor,
+    /// The thickness of the edges in pixels.
+    pub thickness: f32,
+}
+
+impl PyramidWireframe {
+    fn buffer(&self, model: &PyramidModel) -> PyramidWireframeBuffer {
+        PyramidWireframeBuffer {
+            color: self.color.extend(1.0),
+            thickness: self.thickness,
+            wireframe_overlay: self.is_enabled as u32,
+            side_count: model.side_count as u32,
+            height: model.height,
+            base_radius: model.base_radius,
+            _padding: [0.0; 3],
+        }
+    }
+}
+
+impl Default for PyramidWireframe {
+    fn default() -> Self {
+        Self {
+            is_enabled: false,
+            color: RgbColor::BLACK,
+            thickness: 1.0,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct PyramidWireframeBuffer {
+    color: Vec4,
+    thickness: f32,
+    wireframe_overlay: u32,
+    side_count: u32,
+    height: f32,
+    base_radius: f32,
+    _padding: [f32; 3],
+}
+
+impl PyramidWireframeBuffer {
+    fn as_bytes(&self) -> &[u8] {
+        bytemuck::cast_slice(std::slice::from_ref(self))
+    }
+}
+
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct PyramidModel {
     pub height: f32,
     pub base_radius: f32,
     pub side_count: usize,
+    pub top_color: RgbColor,
+    /// The colors of the base vertices, repeated if fewer than the side count.
+    ///
+    /// If [`None`], the colors are spread around the hue circle.
+    pub base_colors: Option<Vec<RgbColor>>,
+    /// The color multiplied with the vertex colors and the texture.
+    pub base_color: RgbColor,
+    /// How metallic the surface is from 0.0 to 1.0, metals tint their
+    /// highlights and have no diffuse lighting.
+    pub metallic: f32,
+    /// How rough the surface is from 0.0 to 1.0, smoother surfaces have
+    /// smaller and sharper highlights.
+    pub roughness: f32,
+    /// The color emitted regardless of the lighting.
+    pub emissive: RgbColor,
 }
 
 impl PyramidModel {
+    /// The positions of the top vertex followed by the base vertices.
+    pub fn positions(&self) -> impl Iterator<Item = Vec3> + '_ {
+        std::iter::once(vec3(0.0, self.height, 0.0)).chain((0..self.side_count).map(|i| {
+            let angle = i as f32 / self.side_count as f32 * 2.0 * std::f32::consts::PI;
+            vec3(
+                self.base_radius * angle.cos(),
+                0.0,
+                self.base_radius * angle.sin(),
+            )
+        }))
+    }
+
+    /// The colors of the top vertex followed by the base vertices.
+    pub fn colors(&self) -> impl Iterator<Item = RgbColor> + '_ {
+        std::iter::once(self.top_color).chain((0..self.side_count).map(|i| {
+            match self.base_colors.as_deref() {
+                Some([]) | None => {
+                    RgbColor::from_hue(i as f32 / self.side_count as f32).expect("valid color")
+                }
+                Some(colors) => colors[i % colors.len()],
+            }
+        }))
+    }
+
+    /// The texture coordinates, projected from the top onto the base circle.
+    pub fn uvs(&self) -> impl Iterator<Item = Vec2> + '_ {
+        std::iter::once(Vec2::splat(0.5)).chain((0..self.side_count).map(|i| {
+            let angle = i as f32 / self.side_count as f32 * 2.0 * std::f32::consts::PI;
+            vec2(angle.cos(), angle.sin()) * 0.5 + 0.5
+        }))
+    }
+
+    /// The normals of the top vertex followed by the base vertices.
+    ///
+    /// The base normals are perpendicular to the slope of the cone, so the
+    /// shading is smooth across the sides.
+    pub fn normals(&self) -> impl Iterator<Item = Vec3> + '_ {
+        std::iter::once(Vec3::Y).chain((0..self.side_count).map(|i| {
+            let angle = i as f32 / self.side_count as f32 * 2.0 * std::f32::consts::PI;
+            vec3(
+                self.height * angle.cos(),
+                self.base_radius,
+                self.height * angle.sin(),
+            )
+            .normalize_or(Vec3::Y)
+        }))
+    }
+
     pub fn indices(&self) -> impl Iterator<Item = u16> + '_ {
         (0..self.side_count).flat_map(|i| {
             [
@@ -308,8 +1688,34 @@ impl PyramidModel {
         })
     }
 
+    /// The number of vertices, the top vertex and one for each side.
+    pub fn vertex_count(&self) -> usize {
+        self.side_count + 1
+    }
+
+    /// The radius of the bounding sphere around the origin of the model.
+    pub fn bounding_radius(&self) -> f32 {
+        self.positions().map(Vec3::length).fold(0.0, f32::max)
+    }
+
+    /// The number of indices, one triangle for each side.
+    pub fn index_count(&self) -> usize {
+        self.side_count * 3
+    }
+
+    /// The indices padded to the maximum number of sides, so the buffer can be
+    /// rewritten when the side count changes.
+    fn index_buffer(&self) -> [u16; PyramidModelBuffer::MAX_SIDES * 3] {
+        let mut buffer = [0; PyramidModelBuffer::MAX_SIDES * 3];
+        buffer
+            .iter_mut()
+            .zip(self.indices())
+            .for_each(|(dst, src)| *dst = src);
+        buffer
+    }
+
     fn buffer(&self) -> PyramidModelBuffer {
-        PyramidModelBuffer::new(self.height, self.base_radius, self.side_count)
+        PyramidModelBuffer::new(self)
     }
 }
 
@@ -319,6 +1725,12 @@ impl Default for PyramidModel {
             height: 1.0,
             base_radius: 1.0,
             side_count: 4,
+            top_color: RgbColor::WHITE,
+            base_colors: None,
+            base_color: RgbColor::WHITE,
+            metallic: 0.0,
+            roughness: 1.0,
+            emissive: RgbColor::BLACK,
         }
     }
 }
@@ -333,26 +1745,28 @@ struct PyramidModelBuffer {
 impl PyramidModelBuffer {
     const MAX_SIDES: usize = 64;
 
-    fn new(height: f32, base_radius: f32, side_count: usize) -> Self {
-        Self {
-            top: PyramidVertex {
-                position: vec3(0.0, height, 0.0),
-                color: RgbColor::WHITE,
-            },
-            bases: std::array::from_fn(|i| {
-                if i >= side_count {
-                    return PyramidVertex {
-                        position: Vec3::ZERO,
-                        color: RgbColor::BLACK,
-                    };
-                }
+    fn new(model: &PyramidModel) -> Self {
+        let mut vertices = model
+            .positions()
+            .zip(model.colors())
+            .zip(model.uvs())
+            .zip(model.normals())
+            .map(|(((position, color), uv), normal)| PyramidVertex {
+                position,
+                color,
+                uv,
+                normal,
+            });
 
-                let factor = i as f32 / side_count as f32;
-                let angle = factor * 2.0 * std::f32::consts::PI;
-                PyramidVertex {
-                    position: vec3(base_radius * angle.cos(), 0.0, base_radius * angle.sin()),
-                    color: RgbColor::from_hue(factor).expect("valid color"),
-                }
+        Self {
+            top: vertices.next().expect("top vertex"),
+            bases: std::array::from_fn(|_| {
+                vertices.next().unwrap_or(PyramidVertex {
+                    position: Vec3::ZERO,
+                    color: RgbColor::BLACK,
+                    uv: Vec2::ZERO,
+                    normal: Vec3::ZERO,
+                })
             }),
         }
     }
@@ -367,6 +1781,8 @@ impl PyramidModelBuffer {
 struct PyramidVertex {
     position: Vec3,
     color: RgbColor,
+    uv: Vec2,
+    normal: Vec3,
 }
 
 impl PyramidVertex {
@@ -384,79 +1800,421 @@ impl PyramidVertex {
                 format: wgpu::VertexFormat::Float32x3,
                 shader_location: 1,
             },
+            wgpu::VertexAttribute {
+                offset: (std::mem::size_of::<Vec3>() * 2) as wgpu::BufferAddress,
+                format: wgpu::VertexFormat::Float32x2,
+                shader_location: 2,
+            },
+            wgpu::VertexAttribute {
+                offset: (std::mem::size_of::<Vec3>() * 2 + std::mem::size_of::<Vec2>())
+                    as wgpu::BufferAddress,
+                format: wgpu::VertexFormat::Float32x3,
+                shader_location: 3,
+            },
         ],
     };
 }
 
 /// Builder of [`Pyramid`].
-pub struct PyramidBuilder<T, U, V> {
+pub struct PyramidBuilder<T, U, V, W, X, Y, Z> {
     device: T,
+    queue: W,
     surface_config: U,
     camera_bind_group_layout: V,
+    light_bind_group_layout: X,
+    point_lights_bind_group_layout: Y,
+    shadow_bind_group_layout: Z,
+    sample_count: u32,
+    color_format: Option<wgpu::TextureFormat>,
+    depth_format: Option<wgpu::TextureFormat>,
+    polygon_mode: wgpu::PolygonMode,
+    front_face: wgpu::FrontFace,
+    cull_mode: Option<wgpu::Face>,
+    blend: wgpu::BlendState,
+    is_depth_write: bool,
+    opacity: f32,
+    wireframe: PyramidWireframe,
     transform: PyramidTransform,
     model: PyramidModel,
+    instances: Vec<PyramidInstance>,
+    objects: Vec<Transform>,
+    texture: TextureData,
+    texture_mipmaps: bool,
+    texture_anisotropy: u16,
+    is_point_lights_storage: bool,
 }
 
 pub mod builder {
     pub struct NoDevice;
     pub struct WithDevice<'a>(pub &'a wgpu::Device);
 
+    pub struct NoQueue;
+    pub struct WithQueue<'a>(pub &'a wgpu::Queue);
+
     pub struct NoSurfaceConfig;
     pub struct WithSurfaceConfig<'a>(pub &'a wgpu::SurfaceConfiguration);
 
     pub struct NoCameraBindGroupLayout;
     pub struct WithCameraBindGroupLayout<'a>(pub &'a wgpu::BindGroupLayout);
+
+    pub struct NoLightBindGroupLayout;
+    pub struct WithLightBindGroupLayout<'a>(pub &'a wgpu::BindGroupLayout);
+
+    pub struct NoPointLightsBindGroupLayout;
+    pub struct WithPointLightsBindGroupLayout<'a>(pub &'a wgpu::BindGroupLayout);
+
+    pub struct NoShadowBindGroupLayout;
+    pub struct WithShadowBindGroupLayout<'a>(pub &'a wgpu::BindGroupLayout);
 }
 
-impl PyramidBuilder<builder::NoDevice, builder::NoSurfaceConfig, builder::NoCameraBindGroupLayout> {
+impl
+    PyramidBuilder<
+        builder::NoDevice,
+        builder::NoSurfaceConfig,
+        builder::NoCameraBindGroupLayout,
+        builder::NoQueue,
+        builder::NoLightBindGroupLayout,
+        builder::NoPointLightsBindGroupLayout,
+        builder::NoShadowBindGroupLayout,
+    >
+{
     pub fn new() -> Self {
         Self {
             device: builder::NoDevice,
+            queue: builder::NoQueue,
             surface_config: builder::NoSurfaceConfig,
             camera_bind_group_layout: builder::NoCameraBindGroupLayout,
+            light_bind_group_layout: builder::NoLightBindGroupLayout,
+            point_lights_bind_group_layout: builder::NoPointLightsBindGroupLayout,
+            shadow_bind_group_layout: builder::NoShadowBindGroupLayout,
+            sample_count: 1,
+            color_format: None,
+            depth_format: None,
+            polygon_mode: wgpu::PolygonMode::Fill,
+            front_face: wgpu::FrontFace::Ccw,
+            cull_mode: Some(wgpu::Face::Back),
+            blend: wgpu::BlendState::REPLACE,
+            is_depth_write: true,
+            opacity: 1.0,
+            wireframe: PyramidWireframe::default(),
             transform: PyramidTransform::default(),
             model: PyramidModel::default(),
+            instances: vec![PyramidInstance::default()],
+            objects: vec![Transform::IDENTITY],
+            texture: TextureData::white(),
+            texture_mipmaps: false,
+            texture_anisotropy: 1,
+            is_point_lights_storage: true,
         }
     }
 }
 
-impl<T, U, V> PyramidBuilder<T, U, V> {
-    pub fn with_device(self, device: &wgpu::Device) -> PyramidBuilder<builder::WithDevice, U, V> {
+impl<T, U, V, W, X, Y, Z> PyramidBuilder<T, U, V, W, X, Y, Z> {
+    pub fn with_device(
+        self,
+        device: &wgpu::Device,
+    ) -> PyramidBuilder<builder::WithDevice, U, V, W, X, Y, Z> {
         PyramidBuilder {
             device: builder::WithDevice(device),
+            queue: self.queue,
             surface_config: self.surface_config,
             camera_bind_group_layout: self.camera_bind_group_layout,
+            light_bind_group_layout: self.light_bind_group_layout,
+            point_lights_bind_group_layout: self.point_lights_bind_group_layout,
+            shadow_bind_group_layout: self.shadow_bind_group_layout,
+            sample_count: self.sample_count,
+            color_format: self.color_format,
+            depth_format: self.depth_format,
+            polygon_mode: self.polygon_mode,
+            front_face: self.front_face,
+            cull_mode: self.cull_mode,
+            blend: self.blend,
+            is_depth_write: self.is_depth_write,
+            opacity: self.opacity,
+            wireframe: self.wireframe,
             transform: self.transform,
             model: self.model,
+            instances: self.instances,
+            objects: self.objects,
+            texture: self.texture,
+            texture_mipmaps: self.texture_mipmaps,
+            texture_anisotropy: self.texture_anisotropy,
+            is_point_lights_storage: self.is_point_lights_storage,
         }
     }
 
     pub fn with_surface_config(
         self,
         surface_config: &wgpu::SurfaceConfiguration,
-    ) -> PyramidBuilder<T, builder::WithSurfaceConfig, V> {
+    ) -> PyramidBuilder<T, builder::WithSurfaceConfig, V, W, X, Y, Z> {
         PyramidBuilder {
             device: self.device,
+            queue: self.queue,
             surface_config: builder::WithSurfaceConfig(surface_config),
             camera_bind_group_layout: self.camera_bind_group_layout,
+            light_bind_group_layout: self.light_bind_group_layout,
+            point_lights_bind_group_layout: self.point_lights_bind_group_layout,
+            shadow_bind_group_layout: self.shadow_bind_group_layout,
+            sample_count: self.sample_count,
+            color_format: self.color_format,
+            depth_format: self.depth_format,
+            polygon_mode: self.polygon_mode,
+            front_face: self.front_face,
+            cull_mode: self.cull_mode,
+            blend: self.blend,
+            is_depth_write: self.is_depth_write,
+            opacity: self.opacity,
+            wireframe: self.wireframe,
             transform: self.transform,
             model: self.model,
+            instances: self.instances,
+            objects: self.objects,
+            texture: self.texture,
+            texture_mipmaps: self.texture_mipmaps,
+            texture_anisotropy: self.texture_anisotropy,
+            is_point_lights_storage: self.is_point_lights_storage,
         }
     }
 
     pub fn with_camera_bind_group_layout(
         self,
         camera_bind_group_layout: &wgpu::BindGroupLayout,
-    ) -> PyramidBuilder<T, U, builder::WithCameraBindGroupLayout> {
+    ) -> PyramidBuilder<T, U, builder::WithCameraBindGroupLayout, W, X, Y, Z> {
         PyramidBuilder {
             device: self.device,
+            queue: self.queue,
             surface_config: self.surface_config,
             camera_bind_group_layout: builder::WithCameraBindGroupLayout(camera_bind_group_layout),
+            light_bind_group_layout: self.light_bind_group_layout,
+            point_lights_bind_group_layout: self.point_lights_bind_group_layout,
+            shadow_bind_group_layout: self.shadow_bind_group_layout,
+            sample_count: self.sample_count,
+            color_format: self.color_format,
+            depth_format: self.depth_format,
+            polygon_mode: self.polygon_mode,
+            front_face: self.front_face,
+            cull_mode: self.cull_mode,
+            blend: self.blend,
+            is_depth_write: self.is_depth_write,
+            opacity: self.opacity,
+            wireframe: self.wireframe,
+            transform: self.transform,
+            model: self.model,
+            instances: self.instances,
+            objects: self.objects,
+            texture: self.texture,
+            texture_mipmaps: self.texture_mipmaps,
+            texture_anisotropy: self.texture_anisotropy,
+            is_point_lights_storage: self.is_point_lights_storage,
+        }
+    }
+
+    pub fn with_light_bind_group_layout(
+        self,
+        light_bind_group_layout: &wgpu::BindGroupLayout,
+    ) -> PyramidBuilder<T, U, V, W, builder::WithLightBindGroupLayout, Y, Z> {
+        PyramidBuilder {
+            device: self.device,
+            queue: self.queue,
+            surface_config: self.surface_config,
+            camera_bind_group_layout: self.camera_bind_group_layout,
+            light_bind_group_layout: builder::WithLightBindGroupLayout(light_bind_group_layout),
+            point_lights_bind_group_layout: self.point_lights_bind_group_layout,
+            shadow_bind_group_layout: self.shadow_bind_group_layout,
+            sample_count: self.sample_count,
+            color_format: self.color_format,
+            depth_format: self.depth_format,
+            polygon_mode: self.polygon_mode,
+            front_face: self.front_face,
+            cull_mode: self.cull_mode,
+            blend: self.blend,
+            is_depth_write: self.is_depth_write,
+            opacity: self.opacity,
+            wireframe: self.wireframe,
+            transform: self.transform,
+            model: self.model,
+            instances: self.instances,
+            objects: self.objects,
+            texture: self.texture,
+            texture_mipmaps: self.texture_mipmaps,
+            texture_anisotropy: self.texture_anisotropy,
+            is_point_lights_storage: self.is_point_lights_storage,
+        }
+    }
+
+    pub fn with_point_lights_bind_group_layout(
+        self,
+        point_lights_bind_group_layout: &wgpu::BindGroupLayout,
+    ) -> PyramidBuilder<T, U, V, W, X, builder::WithPointLightsBindGroupLayout, Z> {
+        PyramidBuilder {
+            device: self.device,
+            queue: self.queue,
+            surface_config: self.surface_config,
+            camera_bind_group_layout: self.camera_bind_group_layout,
+            light_bind_group_layout: self.light_bind_group_layout,
+            point_lights_bind_group_layout: builder::WithPointLightsBindGroupLayout(
+                point_lights_bind_group_layout,
+            ),
+            shadow_bind_group_layout: self.shadow_bind_group_layout,
+            sample_count: self.sample_count,
+            color_format: self.color_format,
+            depth_format: self.depth_format,
+            polygon_mode: self.polygon_mode,
+            front_face: self.front_face,
+            cull_mode: self.cull_mode,
+            blend: self.blend,
+            is_depth_write: self.is_depth_write,
+            opacity: self.opacity,
+            wireframe: self.wireframe,
             transform: self.transform,
             model: self.model,
+            instances: self.instances,
+            objects: self.objects,
+            texture: self.texture,
+            texture_mipmaps: self.texture_mipmaps,
+            texture_anisotropy: self.texture_anisotropy,
+            is_point_lights_storage: self.is_point_lights_storage,
         }
     }
 
+    /// Set the bind group layout of the light in the shadow pass.
+    pub fn with_shadow_bind_group_layout(
+        self,
+        shadow_bind_group_layout: &wgpu::BindGroupLayout,
+    ) -> PyramidBuilder<T, U, V, W, X, Y, builder::WithShadowBindGroupLayout> {
+        PyramidBuilder {
+            device: self.device,
+            queue: self.queue,
+            surface_config: self.surface_config,
+            camera_bind_group_layout: self.camera_bind_group_layout,
+            light_bind_group_layout: self.light_bind_group_layout,
+            point_lights_bind_group_layout: self.point_lights_bind_group_layout,
+            shadow_bind_group_layout: builder::WithShadowBindGroupLayout(shadow_bind_group_layout),
+            sample_count: self.sample_count,
+            color_format: self.color_format,
+            depth_format: self.depth_format,
+            polygon_mode: self.polygon_mode,
+            front_face: self.front_face,
+            cull_mode: self.cull_mode,
+            blend: self.blend,
+            is_depth_write: self.is_depth_write,
+            opacity: self.opacity,
+            wireframe: self.wireframe,
+            transform: self.transform,
+            model: self.model,
+            instances: self.instances,
+            objects: self.objects,
+            texture: self.texture,
+            texture_mipmaps: self.texture_mipmaps,
+            texture_anisotropy: self.texture_anisotropy,
+            is_point_lights_storage: self.is_point_lights_storage,
+        }
+    }
+
+    pub fn with_queue(
+        self,
+        queue: &wgpu::Queue,
+    ) -> PyramidBuilder<T, U, V, builder::WithQueue, X, Y, Z> {
+        PyramidBuilder {
+            device: self.device,
+            queue: builder::WithQueue(queue),
+            surface_config: self.surface_config,
+            camera_bind_group_layout: self.camera_bind_group_layout,
+            light_bind_group_layout: self.light_bind_group_layout,
+            point_lights_bind_group_layout: self.point_lights_bind_group_layout,
+            shadow_bind_group_layout: self.shadow_bind_group_layout,
+            sample_count: self.sample_count,
+            color_format: self.color_format,
+            depth_format: self.depth_format,
+            polygon_mode: self.polygon_mode,
+            front_face: self.front_face,
+            cull_mode: self.cull_mode,
+            blend: self.blend,
+            is_depth_write: self.is_depth_write,
+            opacity: self.opacity,
+            wireframe: self.wireframe,
+            transform: self.transform,
+            model: self.model,
+            instances: self.instances,
+            objects: self.objects,
+            texture: self.texture,
+            texture_mipmaps: self.texture_mipmaps,
+            texture_anisotropy: self.texture_anisotropy,
+            is_point_lights_storage: self.is_point_lights_storage,
+        }
+    }
+
+    pub fn with_sample_count(mut self, sample_count: u32) -> Self {
+        self.sample_count = sample_count;
+        self
+    }
+
+    /// Set the format of the render pass color attachment, defaults to the
+    /// surface format.
+    pub fn with_color_format(mut self, color_format: wgpu::TextureFormat) -> Self {
+        self.color_format = Some(color_format);
+        self
+    }
+
+    /// Enable depth testing with the format of the render pass depth
+    /// attachment.
+    pub fn with_depth_format(mut self, depth_format: wgpu::TextureFormat) -> Self {
+        self.depth_format = Some(depth_format);
+        self
+    }
+
+    /// Set the polygon mode, falls back to [`wgpu::PolygonMode::Fill`] if not
+    /// supported.
+    pub fn with_polygon_mode(mut self, polygon_mode: wgpu::PolygonMode) -> Self {
+        self.polygon_mode = polygon_mode;
+        self
+    }
+
+    /// Set the winding of the front faces, defaults to
+    /// [`wgpu::FrontFace::Ccw`] as wound by [`PyramidModel::indices`].
+    pub fn with_front_face(mut self, front_face: wgpu::FrontFace) -> Self {
+        self.front_face = front_face;
+        self
+    }
+
+    /// Set the faces to cull, defaults to culling [`wgpu::Face::Back`].
+    ///
+    /// The pyramid has no base, so with [`None`] the inside is visible through
+    /// the bottom, and is lit from the flipped normals.
+    pub fn with_cull_mode(mut self, cull_mode: Option<wgpu::Face>) -> Self {
+        self.cull_mode = cull_mode;
+        self
+    }
+
+    /// Set the blending of the color target, defaults to the opaque
+    /// [`wgpu::BlendState::REPLACE`].
+    ///
+    /// For transparency, use [`wgpu::BlendState::ALPHA_BLENDING`] with
+    /// [`PyramidBuilder::with_depth_write`] disabled, so the geometry behind
+    /// is not rejected by the depth test. The faces are not sorted, so
+    /// overlapping faces may blend out of order.
+    pub fn with_blend(mut self, blend: wgpu::BlendState) -> Self {
+        self.blend = blend;
+        self
+    }
+
+    /// Set whether the pyramid writes to the depth buffer, defaults to true.
+    pub fn with_depth_write(mut self, is_depth_write: bool) -> Self {
+        self.is_depth_write = is_depth_write;
+        self
+    }
+
+    /// Set the opacity, defaults to 1.0, see [`Pyramid::set_opacity`].
+    pub fn with_opacity(mut self, opacity: f32) -> Self {
+        self.opacity = opacity.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn with_wireframe(mut self, wireframe: PyramidWireframe) -> Self {
+        self.wireframe = wireframe;
+        self
+    }
+
     pub fn with_pyramid_transform(mut self, transform: PyramidTransform) -> Self {
         self.transform = transform;
         self
@@ -482,6 +2240,11 @@ impl<T, U, V> PyramidBuilder<T, U, V> {
         self
     }
 
+    pub fn with_auto_rotation_axis(mut self, auto_rotation_axis: Vec3) -> Self {
+        self.transform.auto_rotation_axis = auto_rotation_axis;
+        self
+    }
+
     pub fn with_auto_rotation_speed(mut self, auto_rotation_speed: f32) -> Self {
         self.transform.auto_rotation_speed = auto_rotation_speed;
         self
@@ -492,6 +2255,23 @@ impl<T, U, V> PyramidBuilder<T, U, V> {
         self
     }
 
+    /// Set the instances, defaults to a single instance with identity
+    /// transform.
+    pub fn with_instances(mut self, instances: Vec<PyramidInstance>) -> Self {
+        self.instances = instances;
+        self
+    }
+
+    /// Set the objects, each drawing all the instances with its own transform
+    /// slot in a shared uniform buffer, defaults to a single object with
+    /// identity transform.
+    ///
+    /// See [`Pyramid::set_objects`].
+    pub fn with_objects(mut self, objects: Vec<Transform>) -> Self {
+        self.objects = objects;
+        self
+    }
+
     pub fn with_height(mut self, height: f32) -> Self {
         self.model.height = height;
         self
@@ -506,6 +2286,55 @@ impl<T, U, V> PyramidBuilder<T, U, V> {
         self.model.side_count = side_count;
         self
     }
+
+    pub fn with_top_color(mut self, top_color: RgbColor) -> Self {
+        self.model.top_color = top_color;
+        self
+    }
+
+    /// Set the colors of the base vertices, repeated if fewer than the side
+    /// count.
+    pub fn with_base_colors(mut self, base_colors: Vec<RgbColor>) -> Self {
+        self.model.base_colors = Some(base_colors);
+        self
+    }
+
+    /// Generate the mip chain of the texture, to avoid shimmering at a
+    /// distance.
+    pub fn with_mipmaps(mut self, mipmaps: bool) -> Self {
+        self.texture_mipmaps = mipmaps;
+        self
+    }
+
+    /// Set the anisotropic filtering level of the texture, defaults to 1 to
+    /// disable it for compatibility, see [`Texture::clamp_anisotropy`].
+    pub fn with_anisotropy(mut self, anisotropy: u16) -> Self {
+        self.texture_anisotropy = anisotropy;
+        self
+    }
+
+    /// Set whether the point lights are in a storage buffer, defaults to true,
+    /// which must match [`crate::systems::handlers::Lights::is_storage`].
+    pub fn with_point_lights_storage(mut self, is_storage: bool) -> Self {
+        self.is_point_lights_storage = is_storage;
+        self
+    }
+
+    /// Set the texture, defaults to a single white pixel.
+    pub fn with_texture(mut self, texture: TextureData) -> Self {
+        self.texture = texture;
+        self
+    }
+
+    /// Set the texture decoded from PNG or JPEG bytes.
+    #[cfg(feature = "image")]
+    pub fn with_texture_bytes(
+        mut self,
+        bytes: &[u8],
+    ) -> Result<Self, crate::systems::TextureError> {
+        self.texture = TextureData::from_image_bytes(bytes)?;
+        Ok(self)
+    }
 }
 
 impl<'a>
@@ -513,15 +2342,129 @@ impl<'a>
         builder::WithDevice<'a>,
         builder::WithSurfaceConfig<'a>,
         builder::WithCameraBindGroupLayout<'a>,
+        builder::WithQueue<'a>,
+        builder::WithLightBindGroupLayout<'a>,
+        builder::WithPointLightsBindGroupLayout<'a>,
+        builder::WithShadowBindGroupLayout<'a>,
     >
 {
-    pub fn build(self) -> Pyramid {
+    /// Build the pyramid, fails if any of its shaders fails to compile.
+    pub fn build(self) -> Result<Pyramid, ShaderError> {
         Pyramid::new(
             self.device.0,
+            self.queue.0,
             self.surface_config.0,
             self.camera_bind_group_layout.0,
+            self.light_bind_group_layout.0,
+            self.point_lights_bind_group_layout.0,
+            self.shadow_bind_group_layout.0,
+            self.sample_count,
+            self.color_format,
+            self.depth_format,
+            self.polygon_mode,
+            self.front_face,
+            self.cull_mode,
+            self.blend,
+            self.is_depth_write,
+            self.opacity,
+            self.wireframe,
             self.transform,
             self.model,
+            self.instances,
+            self.objects,
+            &self.texture,
+            self.texture_mipmaps,
+            self.texture_anisotropy,
+            self.is_point_lights_storage,
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::systems::handlers::{self, CameraBuilder, LightBuilder, LightsBuilder};
+
+    fn pyramid(device: &wgpu::Device, queue: &wgpu::Queue) -> Pyramid {
+        let surface_config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            width: 1,
+            height: 1,
+            present_mode: wgpu::PresentMode::Fifo,
+            alpha_mode: wgpu::CompositeAlphaMode::Opaque,
+            view_formats: vec![],
+            desired_maximum_frame_latency: 2,
+        };
+        let camera = CameraBuilder::new()
+            .with_device(device)
+            .with_aspect_ratio(1.0)
+            .build();
+        let light = LightBuilder::new().with_device(device).build();
+        let lights = LightsBuilder::new().with_device(device).build();
+
+        PyramidBuilder::new()
+            .with_device(device)
+            .with_queue(queue)
+            .with_surface_config(&surface_config)
+            .with_camera_bind_group_layout(camera.bind_group_layout())
+            .with_light_bind_group_layout(light.bind_group_layout())
+            .with_point_lights_bind_group_layout(lights.bind_group_layout())
+            .with_shadow_bind_group_layout(light.shadow_bind_group_layout())
+            .build()
+            .expect("pyramid")
+    }
+
+    #[test]
+    fn disabled_update_does_not_rotate() {
+        let Some((device, queue)) = handlers::test_device() else {
+            return;
+        };
+        let mut pyramid = pyramid(&device, &queue);
+        let rotation = pyramid.transform().transform.rotation;
+
+        pyramid.set_enabled(false);
+        pyramid.update(1.0);
+        assert_eq!(pyramid.transform().transform.rotation, rotation);
+
+        pyramid.set_enabled(true);
+        pyramid.update(1.0);
+        assert_ne!(pyramid.transform().transform.rotation, rotation);
+    }
+
+    #[test]
+    fn point_lights_source_compiles_with_and_without_storage() {
+        let Some((device, _)) = handlers::test_device() else {
+            return;
+        };
+
+        for is_storage in [true, false] {
+            let source = Pyramid::point_lights_source(Pyramid::SHADER_SOURCE, is_storage);
+            if let Err(e) = systems::create_shader_module(&device, "pyramid.wgsl", &source) {
+                panic!("Pyramid shader with storage {is_storage} failed: {e}");
+            }
+        }
+    }
+
+    #[test]
+    fn point_lights_source_keeps_compilation_error_lines() {
+        let Some((device, _)) = handlers::test_device() else {
+            return;
+        };
+
+        let source = format!(
+            "{}\nfn broken() -> f32 {{ return 1.0 +; }}\n",
+            Pyramid::SHADER_SOURCE.trim_end()
+        );
+        let broken_line = Pyramid::SHADER_SOURCE.trim_end().lines().count() as u32 + 1;
+
+        for is_storage in [true, false] {
+            let source = Pyramid::point_lights_source(&source, is_storage);
+            match systems::create_shader_module(&device, "pyramid.wgsl", &source) {
+                Err(ShaderError::Compilation { line, .. }) => assert_eq!(line, broken_line),
+                Err(e) => panic!("Expected a compilation error, got: {e}"),
+                Ok(_) => panic!("Expected a compilation error"),
+            }
+        }
+    }
+}
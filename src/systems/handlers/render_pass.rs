@@ -0,0 +1,48 @@
+use crate::systems::handlers::Display;
+
+/// A handler drawing into the render pass of [`Display::render`].
+///
+/// The passes are collected and drawn in [`RenderPass::order`] with
+/// [`draw_passes`], once for each viewport, so a handler can be added without
+/// changing the draws of the others. Handlers needing more than the camera,
+/// e.g. [`crate::systems::handlers::Pyramid::pass`], borrow the rest in a
+/// wrapper implementing this.
+///
+/// The orders of the provided handlers are, from first to last:
+///
+/// | Handler                                      | Order |
+/// | -------------------------------------------- | ----- |
+/// | [`crate::systems::handlers::Skybox`]         | 0     |
+/// | [`crate::systems::handlers::Ground`]         | 100   |
+/// | [`crate::systems::handlers::Model`]          | 200   |
+/// | [`crate::systems::handlers::Grid`]           | 300   |
+/// | [`crate::systems::handlers::Pyramid`]        | 400   |
+/// | [`crate::systems::handlers::Gizmo`]          | 500   |
+pub trait RenderPass {
+    /// The order the pass is drawn in, lower first.
+    ///
+    /// Passes of the same order keep the order they are collected in.
+    fn order(&self) -> i32;
+
+    /// Draw into `render_pass` with the camera of the viewport, the viewport
+    /// is already set.
+    fn draw(
+        &mut self,
+        render_pass: &mut wgpu::RenderPass,
+        display: &Display,
+        camera_bind_group: &wgpu::BindGroup,
+    );
+}
+
+/// Sort `passes` by [`RenderPass::order`] and draw them.
+pub fn draw_passes(
+    passes: &mut [&mut dyn RenderPass],
+    render_pass: &mut wgpu::RenderPass,
+    display: &Display,
+    camera_bind_group: &wgpu::BindGroup,
+) {
+    passes.sort_by_key(|pass| pass.order());
+    for pass in passes {
+        pass.draw(render_pass, display, camera_bind_group);
+    }
+}
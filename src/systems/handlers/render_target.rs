@@ -0,0 +1,314 @@
+use winit::dpi::PhysicalSize;
+
+use crate::systems::{handlers::Display, RgbColor};
+
+/// Handler for an offscreen color and depth target, e.g. for a monitor in the
+/// scene showing the view of another camera.
+///
+/// The size is independent of the window, and the color texture can be bound
+/// as a material of another pipeline with [`RenderTarget::view`] and
+/// [`RenderTarget::sampler`]. Pipelines rendering into it must be created
+/// with [`RenderTarget::format`], [`RenderTarget::depth_format`], and
+/// [`RenderTarget::sample_count`], which may differ from the display's.
+pub struct RenderTarget {
+    size: PhysicalSize<u32>,
+    format: wgpu::TextureFormat,
+    sample_count: u32,
+    clear_color: RgbColor,
+
+    texture: wgpu::Texture,
+    view: wgpu::TextureView,
+    msaa_view: Option<wgpu::TextureView>,
+    depth_view: wgpu::TextureView,
+    sampler: wgpu::Sampler,
+}
+
+impl RenderTarget {
+    pub fn new(
+        device: &wgpu::Device,
+        size: PhysicalSize<u32>,
+        format: wgpu::TextureFormat,
+        sample_count: u32,
+        clear_color: RgbColor,
+    ) -> Self {
+        let size = PhysicalSize::new(size.width.max(1), size.height.max(1));
+
+        log::debug!(
+            "Creating render target textures of size {}x{}",
+            size.width,
+            size.height
+        );
+        let (texture, view, msaa_view, depth_view) =
+            Self::create_textures(device, size, format, sample_count);
+
+        log::debug!("Creating render target sampler");
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Render Target Sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        log::info!("Render target handler initialized");
+
+        Self {
+            size,
+            format,
+            sample_count,
+            clear_color,
+
+            texture,
+            view,
+            msaa_view,
+            depth_view,
+            sampler,
+        }
+    }
+
+    pub fn size(&self) -> PhysicalSize<u32> {
+        self.size
+    }
+
+    /// The aspect ratio of the target, for the camera rendering into it.
+    pub fn aspect_ratio(&self) -> f32 {
+        self.size.width as f32 / self.size.height as f32
+    }
+
+    /// The format of the color texture.
+    pub fn format(&self) -> wgpu::TextureFormat {
+        self.format
+    }
+
+    /// The format of the depth texture, the same as the display's.
+    pub fn depth_format(&self) -> wgpu::TextureFormat {
+        Display::DEPTH_FORMAT
+    }
+
+    pub fn sample_count(&self) -> u32 {
+        self.sample_count
+    }
+
+    pub fn clear_color(&self) -> RgbColor {
+        self.clear_color
+    }
+
+    pub fn set_clear_color(&mut self, clear_color: RgbColor) {
+        self.clear_color = clear_color;
+    }
+
+    /// The color texture, resolved if multisampled.
+    pub fn texture(&self) -> &wgpu::Texture {
+        &self.texture
+    }
+
+    /// The view of the color texture.
+    ///
+    /// This changes on [`RenderTarget::resize`], so bind groups using it
+    /// need to be recreated.
+    pub fn view(&self) -> &wgpu::TextureView {
+        &self.view
+    }
+
+    /// A linear clamping sampler for the color texture.
+    pub fn sampler(&self) -> &wgpu::Sampler {
+        &self.sampler
+    }
+
+    /// Recreate the textures at `size`, zero sizes are ignored.
+    pub fn resize(&mut self, device: &wgpu::Device, size: PhysicalSize<u32>) {
+        if size.width == 0 || size.height == 0 || size == self.size {
+            return;
+        }
+
+        log::debug!("Resizing render target to {}x{}", size.width, size.height);
+        self.size = size;
+        (self.texture, self.view, self.msaa_view, self.depth_view) =
+            Self::create_textures(device, size, self.format, self.sample_count);
+    }
+
+    /// Render a pass into the target, submitted before the next
+    /// [`Display::render`] so its texture can be sampled there.
+    pub fn render(&self, display: &Display, render: impl FnOnce(&Display, &mut wgpu::RenderPass)) {
+        let mut encoder =
+            display
+                .device()
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                    label: Some("Render Target Encoder"),
+                });
+
+        {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Render Target Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: self.msaa_view.as_ref().unwrap_or(&self.view),
+                    resolve_target: self.msaa_view.as_ref().map(|_| &self.view),
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color {
+                            r: self.clear_color.r() as f64,
+                            g: self.clear_color.g() as f64,
+                            b: self.clear_color.b() as f64,
+                            a: 1.0,
+                        }),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                    view: &self.depth_view,
+                    depth_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(1.0),
+                        store: wgpu::StoreOp::Store,
+                    }),
+                    stencil_ops: None,
+                }),
+                occlusion_query_set: None,
+                timestamp_writes: None,
+            });
+
+            render(display, &mut render_pass);
+        }
+
+        display.submit(encoder);
+    }
+
+    /// Create the color texture and its view, the MSAA view, and the depth
+    /// view.
+    fn create_textures(
+        device: &wgpu::Device,
+        size: PhysicalSize<u32>,
+        format: wgpu::TextureFormat,
+        sample_count: u32,
+    ) -> (
+        wgpu::Texture,
+        wgpu::TextureView,
+        Option<wgpu::TextureView>,
+        wgpu::TextureView,
+    ) {
+        let extent = wgpu::Extent3d {
+            width: size.width,
+            height: size.height,
+            depth_or_array_layers: 1,
+        };
+
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Render Target Texture"),
+            size: extent,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                | wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let msaa_view = (sample_count > 1).then(|| {
+            device
+                .create_texture(&wgpu::TextureDescriptor {
+                    label: Some("Render Target MSAA Texture"),
+                    size: extent,
+                    mip_level_count: 1,
+                    sample_count,
+                    dimension: wgpu::TextureDimension::D2,
+                    format,
+                    usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+                    view_formats: &[],
+                })
+                .create_view(&wgpu::TextureViewDescriptor::default())
+        });
+
+        let depth_view = device
+            .create_texture(&wgpu::TextureDescriptor {
+                label: Some("Render Target Depth Texture"),
+                size: extent,
+                mip_level_count: 1,
+                sample_count,
+                dimension: wgpu::TextureDimension::D2,
+                format: Display::DEPTH_FORMAT,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+                view_formats: &[],
+            })
+            .create_view(&wgpu::TextureViewDescriptor::default());
+
+        (texture, view, msaa_view, depth_view)
+    }
+}
+
+/// Builder of [`RenderTarget`].
+pub struct RenderTargetBuilder<T> {
+    device: T,
+    size: PhysicalSize<u32>,
+    format: wgpu::TextureFormat,
+    sample_count: u32,
+    clear_color: RgbColor,
+}
+
+pub mod builder {
+    pub struct NoDevice;
+    pub struct WithDevice<'a>(pub &'a wgpu::Device);
+}
+
+impl RenderTargetBuilder<builder::NoDevice> {
+    pub fn new() -> Self {
+        Self {
+            device: builder::NoDevice,
+            size: PhysicalSize::new(512, 512),
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            sample_count: 1,
+            clear_color: RgbColor::BLACK,
+        }
+    }
+}
+
+impl<T> RenderTargetBuilder<T> {
+    pub fn with_device(self, device: &wgpu::Device) -> RenderTargetBuilder<builder::WithDevice> {
+        RenderTargetBuilder {
+            device: builder::WithDevice(device),
+            size: self.size,
+            format: self.format,
+            sample_count: self.sample_count,
+            clear_color: self.clear_color,
+        }
+    }
+
+    /// Set the size of the target, defaults to 512x512.
+    pub fn with_size(mut self, size: PhysicalSize<u32>) -> Self {
+        self.size = size;
+        self
+    }
+
+    /// Set the format of the color texture, defaults to
+    /// [`wgpu::TextureFormat::Rgba8UnormSrgb`].
+    pub fn with_format(mut self, format: wgpu::TextureFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Set the sample count, defaults to 1.
+    pub fn with_sample_count(mut self, sample_count: u32) -> Self {
+        self.sample_count = sample_count;
+        self
+    }
+
+    /// Set the clear color, defaults to black.
+    pub fn with_clear_color(mut self, clear_color: RgbColor) -> Self {
+        self.clear_color = clear_color;
+        self
+    }
+}
+
+impl RenderTargetBuilder<builder::WithDevice<'_>> {
+    pub fn build(self) -> RenderTarget {
+        RenderTarget::new(
+            self.device.0,
+            self.size,
+            self.format,
+            self.sample_count,
+            self.clear_color,
+        )
+    }
+}
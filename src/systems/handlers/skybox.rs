@@ -0,0 +1,408 @@
+use thiserror::Error;
+
+use crate::systems::{
+    handlers::{Display, RenderPass},
+    Texture, TextureData,
+};
+
+/// Handler for the skybox drawn behind everything else.
+pub struct Skybox {
+    texture: wgpu::Texture,
+    render_pipeline: wgpu::RenderPipeline,
+
+    texture_bind_group: wgpu::BindGroup,
+}
+
+impl Skybox {
+    /// The faces are in the order of +X, -X, +Y, -Y, +Z, -Z.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        surface_config: &wgpu::SurfaceConfiguration,
+        camera_bind_group_layout: &wgpu::BindGroupLayout,
+        sample_count: u32,
+        color_format: Option<wgpu::TextureFormat>,
+        depth_format: Option<wgpu::TextureFormat>,
+        faces: &[TextureData; 6],
+    ) -> Result<Self, SkyboxError> {
+        let size = faces[0].width;
+        if faces[0].width != faces[0].height {
+            return Err(SkyboxError::NotSquare(faces[0].width, faces[0].height));
+        }
+        if let Some((index, face)) = faces
+            .iter()
+            .enumerate()
+            .find(|(_, face)| face.width != size || face.height != size)
+        {
+            return Err(SkyboxError::FaceSizeMismatch {
+                index,
+                width: face.width,
+                height: face.height,
+                size,
+            });
+        }
+
+        log::debug!("Creating skybox cubemap of size {size}x{size}");
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Skybox Texture"),
+            size: wgpu::Extent3d {
+                width: size,
+                height: size,
+                depth_or_array_layers: 6,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: Texture::FORMAT,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+
+        for (layer, face) in faces.iter().enumerate() {
+            queue.write_texture(
+                wgpu::ImageCopyTexture {
+                    texture: &texture,
+                    mip_level: 0,
+                    origin: wgpu::Origin3d {
+                        x: 0,
+                        y: 0,
+                        z: layer as u32,
+                    },
+                    aspect: wgpu::TextureAspect::All,
+                },
+                &face.pixels,
+                wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(size * 4),
+                    rows_per_image: Some(size),
+                },
+                wgpu::Extent3d {
+                    width: size,
+                    height: size,
+                    depth_or_array_layers: 1,
+                },
+            );
+        }
+
+        let view = texture.create_view(&wgpu::TextureViewDescriptor {
+            label: Some("Skybox Texture View"),
+            dimension: Some(wgpu::TextureViewDimension::Cube),
+            ..Default::default()
+        });
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Skybox Sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        log::debug!("Creating skybox texture bind group layout");
+        let texture_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("Skybox Texture Bind Group Layout"),
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                            view_dimension: wgpu::TextureViewDimension::Cube,
+                            multisampled: false,
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                        count: None,
+                    },
+                ],
+            });
+
+        log::debug!("Creating skybox texture bind group");
+        let texture_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Skybox Texture Bind Group"),
+            layout: &texture_bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&sampler),
+                },
+            ],
+        });
+
+        log::debug!("Creating skybox shader");
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Skybox Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("../shaders/skybox.wgsl").into()),
+        });
+
+        log::debug!("Creating skybox pipeline layout");
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Skybox Pipeline Layout"),
+            bind_group_layouts: &[camera_bind_group_layout, &texture_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        // Drawn on the far plane without writing depth, so geometry draws on top
+        log::debug!("Creating skybox render pipeline");
+        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Skybox Render Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vertex_main"),
+                buffers: &[],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fragment_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: color_format.unwrap_or(surface_config.format),
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: depth_format.map(|format| wgpu::DepthStencilState {
+                format,
+                depth_write_enabled: false,
+                depth_compare: wgpu::CompareFunction::LessEqual,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState {
+                count: sample_count,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+            cache: None,
+        });
+
+        log::info!("Skybox handler initialized");
+
+        Ok(Self {
+            texture,
+            render_pipeline,
+
+            texture_bind_group,
+        })
+    }
+
+    /// The width and height of each face.
+    pub fn size(&self) -> u32 {
+        self.texture.width()
+    }
+
+    pub fn render(&self, render_pass: &mut wgpu::RenderPass, camera_bind_group: &wgpu::BindGroup) {
+        render_pass.set_pipeline(&self.render_pipeline);
+        render_pass.set_bind_group(0, camera_bind_group, &[]);
+        render_pass.set_bind_group(1, &self.texture_bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+}
+
+impl RenderPass for Skybox {
+    fn order(&self) -> i32 {
+        0
+    }
+
+    fn draw(
+        &mut self,
+        render_pass: &mut wgpu::RenderPass,
+        _display: &Display,
+        camera_bind_group: &wgpu::BindGroup,
+    ) {
+        self.render(render_pass, camera_bind_group);
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum SkyboxError {
+    #[error("skybox face is not square: {0}x{1}")]
+    NotSquare(u32, u32),
+    #[error("skybox face {index} size does not match: {width}x{height} != {size}x{size}")]
+    FaceSizeMismatch {
+        index: usize,
+        width: u32,
+        height: u32,
+        size: u32,
+    },
+}
+
+/// Builder of [`Skybox`].
+pub struct SkyboxBuilder<T, U, V, W> {
+    device: T,
+    queue: W,
+    surface_config: U,
+    camera_bind_group_layout: V,
+    sample_count: u32,
+    color_format: Option<wgpu::TextureFormat>,
+    depth_format: Option<wgpu::TextureFormat>,
+    faces: [TextureData; 6],
+}
+
+pub mod builder {
+    pub struct NoDevice;
+    pub struct WithDevice<'a>(pub &'a wgpu::Device);
+
+    pub struct NoQueue;
+    pub struct WithQueue<'a>(pub &'a wgpu::Queue);
+
+    pub struct NoSurfaceConfig;
+    pub struct WithSurfaceConfig<'a>(pub &'a wgpu::SurfaceConfiguration);
+
+    pub struct NoCameraBindGroupLayout;
+    pub struct WithCameraBindGroupLayout<'a>(pub &'a wgpu::BindGroupLayout);
+}
+
+impl
+    SkyboxBuilder<
+        builder::NoDevice,
+        builder::NoSurfaceConfig,
+        builder::NoCameraBindGroupLayout,
+        builder::NoQueue,
+    >
+{
+    pub fn new() -> Self {
+        Self {
+            device: builder::NoDevice,
+            queue: builder::NoQueue,
+            surface_config: builder::NoSurfaceConfig,
+            camera_bind_group_layout: builder::NoCameraBindGroupLayout,
+            sample_count: 1,
+            color_format: None,
+            depth_format: None,
+            faces: std::array::from_fn(|_| TextureData::white()),
+        }
+    }
+}
+
+impl<T, U, V, W> SkyboxBuilder<T, U, V, W> {
+    pub fn with_device(self, device: &wgpu::Device) -> SkyboxBuilder<builder::WithDevice, U, V, W> {
+        SkyboxBuilder {
+            device: builder::WithDevice(device),
+            queue: self.queue,
+            surface_config: self.surface_config,
+            camera_bind_group_layout: self.camera_bind_group_layout,
+            sample_count: self.sample_count,
+            color_format: self.color_format,
+            depth_format: self.depth_format,
+            faces: self.faces,
+        }
+    }
+
+    pub fn with_surface_config(
+        self,
+        surface_config: &wgpu::SurfaceConfiguration,
+    ) -> SkyboxBuilder<T, builder::WithSurfaceConfig, V, W> {
+        SkyboxBuilder {
+            device: self.device,
+            queue: self.queue,
+            surface_config: builder::WithSurfaceConfig(surface_config),
+            camera_bind_group_layout: self.camera_bind_group_layout,
+            sample_count: self.sample_count,
+            color_format: self.color_format,
+            depth_format: self.depth_format,
+            faces: self.faces,
+        }
+    }
+
+    pub fn with_camera_bind_group_layout(
+        self,
+        camera_bind_group_layout: &wgpu::BindGroupLayout,
+    ) -> SkyboxBuilder<T, U, builder::WithCameraBindGroupLayout, W> {
+        SkyboxBuilder {
+            device: self.device,
+            queue: self.queue,
+            surface_config: self.surface_config,
+            camera_bind_group_layout: builder::WithCameraBindGroupLayout(camera_bind_group_layout),
+            sample_count: self.sample_count,
+            color_format: self.color_format,
+            depth_format: self.depth_format,
+            faces: self.faces,
+        }
+    }
+
+    pub fn with_queue(self, queue: &wgpu::Queue) -> SkyboxBuilder<T, U, V, builder::WithQueue> {
+        SkyboxBuilder {
+            device: self.device,
+            queue: builder::WithQueue(queue),
+            surface_config: self.surface_config,
+            camera_bind_group_layout: self.camera_bind_group_layout,
+            sample_count: self.sample_count,
+            color_format: self.color_format,
+            depth_format: self.depth_format,
+            faces: self.faces,
+        }
+    }
+
+    pub fn with_sample_count(mut self, sample_count: u32) -> Self {
+        self.sample_count = sample_count;
+        self
+    }
+
+    /// Set the format of the render pass color attachment, defaults to the
+    /// surface format.
+    pub fn with_color_format(mut self, color_format: wgpu::TextureFormat) -> Self {
+        self.color_format = Some(color_format);
+        self
+    }
+
+    /// Enable depth testing with the format of the render pass depth
+    /// attachment.
+    pub fn with_depth_format(mut self, depth_format: wgpu::TextureFormat) -> Self {
+        self.depth_format = Some(depth_format);
+        self
+    }
+
+    /// Set the faces in the order of +X, -X, +Y, -Y, +Z, -Z, defaults to white.
+    pub fn with_face_data(mut self, faces: [TextureData; 6]) -> Self {
+        self.faces = faces;
+        self
+    }
+
+    /// Set the faces decoded from PNG or JPEG bytes, in the order of +X, -X,
+    /// +Y, -Y, +Z, -Z.
+    #[cfg(feature = "image")]
+    pub fn with_faces(mut self, faces: [&[u8]; 6]) -> Result<Self, crate::systems::TextureError> {
+        let mut decoded = Vec::with_capacity(6);
+        for face in faces {
+            decoded.push(TextureData::from_image_bytes(face)?);
+        }
+        self.faces = decoded.try_into().expect("6 faces");
+        Ok(self)
+    }
+}
+
+impl<'a>
+    SkyboxBuilder<
+        builder::WithDevice<'a>,
+        builder::WithSurfaceConfig<'a>,
+        builder::WithCameraBindGroupLayout<'a>,
+        builder::WithQueue<'a>,
+    >
+{
+    pub fn build(self) -> Result<Skybox, SkyboxError> {
+        Skybox::new(
+            self.device.0,
+            self.queue.0,
+            self.surface_config.0,
+            self.camera_bind_group_layout.0,
+            self.sample_count,
+            self.color_format,
+            self.depth_format,
+            &self.faces,
+        )
+    }
+}
@@ -1,23 +1,89 @@
-use std::sync::Arc;
+use std::{
+    sync::{mpsc, Arc},
+    time::Duration,
+};
 
 use chrono::prelude::*;
 use winit::window::Window;
 
-use crate::{engine::utils, systems::FpsLimit};
+use crate::{
+    engine::utils,
+    systems::{EngineOutSignal, FpsLimit, StatsSignal},
+};
 
 /// Handler for time-related operations.
 pub struct Time {
     fps_limit: FpsLimit,
     delta: f32,
+    unclamped_delta: f32,
+    overshoot: f32,
+    max_delta: f32,
+    fixed_delta: f32,
+    accumulator: f32,
+    is_paused: bool,
+    time_scale: f32,
+    frame_times: Vec<f32>,
+    stats_window: usize,
+    stats_timer: DateTime<Utc>,
+    budget: Option<Duration>,
+    over_budget_frames: u64,
+    unwarned_over_budget_frames: u64,
+    budget_warning_timer: DateTime<Utc>,
     frame_timer: DateTime<Utc>,
     start_timer: DateTime<Utc>,
 }
 
 impl Time {
-    pub fn new(fps_limit: FpsLimit) -> Self {
+    pub const DEFAULT_MAX_DELTA: f32 = 0.1;
+
+    pub const DEFAULT_FIXED_DELTA: f32 = 1.0 / 60.0;
+    pub const MIN_FIXED_DELTA: f32 = 1e-4;
+
+    /// The maximum number of fixed steps in a frame.
+    ///
+    /// This avoids the spiral of death when a frame takes too long, e.g. on a
+    /// stalled tab.
+    pub const MAX_FIXED_STEPS: u32 = 8;
+
+    pub const DEFAULT_STATS_WINDOW: usize = 120;
+
+    /// The interval in seconds between each [`StatsSignal`].
+    pub const STATS_INTERVAL: f32 = 0.5;
+
+    /// The time in seconds at the end of the frame limit wait that is spun
+    /// instead of slept on native, since sleeping is only accurate to around
+    /// a millisecond.
+    pub const SPIN_WAIT: f32 = 1e-3;
+
+    /// The minimum interval in seconds between each frame budget warning.
+    pub const BUDGET_WARNING_INTERVAL: f32 = 1.0;
+
+    pub fn new(
+        fps_limit: FpsLimit,
+        max_delta: f32,
+        fixed_delta: f32,
+        stats_window: usize,
+        budget: Option<Duration>,
+    ) -> Self {
+        let stats_window = stats_window.max(1);
+
         Self {
             fps_limit,
             delta: 0.0,
+            unclamped_delta: 0.0,
+            overshoot: 0.0,
+            max_delta,
+            fixed_delta: fixed_delta.max(Self::MIN_FIXED_DELTA),
+            accumulator: 0.0,
+            is_paused: false,
+            time_scale: 1.0,
+            frame_times: Vec::with_capacity(stats_window + 1),
+            stats_window,
+            stats_timer: Utc::now(),
+            budget,
+            over_budget_frames: 0,
+            unwarned_over_budget_frames: 0,
+            budget_warning_timer: DateTime::<Utc>::MIN_UTC,
             frame_timer: Utc::now(),
             start_timer: Utc::now(),
         }
@@ -25,14 +91,37 @@ impl Time {
 
     pub fn update(&mut self) {
         // Calculate delta time
-        self.delta = self.time_since_last_frame();
+        self.unclamped_delta = self.time_since_last_frame();
+        self.delta = self.unclamped_delta.min(self.max_delta);
+        self.overshoot = match self.fps_limit.as_secs_f32() {
+            Some(secs) => (self.unclamped_delta - secs).max(0.0),
+            None => 0.0,
+        };
+
+        // Record frame time
+        self.frame_times.push(self.unclamped_delta);
+        if self.frame_times.len() > self.stats_window {
+            self.frame_times.remove(0);
+        }
 
         // Update frame timer
         self.frame_timer = Utc::now();
     }
 
+    /// Request the next frame, waiting for the rest of the frame limit.
+    ///
+    /// The time spent since [`Time::update`], including rendering and
+    /// presenting, is subtracted from the frame limit, so only the remaining
+    /// time is waited. On native, the thread sleeps then spins for the last
+    /// [`Time::SPIN_WAIT`]. On the web, blocking is impossible, so the redraw
+    /// is requested after a timeout and follows the `requestAnimationFrame`
+    /// cadence of the browser.
+    ///
+    /// The same time is compared against the budget, see
+    /// [`TimeBuilder::with_budget`].
     pub fn end_frame(&mut self, window: Arc<Window>) {
         let since_last = self.time_since_last_frame();
+        self.check_budget(since_last);
 
         // Limit the frame rate
         match self.fps_limit.as_secs_f32() {
@@ -41,10 +130,174 @@ impl Time {
         }
     }
 
+    pub fn fps_limit(&self) -> FpsLimit {
+        self.fps_limit
+    }
+
+    /// The frame time budget, [`None`] if unchecked.
+    pub fn budget(&self) -> Option<Duration> {
+        self.budget
+    }
+
+    /// The number of frames that took longer than the budget, including the
+    /// ones not warned about.
+    pub fn over_budget_frames(&self) -> u64 {
+        self.over_budget_frames
+    }
+
+    /// Set the frame rate limit, [`None`] means unlimited.
+    ///
+    /// This takes effect on the next [`Time::end_frame`].
+    pub fn set_fps_limit(&mut self, limit: Option<u32>) {
+        self.fps_limit = match limit {
+            Some(fps) => FpsLimit::new(fps),
+            None => FpsLimit::unlimited(),
+        };
+    }
+
+    /// The scaled delta time, or zero when paused.
+    ///
+    /// The delta time is clamped to [`Time::max_delta`] before scaling, so a
+    /// stall (e.g. a backgrounded tab) does not cause a huge jump.
     pub fn delta(&self) -> f32 {
+        match self.is_paused {
+            true => 0.0,
+            false => self.delta * self.time_scale,
+        }
+    }
+
+    /// The clamped delta time unaffected by pausing and time scale.
+    pub fn raw_delta(&self) -> f32 {
         self.delta
     }
 
+    /// The actual time since the last frame, not clamped to
+    /// [`Time::max_delta`].
+    ///
+    /// This is for diagnostics, use [`Time::delta`] or [`Time::raw_delta`]
+    /// otherwise.
+    pub fn unclamped_delta(&self) -> f32 {
+        self.unclamped_delta
+    }
+
+    /// How much longer than the frame limit the last frame took, zero without
+    /// a limit.
+    ///
+    /// This is for diagnostics of the frame pacing, a consistently large value
+    /// means the frames take longer than the limit to render.
+    pub fn overshoot(&self) -> f32 {
+        self.overshoot
+    }
+
+    pub fn max_delta(&self) -> f32 {
+        self.max_delta
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.is_paused
+    }
+
+    pub fn set_paused(&mut self, is_paused: bool) {
+        self.is_paused = is_paused;
+    }
+
+    pub fn time_scale(&self) -> f32 {
+        self.time_scale
+    }
+
+    /// Set the time scale, negative values are treated as zero.
+    pub fn set_time_scale(&mut self, time_scale: f32) {
+        self.time_scale = time_scale.max(0.0);
+    }
+
+    pub fn fixed_delta(&self) -> f32 {
+        self.fixed_delta
+    }
+
+    /// Call the step zero or more times with [`Time::fixed_delta`] to catch up
+    /// with the time accumulated since the last frame.
+    ///
+    /// This should be called once per frame after [`Time::update`].
+    ///
+    /// Since the accumulated time comes from the clamped [`Time::delta`], the
+    /// time lost in a stall longer than [`Time::max_delta`] is not caught
+    /// up.
+    pub fn fixed_update(&mut self, mut step: impl FnMut(f32)) {
+        self.accumulator += self.delta();
+
+        let mut steps = 0;
+        while self.accumulator >= self.fixed_delta {
+            if steps >= Self::MAX_FIXED_STEPS {
+                log::warn!("Fixed update is falling behind, skipping accumulated time");
+                self.accumulator %= self.fixed_delta;
+                break;
+            }
+
+            step(self.fixed_delta);
+            self.accumulator -= self.fixed_delta;
+            steps += 1;
+        }
+    }
+
+    /// The fraction of a fixed step left in the accumulator, in `0.0..1.0`.
+    ///
+    /// This can be used to interpolate between the last two fixed steps when
+    /// rendering.
+    pub fn interpolation_alpha(&self) -> f32 {
+        self.accumulator / self.fixed_delta
+    }
+
+    /// The frame times of the last frames in the stats window, from oldest to
+    /// newest.
+    pub fn frame_times(&self) -> &[f32] {
+        &self.frame_times
+    }
+
+    /// The average frames per second over the stats window.
+    pub fn average_fps(&self) -> f32 {
+        match self.frame_times.iter().sum::<f32>() {
+            total if total > 0.0 => self.frame_times.len() as f32 / total,
+            _ => 0.0,
+        }
+    }
+
+    /// The 99th percentile frame time over the stats window.
+    pub fn frame_time_p99(&self) -> f32 {
+        if self.frame_times.is_empty() {
+            return 0.0;
+        }
+
+        let mut frame_times = self.frame_times.clone();
+        frame_times.sort_by(f32::total_cmp);
+
+        let index = (frame_times.len() as f32 * 0.99).ceil() as usize - 1;
+        frame_times[index.min(frame_times.len() - 1)]
+    }
+
+    /// Send a [`StatsSignal`] if [`Time::STATS_INTERVAL`] has passed since the
+    /// last one, with the `visible_instances` drawn in the last frame.
+    pub fn signal(&mut self, visible_instances: usize, tx: &mpsc::Sender<EngineOutSignal>) {
+        let since_last = Utc::now()
+            .signed_duration_since(self.stats_timer)
+            .num_nanoseconds()
+            .expect("nanoseconds since last stats") as f32
+            * 1e-9;
+
+        if since_last < Self::STATS_INTERVAL {
+            return;
+        }
+
+        tx.send(StatsSignal::out_signal(
+            self.average_fps(),
+            self.frame_time_p99(),
+            self.frame_times.clone(),
+            visible_instances,
+        ))
+        .unwrap();
+
+        self.stats_timer = Utc::now();
+    }
+
     pub fn elapsed(&self) -> f32 {
         Utc::now()
             .signed_duration_since(self.start_timer)
@@ -61,6 +314,45 @@ impl Time {
             * 1e-9
     }
 
+    /// Count the frame if `frame_time` is over the budget, and warn at most
+    /// once every [`Time::BUDGET_WARNING_INTERVAL`].
+    ///
+    /// A slow frame makes the next ones slower with more to catch up on, so
+    /// warning every frame would only add to it.
+    fn check_budget(&mut self, frame_time: f32) {
+        let Some(budget) = self.budget else {
+            return;
+        };
+
+        let budget = budget.as_secs_f32();
+        if frame_time <= budget {
+            return;
+        }
+
+        self.over_budget_frames += 1;
+        self.unwarned_over_budget_frames += 1;
+
+        let since_warning = Utc::now()
+            .signed_duration_since(self.budget_warning_timer)
+            .num_milliseconds() as f32
+            * 1e-3;
+
+        if since_warning < Self::BUDGET_WARNING_INTERVAL {
+            return;
+        }
+
+        log::warn!(
+            "Frame took {:.2}ms, {:.2}ms over the budget of {:.2}ms ({} frames over budget since the last warning)",
+            frame_time * 1000.0,
+            (frame_time - budget) * 1000.0,
+            budget * 1000.0,
+            self.unwarned_over_budget_frames,
+        );
+
+        self.unwarned_over_budget_frames = 0;
+        self.budget_warning_timer = Utc::now();
+    }
+
     fn set_timeout_redraw(window: Arc<Window>, duration: f32) {
         cfg_if::cfg_if! {
             if #[cfg(target_arch = "wasm32")] {
@@ -68,7 +360,16 @@ impl Time {
                     window.request_redraw();
                 }, (duration * 1000.0) as i32)
             } else {
-                std::thread::sleep(std::time::Duration::from_secs_f32(duration));
+                use std::time::{Duration, Instant};
+
+                let deadline = Instant::now() + Duration::from_secs_f32(duration);
+                if duration > Self::SPIN_WAIT {
+                    std::thread::sleep(Duration::from_secs_f32(duration - Self::SPIN_WAIT));
+                }
+                while Instant::now() < deadline {
+                    std::hint::spin_loop();
+                }
+
                 window.request_redraw();
             }
         }
@@ -78,12 +379,20 @@ impl Time {
 /// Builder of [`Time`].
 pub struct TimeBuilder {
     fps_limit: FpsLimit,
+    max_delta: f32,
+    fixed_delta: f32,
+    stats_window: usize,
+    budget: Option<Duration>,
 }
 
 impl TimeBuilder {
     pub fn new() -> Self {
         Self {
             fps_limit: FpsLimit::unlimited(),
+            max_delta: Time::DEFAULT_MAX_DELTA,
+            fixed_delta: Time::DEFAULT_FIXED_DELTA,
+            stats_window: Time::DEFAULT_STATS_WINDOW,
+            budget: None,
         }
     }
 }
@@ -94,7 +403,39 @@ impl TimeBuilder {
         self
     }
 
+    /// Set the maximum delta time in seconds, see [`Time::delta`].
+    pub fn with_max_delta(mut self, secs: f32) -> Self {
+        self.max_delta = secs;
+        self
+    }
+
+    pub fn with_fixed_timestep(mut self, secs: f32) -> Self {
+        self.fixed_delta = secs;
+        self
+    }
+
+    /// Set the number of frames kept for the frame time statistics.
+    pub fn with_stats_window(mut self, stats_window: usize) -> Self {
+        self.stats_window = stats_window;
+        self
+    }
+
+    /// Set the frame time budget, e.g. 16.6ms for 60 FPS, warning when a frame
+    /// takes longer to update and render, see [`Time::over_budget_frames`].
+    ///
+    /// The time waited for the frame limit is not counted.
+    pub fn with_budget(mut self, budget: Duration) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+
     pub fn build(self) -> Time {
-        Time::new(self.fps_limit)
+        Time::new(
+            self.fps_limit,
+            self.max_delta,
+            self.fixed_delta,
+            self.stats_window,
+            self.budget,
+        )
     }
 }
@@ -0,0 +1,101 @@
+use std::collections::HashMap;
+
+use glam::*;
+use winit::event::{Touch as TouchEvent, TouchPhase, WindowEvent};
+
+/// Handler for the touch points.
+///
+/// [`winit_input_helper::WinitInputHelper`] does not track multitouch, so the
+/// active touch points are tracked here from [`Touch::window_event`], and
+/// turned into [`TouchGestures`] once per frame in [`Touch::update`].
+#[derive(Debug, Default)]
+pub struct Touch {
+    points: HashMap<u64, Vec2>,
+    last_points: HashMap<u64, Vec2>,
+    gestures: TouchGestures,
+}
+
+impl Touch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The number of fingers on the screen.
+    pub fn count(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_active(&self) -> bool {
+        !self.points.is_empty()
+    }
+
+    /// The gestures of the frame.
+    pub fn gestures(&self) -> &TouchGestures {
+        &self.gestures
+    }
+
+    pub fn window_event(&mut self, event: &WindowEvent) {
+        if let WindowEvent::Touch(TouchEvent {
+            id,
+            phase,
+            location,
+            ..
+        }) = event
+        {
+            let location = Vec2::new(location.x as f32, location.y as f32);
+            match phase {
+                TouchPhase::Started | TouchPhase::Moved => {
+                    self.points.insert(*id, location);
+                }
+                TouchPhase::Ended | TouchPhase::Cancelled => {
+                    self.points.remove(id);
+                }
+            }
+        }
+    }
+
+    /// Compute the gestures since the last frame.
+    ///
+    /// A gesture is only recognized when the same fingers stay on the screen
+    /// across the frames, so adding or lifting a finger does not jump.
+    pub fn update(&mut self) {
+        self.gestures = TouchGestures::default();
+
+        let is_same_points = self.points.len() == self.last_points.len()
+            && self
+                .points
+                .keys()
+                .all(|id| self.last_points.contains_key(id));
+
+        if is_same_points {
+            match self.points.len() {
+                1 => {
+                    let (id, point) = self.points.iter().next().expect("one point");
+                    self.gestures.look = *point - self.last_points[id];
+                }
+                2 => {
+                    let ids = self.points.keys().collect::<Vec<_>>();
+                    let (a, b) = (self.points[ids[0]], self.points[ids[1]]);
+                    let (last_a, last_b) = (self.last_points[ids[0]], self.last_points[ids[1]]);
+
+                    self.gestures.pan = (a + b) / 2.0 - (last_a + last_b) / 2.0;
+                    self.gestures.pinch = a.distance(b) - last_a.distance(last_b);
+                }
+                _ => {}
+            }
+        }
+
+        self.last_points.clone_from(&self.points);
+    }
+}
+
+/// The touch gestures of a frame in physical pixels.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TouchGestures {
+    /// The motion of one finger dragging, used for looking.
+    pub look: Vec2,
+    /// The motion of the center of two fingers dragging, used for panning.
+    pub pan: Vec2,
+    /// The change in distance between two fingers, positive when spreading.
+    pub pinch: f32,
+}
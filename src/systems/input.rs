@@ -0,0 +1,166 @@
+use std::collections::HashMap;
+
+use winit::{event::MouseButton, keyboard::KeyCode};
+use winit_input_helper::WinitInputHelper;
+
+/// Mapping of named actions to the inputs triggering them.
+///
+/// Handlers query actions, e.g. `actions.pressed(input, "move_forward")`,
+/// instead of raw keys, so the controls can be rebound in one place. An
+/// action can have multiple chords, any of which triggers it.
+///
+/// With the `serde` feature, this is (de)serialized as a map of action names
+/// to chords, so rebinds can be persisted.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+pub struct ActionMap {
+    actions: HashMap<String, Vec<Chord>>,
+}
+
+impl ActionMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a chord triggering the action, keeping the existing ones.
+    pub fn bind(&mut self, action: impl Into<String>, chord: impl Into<Chord>) {
+        self.actions
+            .entry(action.into())
+            .or_default()
+            .push(chord.into());
+    }
+
+    /// Add a chord triggering the action, see [`ActionMap::bind`].
+    pub fn with_binding(mut self, action: impl Into<String>, chord: impl Into<Chord>) -> Self {
+        self.bind(action, chord);
+        self
+    }
+
+    /// Replace all the chords of the action.
+    pub fn rebind(&mut self, action: impl Into<String>, chords: Vec<Chord>) {
+        self.actions.insert(action.into(), chords);
+    }
+
+    /// Remove all the chords of the action, returning them.
+    pub fn unbind(&mut self, action: &str) -> Option<Vec<Chord>> {
+        self.actions.remove(action)
+    }
+
+    /// The chords of the action, empty if unbound.
+    pub fn chords(&self, action: &str) -> &[Chord] {
+        self.actions.get(action).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// The bound actions and their chords.
+    pub fn actions(&self) -> impl Iterator<Item = (&str, &[Chord])> {
+        self.actions
+            .iter()
+            .map(|(action, chords)| (action.as_str(), chords.as_slice()))
+    }
+
+    /// Whether any chord of the action is held.
+    pub fn pressed(&self, input: &WinitInputHelper, action: &str) -> bool {
+        self.chords(action).iter().any(|chord| chord.held(input))
+    }
+
+    /// Whether any chord of the action was completed this frame.
+    pub fn just_pressed(&self, input: &WinitInputHelper, action: &str) -> bool {
+        self.chords(action)
+            .iter()
+            .any(|chord| chord.just_pressed(input))
+    }
+}
+
+/// A key or mouse button.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum InputButton {
+    Key(KeyCode),
+    Mouse(MouseButton),
+}
+
+impl InputButton {
+    fn held(&self, input: &WinitInputHelper) -> bool {
+        match *self {
+            Self::Key(key) => input.key_held(key),
+            Self::Mouse(button) => input.mouse_held(button),
+        }
+    }
+
+    fn just_pressed(&self, input: &WinitInputHelper) -> bool {
+        match *self {
+            Self::Key(key) => input.key_pressed(key),
+            Self::Mouse(button) => input.mouse_pressed(button),
+        }
+    }
+}
+
+impl From<KeyCode> for InputButton {
+    fn from(key: KeyCode) -> Self {
+        Self::Key(key)
+    }
+}
+
+impl From<MouseButton> for InputButton {
+    fn from(button: MouseButton) -> Self {
+        Self::Mouse(button)
+    }
+}
+
+/// A button with the modifiers held with it, e.g. Shift+W.
+///
+/// The modifiers are not exclusive, so W also triggers while Shift+W does.
+/// Bind the chords with more modifiers to actions checked first if they
+/// conflict.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Chord {
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub modifiers: Vec<InputButton>,
+    pub button: InputButton,
+}
+
+impl Chord {
+    pub fn new(modifiers: Vec<InputButton>, button: impl Into<InputButton>) -> Self {
+        Self {
+            modifiers,
+            button: button.into(),
+        }
+    }
+
+    /// Whether the button and all the modifiers are held.
+    pub fn held(&self, input: &WinitInputHelper) -> bool {
+        self.button.held(input) && self.modifiers.iter().all(|modifier| modifier.held(input))
+    }
+
+    /// Whether the button was pressed this frame with all the modifiers held.
+    ///
+    /// Pressing a modifier after the button does not trigger this, and neither
+    /// does holding the button, so this fires once per press.
+    pub fn just_pressed(&self, input: &WinitInputHelper) -> bool {
+        self.button.just_pressed(input)
+            && self.modifiers.iter().all(|modifier| modifier.held(input))
+    }
+}
+
+impl From<InputButton> for Chord {
+    fn from(button: InputButton) -> Self {
+        Self::new(Vec::new(), button)
+    }
+}
+
+impl From<KeyCode> for Chord {
+    fn from(key: KeyCode) -> Self {
+        Self::new(Vec::new(), key)
+    }
+}
+
+impl From<MouseButton> for Chord {
+    fn from(button: MouseButton) -> Self {
+        Self::new(Vec::new(), button)
+    }
+}
@@ -1,12 +1,16 @@
 mod args;
 mod error;
 pub mod handlers;
+mod input;
 mod pipeline;
+mod postprocess;
 mod signal;
 mod utils;
 
-pub use args::{Args, FpsLimit};
+pub use args::{Args, ArgsError, FpsLimit};
 pub use error::Error;
+pub use input::{ActionMap, Chord, InputButton};
 pub use pipeline::Pipeline;
+pub use postprocess::*;
 pub use signal::*;
 pub use utils::*;
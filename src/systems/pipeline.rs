@@ -1,10 +1,19 @@
 use std::sync::Arc;
 
-use winit::{dpi::LogicalSize, window::Window};
+use glam::*;
+use winit::{
+    dpi::{PhysicalPosition, PhysicalSize},
+    event::{DeviceEvent, ElementState, MouseButton, WindowEvent},
+    keyboard::KeyCode,
+    window::{Window, WindowAttributes},
+};
 
 use crate::{
     engine,
-    systems::{handlers, Args, Signal},
+    systems::{
+        self, handlers, Args, CloseRequestedSignal, Error, InitFailedSignal, LoadProgressSignal,
+        PickedSignal, Signal,
+    },
 };
 
 /// Pipeline.
@@ -12,83 +21,515 @@ pub struct Pipeline {
     time: handlers::Time,
     display: handlers::Display,
     cursor_lock: handlers::CursorLock,
-    camera: handlers::Camera,
+    touch: handlers::Touch,
+    /// The first camera is controlled and picks, the others are fixed views
+    /// drawn side by side with it for split screen.
+    cameras: Vec<handlers::Camera>,
+    /// The frustums of the cameras, reused across frames for culling.
+    frustums: Vec<systems::Frustum>,
+    skybox: Option<handlers::Skybox>,
+    light: handlers::Light,
+    lights: handlers::Lights,
+    ground: handlers::Ground,
+    grid: handlers::Grid,
+    gizmo: handlers::Gizmo,
     pyramid: handlers::Pyramid,
+    /// [`None`] on backends without compute shaders, e.g. WebGL.
+    compute: Option<handlers::Compute>,
+    #[cfg(all(feature = "hot-reload", debug_assertions, not(target_arch = "wasm32")))]
+    pyramid_shader_watcher: Option<systems::ShaderWatcher>,
+    model: Option<handlers::Model>,
+    pending_model:
+        Option<engine::PendingResource<Result<handlers::ModelData, handlers::ModelError>>>,
+    scenes: engine::Scenes<handlers::Display, Signal>,
+
+    actions: systems::ActionMap,
+    cursor_position: Option<PhysicalPosition<f64>>,
+}
+
+impl Pipeline {
+    /// The id of the built in pyramid scene, which is rendered when no
+    /// registered scene is active.
+    pub const BUILTIN_SCENE: &'static str = "pyramid";
+
+    /// The action switching the camera between fly and orbit mode.
+    pub const ACTION_TOGGLE_CONTROL_MODE: &'static str = "toggle_control_mode";
+    /// The action showing and hiding the pyramid wireframe overlay.
+    pub const ACTION_TOGGLE_WIREFRAME: &'static str = "toggle_wireframe";
+
+    /// The default bindings of the actions,
+    /// [`handlers::Camera::default_actions`] with Tab to toggle the control
+    /// mode and F to toggle the wireframe.
+    pub fn default_actions() -> systems::ActionMap {
+        handlers::Camera::default_actions()
+            .with_binding(Self::ACTION_TOGGLE_CONTROL_MODE, KeyCode::Tab)
+            .with_binding(Self::ACTION_TOGGLE_WIREFRAME, KeyCode::KeyF)
+    }
 }
 
 impl engine::SystemPipeline for Pipeline {
     type Args = Args;
     type InSignal = Signal;
     type OutSignal = Signal;
+    type InitError = Error;
+
+    fn window_attributes(args: &Args, window_attributes: WindowAttributes) -> WindowAttributes {
+        args.window_attributes(window_attributes)
+    }
 
-    async fn init(window: Arc<Window>, configs: Self::Args) -> Self {
+    async fn init(window: Arc<Window>, configs: Self::Args) -> Result<Self, Self::InitError> {
         log::debug!("Initializing system pipeline");
 
         let time = handlers::TimeBuilder::new()
             .with_fps_limit(configs.fps_limit)
             .build();
-        let display = handlers::DisplayBuilder::new()
+        let mut display = handlers::DisplayBuilder::new()
             .with_window(window.clone())
             .with_clear_color(configs.clear_color)
+            .with_sample_count(configs.sample_count)
+            .with_hdr(configs.hdr)
+            .with_fxaa(configs.fxaa)
+            .with_render_scale(configs.render_scale)
+            .with_target_aspect(configs.target_aspect)
             .build()
-            .await;
+            .await?;
+        display.set_on_frame_complete(|frame_index| log::trace!("Frame {frame_index} rendered"));
+        if configs.trails {
+            display.set_load_op(handlers::ColorLoadOp::Load);
+        }
         let cursor_lock = handlers::CursorLockBuilder::new()
             .with_window(window.clone())
             .with_should_lock_cursor(true)
             .build();
-        let camera = handlers::CameraBuilder::new()
+        let viewports = display.split_viewports(if configs.split_screen { 2 } else { 1 });
+        let mut cameras = vec![handlers::CameraBuilder::new()
+            .with_device(display.device())
+            .with_aspect_ratio(viewports[0].aspect_ratio())
+            .build()];
+        if configs.split_screen {
+            // The second view looks at the pyramid from the opposite side
+            cameras.push(
+                handlers::CameraBuilder::new()
+                    .with_device(display.device())
+                    .with_aspect_ratio(viewports[1].aspect_ratio())
+                    .with_position(vec3(0.0, 2.0, -5.0))
+                    .looking_at(Vec3::ZERO)
+                    .build(),
+            );
+        }
+        let skybox = configs.skybox.and_then(|faces| {
+            handlers::SkyboxBuilder::new()
+                .with_device(display.device())
+                .with_queue(display.queue())
+                .with_surface_config(display.config())
+                .with_camera_bind_group_layout(cameras[0].bind_group_layout())
+                .with_sample_count(display.sample_count())
+                .with_color_format(display.color_format())
+                .with_depth_format(display.depth_format())
+                .with_face_data(faces)
+                .build()
+                .inspect_err(|e| log::warn!("Failed to create skybox: {e}"))
+                .ok()
+        });
+        let light = handlers::LightBuilder::new()
+            .with_device(display.device())
+            .build();
+        // WebGL has no storage buffers nor compute shaders
+        let is_gl = display.backend() == wgpu::Backend::Gl;
+        let lights = handlers::LightsBuilder::new()
             .with_device(display.device())
-            .with_aspect_ratio(display.aspect_ratio())
+            .with_storage(!is_gl)
             .build();
+        let ground = handlers::GroundBuilder::new()
+            .with_device(display.device())
+            .with_surface_config(display.config())
+            .with_camera_bind_group_layout(cameras[0].bind_group_layout())
+            .with_light_bind_group_layout(light.bind_group_layout())
+            .with_sample_count(display.sample_count())
+            .with_color_format(display.color_format())
+            .with_depth_format(display.depth_format())
+            .build();
+        let grid = handlers::GridBuilder::new()
+            .with_device(display.device())
+            .with_surface_config(display.config())
+            .with_camera_bind_group_layout(cameras[0].bind_group_layout())
+            .with_sample_count(display.sample_count())
+            .with_color_format(display.color_format())
+            .with_depth_format(display.depth_format())
+            .build();
+        let gizmo = handlers::GizmoBuilder::new()
+            .with_device(display.device())
+            .with_surface_config(display.config())
+            .with_camera_bind_group_layout(cameras[0].bind_group_layout())
+            .with_sample_count(display.sample_count())
+            .with_color_format(display.color_format())
+            .with_depth_format(display.depth_format())
+            .build();
+        #[cfg(feature = "rng")]
+        let pyramid_instances = match configs.pyramid_scatter_count {
+            0 => vec![handlers::PyramidInstance::default()],
+            count => {
+                log::debug!(
+                    "Scattering {count} pyramid instances with seed {}",
+                    configs.seed
+                );
+                handlers::PyramidInstance::scatter(
+                    &mut systems::Rng::new(configs.seed),
+                    count,
+                    configs.pyramid_scatter_radius,
+                )
+            }
+        };
+        #[cfg(not(feature = "rng"))]
+        let pyramid_instances = vec![handlers::PyramidInstance::default()];
         let pyramid = handlers::PyramidBuilder::new()
             .with_device(display.device())
+            .with_queue(display.queue())
             .with_surface_config(display.config())
-            .with_camera_bind_group_layout(camera.bind_group_layout())
+            .with_camera_bind_group_layout(cameras[0].bind_group_layout())
+            .with_light_bind_group_layout(light.bind_group_layout())
+            .with_point_lights_bind_group_layout(lights.bind_group_layout())
+            .with_point_lights_storage(lights.is_storage())
+            .with_shadow_bind_group_layout(light.shadow_bind_group_layout())
+            .with_sample_count(display.sample_count())
+            .with_color_format(display.color_format())
+            .with_depth_format(display.depth_format())
             .with_pyramid_transform(configs.pyramid_transform)
             .with_model(configs.pyramid_model)
-            .build();
+            .with_instances(pyramid_instances)
+            .with_objects(configs.pyramid_objects)
+            .with_anisotropy(systems::Texture::clamp_anisotropy(
+                configs.anisotropy,
+                display.max_anisotropy(),
+            ))
+            .build()?;
+        let compute = match is_gl {
+            true => {
+                log::info!("Compute is not supported on the GL backend, disabling it");
+                None
+            }
+            false => Some(
+                handlers::ComputeBuilder::new()
+                    .with_device(display.device())
+                    .with_pyramid(&pyramid)
+                    .build()?,
+            ),
+        };
+
+        // Reload the pyramid shader from the source tree when it is saved
+        #[cfg(all(feature = "hot-reload", debug_assertions, not(target_arch = "wasm32")))]
+        let pyramid_shader_watcher = systems::ShaderWatcher::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/src/systems/shaders/pyramid.wgsl"
+        ))
+        .inspect_err(|e| log::warn!("Failed to watch pyramid shader: {e}"))
+        .ok();
+
+        // Parse the model across frames so the first frame is not blocked
+        #[cfg(feature = "gltf")]
+        let pending_model = configs.model_gltf.map(|bytes| {
+            engine::PendingResource::new(|progress| async move {
+                handlers::ModelData::load_gltf_bytes(&bytes, &progress).await
+            })
+        });
+        #[cfg(not(feature = "gltf"))]
+        let pending_model = None;
+
+        // Register scenes with `with_scene`, they share the display so the
+        // device and queue are kept when switching
+        let scenes = engine::Scenes::new();
 
         log::info!("System pipeline initialized");
 
-        Self {
+        Ok(Self {
             time,
             display,
             cursor_lock,
-            camera,
+            touch: handlers::Touch::new(),
+            cameras,
+            frustums: Vec::new(),
+            skybox,
+            light,
+            lights,
+            ground,
+            grid,
+            gizmo,
             pyramid,
+            compute,
+            #[cfg(all(feature = "hot-reload", debug_assertions, not(target_arch = "wasm32")))]
+            pyramid_shader_watcher,
+            model: None,
+            pending_model,
+            scenes,
+
+            actions: configs.actions,
+            cursor_position: None,
+        })
+    }
+
+    fn init_error_signal(error: &Self::InitError) -> Option<Self::OutSignal> {
+        Some(InitFailedSignal::out_signal(error.to_string()))
+    }
+
+    fn on_suspend(&mut self) {
+        self.display.suspend();
+    }
+
+    fn on_resume(&mut self, window: Arc<Window>) {
+        self.display.resume(window);
+    }
+
+    fn on_close_requested(&mut self, items: &mut engine::Items<Self::OutSignal>) -> bool {
+        log::info!("Close requested, sending the state to save");
+        if let Some(tx) = items.tx.as_ref() {
+            let _ = tx.send(CloseRequestedSignal::out_signal(
+                self.cameras[0].model().clone(),
+            ));
         }
+        true
+    }
+
+    fn device_event(&mut self, items: &mut engine::Items<Self::OutSignal>, event: &DeviceEvent) {
+        self.cursor_lock.device_event(event);
+        self.scenes.device_event(&mut self.display, items, event);
     }
 
     fn window_event(
         &mut self,
-        _: &mut engine::Items<Self::OutSignal>,
+        items: &mut engine::Items<Self::OutSignal>,
         event: &winit::event::WindowEvent,
     ) {
+        self.display.window_event(event);
         self.cursor_lock.window_event(event);
+        self.touch.window_event(event);
+
+        if self.scenes.active_id().is_some() {
+            self.scenes.window_event(&mut self.display, items, event);
+            return;
+        }
+
+        match event {
+            WindowEvent::CursorMoved { position, .. } => self.cursor_position = Some(*position),
+            WindowEvent::CursorLeft { .. } => self.cursor_position = None,
+            WindowEvent::MouseInput {
+                state: ElementState::Pressed,
+                button: MouseButton::Left,
+                ..
+            } => {
+                // Pick at the center when the cursor is locked
+                let position = match self.cursor_lock.is_cursor_locked() {
+                    true => Some(PhysicalPosition::new(
+                        self.display.config().width as f64 / 2.0,
+                        self.display.config().height as f64 / 2.0,
+                    )),
+                    false => self.cursor_position,
+                };
+
+                if let Some(position) = position {
+                    self.display.request_pick(position);
+                }
+            }
+            _ => {}
+        }
     }
 
     fn update(&mut self, items: &mut engine::Items<Self::OutSignal>) {
         // Updates
         self.time.update();
         self.display.update(&items.input);
-        self.cursor_lock.update(&mut items.input);
-        self.pyramid.update(self.time.delta());
+        self.cursor_lock.update();
+        self.touch.update();
+
+        // The active scene replaces the built in one
+        if self.scenes.active_id().is_some() {
+            self.scenes.update(&mut self.display, items);
+
+            if let Some(tx) = items.tx.as_ref() {
+                // The pyramid is not drawn in a scene
+                self.time.signal(0, tx);
+                self.display.signal(tx);
+            }
+
+            self.time.end_frame(items.window.clone());
+            return;
+        }
+
+        // Loading screen until the model is ready
+        if let Some(pending_model) = self.pending_model.as_mut() {
+            let data = pending_model.poll();
+
+            if let Some(tx) = items.tx.as_ref() {
+                tx.send(LoadProgressSignal::out_signal(pending_model.progress()))
+                    .unwrap();
+            }
+
+            match data {
+                Some(Ok(data)) => {
+                    log::info!("Model loaded with {} primitives", data.primitives.len());
+                    self.model = Some(
+                        handlers::ModelBuilder::new()
+                            .with_device(self.display.device())
+                            .with_surface_config(self.display.config())
+                            .with_camera_bind_group_layout(self.cameras[0].bind_group_layout())
+                            .with_sample_count(self.display.sample_count())
+                            .with_color_format(self.display.color_format())
+                            .with_depth_format(self.display.depth_format())
+                            .with_data(data)
+                            .build(),
+                    );
+
+                    // The size of the model is unknown, so fit it in view
+                    if let Some((min, max)) = self.model.as_ref().and_then(|m| m.bounding_box()) {
+                        let aspect_ratio =
+                            self.display.split_viewports(self.cameras.len())[0].aspect_ratio();
+                        self.cameras[0].frame_bounds(min, max, aspect_ratio);
+                    }
+                }
+                Some(Err(e)) => log::warn!("Failed to load model: {e}"),
+                None => {
+                    if let Err(e) = self.display.render(|_, _| {}) {
+                        panic!("Fatal display error: {e}");
+                    }
+                    self.time.end_frame(items.window.clone());
+                    return;
+                }
+            }
+
+            self.pending_model = None;
+        }
+
+        #[cfg(all(feature = "hot-reload", debug_assertions, not(target_arch = "wasm32")))]
+        if let Some(source) = self
+            .pyramid_shader_watcher
+            .as_ref()
+            .and_then(|watcher| watcher.poll())
+        {
+            if let Err(e) = self.pyramid.reload_shader(self.display.device(), &source) {
+                log::warn!("Failed to reload pyramid shader, keeping the last one: {e}");
+            }
+        }
 
-        if self.cursor_lock.is_cursor_locked() {
-            self.camera.update(self.time.delta(), &items.input);
+        self.time.fixed_update(|dt| self.pyramid.update(dt));
+        if let Some(compute) = &mut self.compute {
+            compute.update(self.time.delta());
+        }
+
+        // Toggles are edge triggered with `just_pressed`, which is only true on
+        // the frame the key goes down, so holding the key does not retrigger
+        // them, unlike the movement which is polled with `pressed` every frame
+        if self
+            .actions
+            .just_pressed(&items.input, Self::ACTION_TOGGLE_CONTROL_MODE)
+        {
+            self.cameras[0].toggle_control_mode();
+        }
+        if self
+            .actions
+            .just_pressed(&items.input, Self::ACTION_TOGGLE_WIREFRAME)
+        {
+            let mut wireframe = self.pyramid.wireframe().clone();
+            wireframe.is_enabled = !wireframe.is_enabled;
+            self.pyramid.set_wireframe(wireframe);
+        }
+
+        // Touch screens cannot lock the cursor
+        if self.cursor_lock.is_cursor_locked() || self.touch.is_active() {
+            self.cameras[0].update(
+                self.time.delta(),
+                &items.input,
+                &self.actions,
+                self.cursor_lock.mouse_motion(&items.input),
+                self.touch.gestures(),
+                &items.gamepad,
+            );
         }
 
         // Signal
         if let Some(tx) = items.tx.as_ref() {
+            self.time.signal(self.pyramid.visible_instance_count(), tx);
+            self.display.signal(tx);
+            self.cameras[0].signal(self.time.delta(), tx);
             self.pyramid.signal(tx);
+
+            // Only the pyramid is pickable, so it resolves the picked ID
+            if let Some(id) = self.display.take_picked() {
+                let object = id.and_then(|id| self.pyramid.picked_object(id));
+                tx.send(PickedSignal::out_signal(object)).unwrap();
+            }
         }
 
         // Render
-        self.display.render(|display, pass| {
-            self.camera
-                .render(display.queue(), display.aspect_ratio(), &items.input);
+        self.light.render(self.display.queue());
+        if let Some(compute) = self.compute.as_ref().filter(|compute| compute.is_enabled()) {
+            // Deform the current model, before any pass draws it
+            self.pyramid.write_buffers(&self.display);
+            self.display
+                .compute(|display, pass| compute.compute(display, pass, &self.pyramid));
+        }
+        let deformed_buffer = self
+            .compute
+            .as_ref()
+            .and_then(handlers::Compute::vertex_buffer);
+        self.display
+            .render_shadow(self.light.shadow_map_view(), |display, pass| {
+                self.pyramid.render_shadow(
+                    display,
+                    pass,
+                    self.light.shadow_bind_group(),
+                    deformed_buffer,
+                )
+            });
+        self.lights
+            .render(self.display.device(), self.display.queue());
+
+        let viewports = self.display.split_viewports(self.cameras.len());
+
+        // The cameras share the instance buffer, so cull against all of them
+        self.frustums.clear();
+        self.frustums.extend(
+            self.cameras
+                .iter()
+                .zip(&viewports)
+                .map(|(camera, viewport)| camera.frustum(viewport.aspect_ratio())),
+        );
+        self.pyramid.cull(&self.frustums);
+
+        // The gizmo shows the axes of the first object
+        if let Some(matrix) = self.pyramid.object_matrices().next() {
+            self.gizmo.set_transform(matrix);
+        }
+
+        // Recoverable surface errors are handled by the display, there is no way
+        // to continue rendering from the rest
+        let result = self
+            .display
+            .render_viewports(&viewports, |display, pass, i| {
+                let camera = &mut self.cameras[i];
+                camera.render(display, viewports[i].aspect_ratio());
+
+                let mut ground = self.ground.pass(self.light.bind_group());
+                let mut pyramid = self.pyramid.pass(
+                    self.light.bind_group(),
+                    self.lights.bind_group(),
+                    deformed_buffer,
+                );
+                let mut passes: Vec<&mut dyn handlers::RenderPass> =
+                    vec![&mut ground, &mut self.grid, &mut pyramid, &mut self.gizmo];
+                if let Some(skybox) = self.skybox.as_mut() {
+                    passes.push(skybox);
+                }
+                if let Some(model) = self.model.as_mut() {
+                    passes.push(model);
+                }
+                handlers::draw_passes(&mut passes, pass, display, camera.bind_group());
+            });
+        if let Err(e) = result {
+            panic!("Fatal display error: {e}");
+        }
+        self.display.render_pick(|display, pass| {
+            viewports[0].set(pass);
             self.pyramid
-                .render(display.queue(), pass, self.camera.bind_group())
+                .render_pick(display, pass, self.cameras[0].bind_group(), deformed_buffer)
         });
 
         self.time.end_frame(items.window.clone());
@@ -102,18 +543,203 @@ impl engine::SystemPipeline for Pipeline {
                     resize.width,
                     resize.height
                 );
+                // Hidden or minimized, there is nothing to render into
+                if resize.width <= 0.0 || resize.height <= 0.0 {
+                    return;
+                }
+
                 let _ = items
                     .window
-                    .request_inner_size(LogicalSize::new(resize.width, resize.height));
+                    .request_inner_size(PhysicalSize::new(resize.width, resize.height));
+            }
+            Signal::FpsLimitUpdate(update) => {
+                log::debug!("FPS limit incoming signal: {:?}", update.fps_limit);
+                self.time.set_fps_limit(update.fps_limit);
+            }
+            Signal::PausedUpdate(update) => {
+                log::debug!("Paused incoming signal: {}", update.paused);
+                self.time.set_paused(update.paused);
+            }
+            Signal::TimeScaleUpdate(update) => {
+                log::debug!("Time scale incoming signal: {}", update.time_scale);
+                self.time.set_time_scale(update.time_scale);
+            }
+            Signal::PresentModeUpdate(update) => {
+                log::debug!("Present mode incoming signal: {:?}", update.present_mode);
+                self.display.set_present_mode(update.present_mode);
+            }
+            Signal::ClearColorUpdate(update) => {
+                log::debug!("Clear color incoming signal: {:?}", update.clear_color);
+                self.display.set_clear_color(update.clear_color);
+            }
+            Signal::ExposureUpdate(update) => {
+                log::debug!("Exposure incoming signal: {}", update.exposure);
+                self.display.set_exposure(update.exposure);
+            }
+            Signal::BloomUpdate(update) => {
+                log::debug!(
+                    "Bloom incoming signal: threshold {}, intensity {}",
+                    update.threshold,
+                    update.intensity
+                );
+                self.display.set_bloom(update.threshold, update.intensity);
+            }
+            Signal::FxaaUpdate(update) => {
+                log::debug!("FXAA incoming signal: {}", update.fxaa);
+                self.display.set_fxaa(update.fxaa);
+            }
+            Signal::TrailsUpdate(update) => {
+                log::debug!("Trails incoming signal: {}", update.trails);
+                self.display.set_load_op(match update.trails {
+                    true => handlers::ColorLoadOp::Load,
+                    false => handlers::ColorLoadOp::Clear,
+                });
+            }
+            Signal::RenderScaleUpdate(update) => {
+                log::debug!("Render scale incoming signal: {}", update.render_scale);
+                self.display.set_render_scale(update.render_scale);
+            }
+            Signal::SwitchScene(switch) => {
+                log::debug!("Switch scene incoming signal: {}", switch.scene);
+                if self.pending_model.take().is_some() {
+                    log::info!("Model load cancelled by scene switch");
+                }
+                match switch.scene.0 == Self::BUILTIN_SCENE {
+                    true => self.scenes.clear(&mut self.display, items),
+                    false => {
+                        if let Err(e) = self.scenes.switch(&switch.scene, &mut self.display, items)
+                        {
+                            log::warn!("Failed to switch scene: {e}");
+                        }
+                    }
+                }
+            }
+            Signal::CaptureFrame(..) => {
+                log::debug!("Capture frame incoming signal");
+                self.display.capture_frame();
+            }
+            Signal::FrameCaptured(..) => log::warn!("Frame captured signal is outgoing only"),
+            Signal::Picked(..) => log::warn!("Picked signal is outgoing only"),
+            Signal::InitFailed(..) => log::warn!("Init failed signal is outgoing only"),
+            Signal::Stats(..) => log::warn!("Stats signal is outgoing only"),
+            Signal::LoadProgress(..) => log::warn!("Load progress signal is outgoing only"),
+            Signal::CloseRequested(..) => log::warn!("Close requested signal is outgoing only"),
+            Signal::CancelLoad(..) => {
+                log::debug!("Cancel load incoming signal");
+                if self.pending_model.take().is_some() {
+                    log::info!("Model load cancelled");
+                }
             }
             Signal::PyramidTransformUpdate(update) => {
                 log::debug!("Pyramid transform incoming signal");
                 self.pyramid.set_transform(update.transform);
             }
+            Signal::PyramidAutoRotationUpdate(update) => {
+                log::debug!(
+                    "Pyramid auto rotation incoming signal: {:?} at {}",
+                    update.axis,
+                    update.speed
+                );
+                self.pyramid.set_auto_rotation(update.axis, update.speed);
+            }
             Signal::PyramidModelUpdate(update) => {
                 log::debug!("Pyramid model incoming signal");
                 self.pyramid.set_model(update.model);
             }
+            Signal::PyramidInstancesUpdate(update) => {
+                log::debug!(
+                    "Pyramid instances incoming signal: {} instances",
+                    update.instances.len()
+                );
+                self.pyramid.set_instances(
+                    self.display.device(),
+                    self.display.queue(),
+                    &update.instances,
+                );
+            }
+            Signal::PyramidPolygonModeUpdate(update) => {
+                log::debug!(
+                    "Pyramid polygon mode incoming signal: {:?}",
+                    update.polygon_mode
+                );
+                self.pyramid.set_polygon_mode(update.polygon_mode);
+            }
+            Signal::PyramidOpacityUpdate(update) => {
+                log::debug!("Pyramid opacity incoming signal: {}", update.opacity);
+                self.pyramid.set_opacity(update.opacity);
+            }
+            Signal::PyramidWireframeUpdate(update) => {
+                log::debug!("Pyramid wireframe incoming signal: {:?}", update.wireframe);
+                self.pyramid.set_wireframe(update.wireframe);
+            }
+            Signal::ComputeUpdate(update) => {
+                log::debug!(
+                    "Compute incoming signal: {} {:?}",
+                    update.is_enabled,
+                    update.params
+                );
+                match &mut self.compute {
+                    Some(compute) => {
+                        compute.set_enabled(update.is_enabled);
+                        compute.set_params(update.params);
+                    }
+                    None => log::warn!("Compute is not supported on the backend, ignoring"),
+                }
+            }
+            Signal::HandlersEnabledUpdate(update) => {
+                log::debug!(
+                    "Handlers enabled incoming signal: camera {}, pyramid {}, cursor lock {}",
+                    update.camera,
+                    update.pyramid,
+                    update.cursor_lock
+                );
+                self.cameras[0].set_enabled(update.camera);
+                self.pyramid.set_enabled(update.pyramid);
+                if let Err(e) = self.cursor_lock.set_enabled(update.cursor_lock) {
+                    log::warn!("Unable to release cursor lock on disabled: {e:?}");
+                }
+            }
+            Signal::GizmoUpdate(update) => {
+                log::debug!(
+                    "Gizmo incoming signal: enabled {}, on top {}",
+                    update.is_enabled,
+                    update.is_on_top
+                );
+                self.gizmo.set_enabled(update.is_enabled);
+                self.gizmo.set_on_top(update.is_on_top);
+            }
+            Signal::ActionMapUpdate(update) => {
+                log::debug!("Action map incoming signal: {:?}", update.actions);
+                self.actions = update.actions;
+            }
+            Signal::CameraUpdate(update) => {
+                log::debug!("Camera incoming signal");
+                self.cameras[0].set_model(update.model);
+            }
+            Signal::CameraMoved(..) => log::warn!("Camera moved signal is outgoing only"),
+            Signal::LightUpdate(update) => {
+                log::debug!("Light incoming signal: {:?}", update.model);
+                self.light.set_model(update.model);
+            }
+            Signal::PointLightsUpdate(update) => {
+                log::debug!("Point lights incoming signal: {:?}", update.lights);
+                self.lights.set_lights(&update.lights);
+            }
+        }
+    }
+
+    fn in_signals(&mut self, items: &mut engine::Items<Self::OutSignal>, signals: Vec<Signal>) {
+        // Only the last transform update of the frame is applied
+        let last_transform_update = signals
+            .iter()
+            .rposition(Signal::is_pyramid_transform_update);
+
+        for (i, signal) in signals.into_iter().enumerate() {
+            if signal.is_pyramid_transform_update() && Some(i) != last_transform_update {
+                continue;
+            }
+
+            self.in_signal(items, signal);
         }
     }
 }
@@ -0,0 +1,382 @@
+use wgpu::util::DeviceExt;
+
+/// Bloom post-process on an HDR target.
+///
+/// The pixels above the threshold are extracted into a half resolution mip
+/// chain, downsampled, upsampled back with a tent filter, and added onto the
+/// HDR target scaled by the intensity.
+#[derive(Debug)]
+pub struct Bloom {
+    threshold: f32,
+    intensity: f32,
+
+    threshold_buffer: wgpu::Buffer,
+    sampler: wgpu::Sampler,
+    bind_group_layout: wgpu::BindGroupLayout,
+
+    prefilter_pipeline: wgpu::RenderPipeline,
+    downsample_pipeline: wgpu::RenderPipeline,
+    upsample_pipeline: wgpu::RenderPipeline,
+    composite_pipeline: wgpu::RenderPipeline,
+
+    mips: BloomMips,
+}
+
+impl Bloom {
+    pub const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+
+    /// The maximum number of mip levels of the chain.
+    pub const MAX_MIP_LEVELS: u32 = 6;
+
+    pub fn new(
+        device: &wgpu::Device,
+        config: &wgpu::SurfaceConfiguration,
+        source: &wgpu::TextureView,
+        threshold: f32,
+        intensity: f32,
+    ) -> Self {
+        let threshold_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Bloom Threshold Buffer"),
+            contents: bytemuck::bytes_of(&Self::threshold_uniform(threshold)),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Bloom Sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Bloom Bind Group Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        });
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Bloom Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("../shaders/bloom.wgsl").into()),
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Bloom Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let additive = |factor| wgpu::BlendState {
+            color: wgpu::BlendComponent {
+                src_factor: factor,
+                dst_factor: wgpu::BlendFactor::One,
+                operation: wgpu::BlendOperation::Add,
+            },
+            alpha: wgpu::BlendComponent::REPLACE,
+        };
+
+        let create_pipeline = |label, entry_point, blend| {
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some(label),
+                layout: Some(&pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: Some("vertex_main"),
+                    buffers: &[],
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point: Some(entry_point),
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format: Self::FORMAT,
+                        blend: Some(blend),
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                }),
+                primitive: wgpu::PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState::default(),
+                multiview: None,
+                cache: None,
+            })
+        };
+
+        let prefilter_pipeline = create_pipeline(
+            "Bloom Prefilter Render Pipeline",
+            "fragment_prefilter",
+            wgpu::BlendState::REPLACE,
+        );
+        let downsample_pipeline = create_pipeline(
+            "Bloom Downsample Render Pipeline",
+            "fragment_downsample",
+            wgpu::BlendState::REPLACE,
+        );
+        let upsample_pipeline = create_pipeline(
+            "Bloom Upsample Render Pipeline",
+            "fragment_upsample",
+            additive(wgpu::BlendFactor::One),
+        );
+        // The intensity is the blend constant
+        let composite_pipeline = create_pipeline(
+            "Bloom Composite Render Pipeline",
+            "fragment_upsample",
+            additive(wgpu::BlendFactor::Constant),
+        );
+
+        let mips = BloomMips::new(
+            device,
+            config,
+            source,
+            &bind_group_layout,
+            &sampler,
+            &threshold_buffer,
+        );
+
+        Self {
+            threshold,
+            intensity,
+
+            threshold_buffer,
+            sampler,
+            bind_group_layout,
+
+            prefilter_pipeline,
+            downsample_pipeline,
+            upsample_pipeline,
+            composite_pipeline,
+
+            mips,
+        }
+    }
+
+    pub fn threshold(&self) -> f32 {
+        self.threshold
+    }
+
+    pub fn set_threshold(&mut self, queue: &wgpu::Queue, threshold: f32) {
+        self.threshold = threshold;
+        queue.write_buffer(
+            &self.threshold_buffer,
+            0,
+            bytemuck::bytes_of(&Self::threshold_uniform(threshold)),
+        );
+    }
+
+    pub fn intensity(&self) -> f32 {
+        self.intensity
+    }
+
+    /// Sets the intensity, bloom is skipped if it is not positive.
+    pub fn set_intensity(&mut self, intensity: f32) {
+        self.intensity = intensity;
+    }
+
+    /// Recreate the mip chain for the new size and source.
+    pub fn resize(
+        &mut self,
+        device: &wgpu::Device,
+        config: &wgpu::SurfaceConfiguration,
+        source: &wgpu::TextureView,
+    ) {
+        self.mips = BloomMips::new(
+            device,
+            config,
+            source,
+            &self.bind_group_layout,
+            &self.sampler,
+            &self.threshold_buffer,
+        );
+    }
+
+    /// Add the bloom onto `target`, which must be the source view.
+    pub fn render(&self, encoder: &mut wgpu::CommandEncoder, target: &wgpu::TextureView) {
+        if self.intensity <= 0.0 {
+            return;
+        }
+
+        let pass = |encoder: &mut wgpu::CommandEncoder,
+                    view: &wgpu::TextureView,
+                    load: wgpu::LoadOp<wgpu::Color>,
+                    pipeline: &wgpu::RenderPipeline,
+                    bind_group: &wgpu::BindGroup| {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Bloom Render Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load,
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                occlusion_query_set: None,
+                timestamp_writes: None,
+            });
+
+            render_pass.set_pipeline(pipeline);
+            render_pass.set_bind_group(0, bind_group, &[]);
+            render_pass.set_blend_constant(wgpu::Color {
+                r: self.intensity as f64,
+                g: self.intensity as f64,
+                b: self.intensity as f64,
+                a: 1.0,
+            });
+            render_pass.draw(0..3, 0..1);
+        };
+
+        let clear = wgpu::LoadOp::Clear(wgpu::Color::BLACK);
+        let views = &self.mips.views;
+        let bind_groups = &self.mips.bind_groups;
+
+        // Extract the bright pixels and downsample
+        pass(
+            encoder,
+            &views[0],
+            clear,
+            &self.prefilter_pipeline,
+            &self.mips.source_bind_group,
+        );
+        for i in 1..views.len() {
+            pass(
+                encoder,
+                &views[i],
+                clear,
+                &self.downsample_pipeline,
+                &bind_groups[i - 1],
+            );
+        }
+
+        // Upsample and accumulate
+        for i in (1..views.len()).rev() {
+            pass(
+                encoder,
+                &views[i - 1],
+                wgpu::LoadOp::Load,
+                &self.upsample_pipeline,
+                &bind_groups[i],
+            );
+        }
+
+        pass(
+            encoder,
+            target,
+            wgpu::LoadOp::Load,
+            &self.composite_pipeline,
+            &bind_groups[0],
+        );
+    }
+
+    /// The threshold padded to the 16 bytes uniform alignment.
+    fn threshold_uniform(threshold: f32) -> [f32; 4] {
+        [threshold, 0.0, 0.0, 0.0]
+    }
+}
+
+/// The mip chain of [`Bloom`] and the bind groups sampling each level.
+#[derive(Debug)]
+struct BloomMips {
+    views: Vec<wgpu::TextureView>,
+    bind_groups: Vec<wgpu::BindGroup>,
+    source_bind_group: wgpu::BindGroup,
+}
+
+impl BloomMips {
+    fn new(
+        device: &wgpu::Device,
+        config: &wgpu::SurfaceConfiguration,
+        source: &wgpu::TextureView,
+        layout: &wgpu::BindGroupLayout,
+        sampler: &wgpu::Sampler,
+        threshold_buffer: &wgpu::Buffer,
+    ) -> Self {
+        let size = wgpu::Extent3d {
+            width: (config.width / 2).max(1),
+            height: (config.height / 2).max(1),
+            depth_or_array_layers: 1,
+        };
+        let mip_level_count = size
+            .max_mips(wgpu::TextureDimension::D2)
+            .min(Bloom::MAX_MIP_LEVELS);
+
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Bloom Texture"),
+            size,
+            mip_level_count,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: Bloom::FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+
+        let create_bind_group = |view: &wgpu::TextureView| {
+            device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Bloom Bind Group"),
+                layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(view),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::Sampler(sampler),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: threshold_buffer.as_entire_binding(),
+                    },
+                ],
+            })
+        };
+
+        let views = (0..mip_level_count)
+            .map(|mip_level| {
+                texture.create_view(&wgpu::TextureViewDescriptor {
+                    label: Some("Bloom Texture View"),
+                    base_mip_level: mip_level,
+                    mip_level_count: Some(1),
+                    ..Default::default()
+                })
+            })
+            .collect::<Vec<_>>();
+        let bind_groups = views.iter().map(create_bind_group).collect();
+        let source_bind_group = create_bind_group(source);
+
+        Self {
+            views,
+            bind_groups,
+            source_bind_group,
+        }
+    }
+}
@@ -0,0 +1,7 @@
+mod bloom;
+mod downsample;
+mod fxaa;
+
+pub use bloom::Bloom;
+pub use downsample::Downsample;
+pub use fxaa::Fxaa;
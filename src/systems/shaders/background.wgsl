@@ -0,0 +1,28 @@
+struct Gradient {
+    top: vec4<f32>,
+    bottom: vec4<f32>,
+}
+@group(0) @binding(0)
+var<uniform> gradient: Gradient;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+}
+
+// Fullscreen triangle covering the screen with 3 vertices, at the far plane so
+// the scene is drawn in front.
+@vertex
+fn vertex_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+
+    var output: VertexOutput;
+    output.position = vec4<f32>(uv * vec2<f32>(2.0, -2.0) + vec2<f32>(-1.0, 1.0), 1.0, 1.0);
+    output.uv = uv;
+    return output;
+}
+
+@fragment
+fn fragment_main(input: VertexOutput) -> @location(0) vec4<f32> {
+    return vec4<f32>(mix(gradient.top.rgb, gradient.bottom.rgb, saturate(input.uv.y)), 1.0);
+}
@@ -0,0 +1,68 @@
+struct Bloom {
+    threshold: f32,
+}
+@group(0) @binding(0)
+var source_texture: texture_2d<f32>;
+@group(0) @binding(1)
+var source_sampler: sampler;
+@group(0) @binding(2)
+var<uniform> bloom: Bloom;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+}
+
+// Fullscreen triangle covering the screen with 3 vertices.
+@vertex
+fn vertex_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+
+    var output: VertexOutput;
+    output.position = vec4<f32>(uv * vec2<f32>(2.0, -2.0) + vec2<f32>(-1.0, 1.0), 0.0, 1.0);
+    output.uv = uv;
+    return output;
+}
+
+// 4 bilinear taps covering a 4x4 texel box.
+fn box_sample(uv: vec2<f32>) -> vec3<f32> {
+    let texel = 1.0 / vec2<f32>(textureDimensions(source_texture));
+    let offset = texel * vec2<f32>(1.0, -1.0);
+    return (
+        textureSample(source_texture, source_sampler, uv + offset.xx).rgb
+        + textureSample(source_texture, source_sampler, uv + offset.xy).rgb
+        + textureSample(source_texture, source_sampler, uv + offset.yx).rgb
+        + textureSample(source_texture, source_sampler, uv + offset.yy).rgb
+    ) * 0.25;
+}
+
+// Keep only the color above the threshold, with a soft knee.
+@fragment
+fn fragment_prefilter(input: VertexOutput) -> @location(0) vec4<f32> {
+    let color = box_sample(input.uv);
+    let brightness = max(color.r, max(color.g, color.b));
+    let knee = bloom.threshold * 0.5;
+    let soft = clamp(brightness - bloom.threshold + knee, 0.0, 2.0 * knee);
+    let contribution = max(soft * soft / (4.0 * knee + 0.0001), brightness - bloom.threshold);
+    return vec4<f32>(color * contribution / max(brightness, 0.0001), 1.0);
+}
+
+@fragment
+fn fragment_downsample(input: VertexOutput) -> @location(0) vec4<f32> {
+    return vec4<f32>(box_sample(input.uv), 1.0);
+}
+
+// 3x3 tent filter, blended additively onto the larger mip.
+@fragment
+fn fragment_upsample(input: VertexOutput) -> @location(0) vec4<f32> {
+    let texel = 1.0 / vec2<f32>(textureDimensions(source_texture));
+    var color = vec3<f32>(0.0);
+    for (var x = -1; x <= 1; x++) {
+        for (var y = -1; y <= 1; y++) {
+            let weight = f32((2 - abs(x)) * (2 - abs(y))) / 16.0;
+            let offset = vec2<f32>(f32(x), f32(y)) * texel;
+            color += textureSample(source_texture, source_sampler, input.uv + offset).rgb * weight;
+        }
+    }
+    return vec4<f32>(color, 1.0);
+}
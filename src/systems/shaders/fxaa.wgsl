@@ -0,0 +1,72 @@
+@group(0) @binding(0)
+var ldr_texture: texture_2d<f32>;
+@group(0) @binding(1)
+var ldr_sampler: sampler;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+}
+
+// Fullscreen triangle covering the screen with 3 vertices.
+@vertex
+fn vertex_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+
+    var output: VertexOutput;
+    output.position = vec4<f32>(uv * vec2<f32>(2.0, -2.0) + vec2<f32>(-1.0, 1.0), 0.0, 1.0);
+    output.uv = uv;
+    return output;
+}
+
+const REDUCE_MIN: f32 = 1.0 / 128.0;
+const REDUCE_MUL: f32 = 1.0 / 8.0;
+const SPAN_MAX: f32 = 8.0;
+
+// Perceptual luminance, the sampled color is linear so it is gamma encoded
+// back with a square root.
+fn luma(color: vec3<f32>) -> f32 {
+    return dot(sqrt(color), vec3<f32>(0.299, 0.587, 0.114));
+}
+
+fn sample(uv: vec2<f32>) -> vec3<f32> {
+    return textureSample(ldr_texture, ldr_sampler, uv).rgb;
+}
+
+// FXAA by Timothy Lottes, blurring along the edge found from the luminance of
+// the diagonal neighbours.
+@fragment
+fn fragment_main(input: VertexOutput) -> @location(0) vec4<f32> {
+    let texel = 1.0 / vec2<f32>(textureDimensions(ldr_texture));
+
+    let luma_nw = luma(sample(input.uv + vec2<f32>(-1.0, -1.0) * texel));
+    let luma_ne = luma(sample(input.uv + vec2<f32>(1.0, -1.0) * texel));
+    let luma_sw = luma(sample(input.uv + vec2<f32>(-1.0, 1.0) * texel));
+    let luma_se = luma(sample(input.uv + vec2<f32>(1.0, 1.0) * texel));
+    let luma_m = luma(sample(input.uv));
+
+    let luma_min = min(luma_m, min(min(luma_nw, luma_ne), min(luma_sw, luma_se)));
+    let luma_max = max(luma_m, max(max(luma_nw, luma_ne), max(luma_sw, luma_se)));
+
+    var direction = vec2<f32>(
+        -((luma_nw + luma_ne) - (luma_sw + luma_se)),
+        (luma_nw + luma_sw) - (luma_ne + luma_se),
+    );
+    let direction_reduce = max((luma_nw + luma_ne + luma_sw + luma_se) * 0.25 * REDUCE_MUL, REDUCE_MIN);
+    let direction_scale = 1.0 / (min(abs(direction.x), abs(direction.y)) + direction_reduce);
+    direction = clamp(direction * direction_scale, vec2<f32>(-SPAN_MAX), vec2<f32>(SPAN_MAX)) * texel;
+
+    let color_a = 0.5 * (
+        sample(input.uv + direction * (1.0 / 3.0 - 0.5))
+        + sample(input.uv + direction * (2.0 / 3.0 - 0.5))
+    );
+    let color_b = color_a * 0.5 + 0.25 * (
+        sample(input.uv + direction * -0.5)
+        + sample(input.uv + direction * 0.5)
+    );
+
+    // Fall back to the narrower blur if the wider one samples past the edge
+    let luma_b = luma(color_b);
+    let is_outside = luma_b < luma_min || luma_b > luma_max;
+    return vec4<f32>(select(color_b, color_a, is_outside), 1.0);
+}
@@ -0,0 +1,40 @@
+struct Camera {
+    view: mat4x4<f32>,
+    projection: mat4x4<f32>,
+    view_projection: mat4x4<f32>,
+    inverse_view_projection: mat4x4<f32>,
+    position: vec4<f32>,
+}
+@group(0) @binding(0)
+var<uniform> camera: Camera;
+
+struct Gizmo {
+    transform: mat4x4<f32>,
+    colors: array<vec4<f32>, 3>,
+    length: f32,
+}
+@group(1) @binding(0)
+var<uniform> gizmo: Gizmo;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+}
+
+// Two vertices for each of the X, Y, and Z axes, from the origin to the length
+@vertex
+fn vertex_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    let axis = index / 2u;
+    var position = vec3<f32>(0.0);
+    position[axis] = f32(index & 1u) * gizmo.length;
+
+    var output: VertexOutput;
+    output.position = camera.view_projection * gizmo.transform * vec4<f32>(position, 1.0);
+    output.color = gizmo.colors[axis];
+    return output;
+}
+
+@fragment
+fn fragment_main(input: VertexOutput) -> @location(0) vec4<f32> {
+    return input.color;
+}
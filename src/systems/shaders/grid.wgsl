@@ -0,0 +1,75 @@
+struct Camera {
+    view: mat4x4<f32>,
+    projection: mat4x4<f32>,
+    view_projection: mat4x4<f32>,
+    inverse_view_projection: mat4x4<f32>,
+    position: vec4<f32>,
+}
+@group(0) @binding(0)
+var<uniform> camera: Camera;
+
+struct Grid {
+    color: vec4<f32>,
+    spacing: f32,
+    fade_distance: f32,
+}
+@group(1) @binding(0)
+var<uniform> grid: Grid;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) ndc: vec2<f32>,
+}
+
+struct FragmentOutput {
+    @location(0) color: vec4<f32>,
+    @builtin(frag_depth) depth: f32,
+}
+
+// Fullscreen triangle
+@vertex
+fn vertex_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    let ndc = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u)) * 2.0 - 1.0;
+
+    var output: VertexOutput;
+    output.position = vec4<f32>(ndc, 0.0, 1.0);
+    output.ndc = ndc;
+    return output;
+}
+
+fn unproject(ndc: vec3<f32>) -> vec3<f32> {
+    let position = camera.inverse_view_projection * vec4<f32>(ndc, 1.0);
+    return position.xyz / position.w;
+}
+
+@fragment
+fn fragment_main(input: VertexOutput) -> FragmentOutput {
+    // Intersect the view ray with the XZ plane
+    let near = unproject(vec3<f32>(input.ndc, 0.0));
+    let far = unproject(vec3<f32>(input.ndc, 1.0));
+    let t = -near.y / (far.y - near.y);
+    let world_position = near + t * (far - near);
+
+    // Anti-aliased lines from the screen space derivative
+    let coord = world_position.xz / grid.spacing;
+    let derivative = fwidth(coord);
+    let line = abs(fract(coord - 0.5) - 0.5) / derivative;
+    var alpha = 1.0 - min(min(line.x, line.y), 1.0);
+
+    // Fade with distance and where lines are denser than pixels to avoid moiré
+    let distance = length(world_position.xz - camera.position.xz);
+    alpha *= 1.0 - smoothstep(0.0, grid.fade_distance, distance);
+    alpha *= 1.0 - smoothstep(0.25, 0.5, max(derivative.x, derivative.y));
+
+    if t <= 0.0 || t >= 1.0 || alpha <= 0.0 {
+        discard;
+    }
+
+    let clip = camera.view_projection * vec4<f32>(world_position, 1.0);
+
+    var output: FragmentOutput;
+    output.color = vec4<f32>(grid.color.rgb, grid.color.a * alpha);
+    // Pulled slightly towards the camera so it is not hidden by the ground
+    output.depth = clip.z / clip.w * (1.0 - 1e-6);
+    return output;
+}
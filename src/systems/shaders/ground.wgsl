@@ -0,0 +1,84 @@
+struct Camera {
+    view: mat4x4<f32>,
+    projection: mat4x4<f32>,
+    view_projection: mat4x4<f32>,
+    inverse_view_projection: mat4x4<f32>,
+    position: vec4<f32>,
+}
+@group(0) @binding(0)
+var<uniform> camera: Camera;
+
+struct Ground {
+    color: vec4<f32>,
+    size: f32,
+    height: f32,
+}
+@group(1) @binding(0)
+var<uniform> ground: Ground;
+
+struct Light {
+    view_projection: mat4x4<f32>,
+    direction: vec4<f32>,
+    color: vec4<f32>,
+    ambient: f32,
+}
+@group(2) @binding(0)
+var<uniform> light: Light;
+@group(2) @binding(1)
+var shadow_map: texture_depth_2d;
+@group(2) @binding(2)
+var shadow_sampler: sampler_comparison;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) world_position: vec3<f32>,
+}
+
+// Two triangles of a quad centered at the origin, facing up
+@vertex
+fn vertex_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    var corners = array<vec2<f32>, 6>(
+        vec2<f32>(-0.5, -0.5),
+        vec2<f32>(-0.5, 0.5),
+        vec2<f32>(0.5, 0.5),
+        vec2<f32>(-0.5, -0.5),
+        vec2<f32>(0.5, 0.5),
+        vec2<f32>(0.5, -0.5),
+    );
+    let corner = corners[index] * ground.size;
+    let world_position = vec3<f32>(corner.x, ground.height, corner.y);
+
+    var output: VertexOutput;
+    output.position = camera.view_projection * vec4<f32>(world_position, 1.0);
+    output.world_position = world_position;
+    return output;
+}
+
+// 3x3 PCF of the shadow map, 1 is fully lit
+fn shadow(world_position: vec3<f32>) -> f32 {
+    let light_position = light.view_projection * vec4<f32>(world_position, 1.0);
+    let ndc = light_position.xyz / light_position.w;
+    let uv = ndc.xy * vec2<f32>(0.5, -0.5) + 0.5;
+
+    if any(uv < vec2<f32>(0.0)) || any(uv > vec2<f32>(1.0)) || ndc.z > 1.0 {
+        return 1.0;
+    }
+
+    let texel = 1.0 / vec2<f32>(textureDimensions(shadow_map));
+    var visibility = 0.0;
+    for (var x = -1; x <= 1; x++) {
+        for (var y = -1; y <= 1; y++) {
+            let offset = vec2<f32>(f32(x), f32(y)) * texel;
+            visibility += textureSampleCompareLevel(shadow_map, shadow_sampler, uv + offset, ndc.z);
+        }
+    }
+    return visibility / 9.0;
+}
+
+@fragment
+fn fragment_main(input: VertexOutput) -> @location(0) vec4<f32> {
+    let diffuse = max(dot(vec3<f32>(0.0, 1.0, 0.0), -light.direction.xyz), 0.0) * shadow(input.world_position);
+    let lighting = light.color.rgb * (light.ambient + diffuse * (1.0 - light.ambient));
+
+    return vec4<f32>(ground.color.rgb * lighting, 1.0);
+}
@@ -1,5 +1,9 @@
 struct Camera {
+    view: mat4x4<f32>,
+    projection: mat4x4<f32>,
     view_projection: mat4x4<f32>,
+    inverse_view_projection: mat4x4<f32>,
+    position: vec4<f32>,
 }
 @group(0) @binding(0)
 var<uniform> camera: Camera;
@@ -10,25 +14,141 @@ struct PyramidTransform {
 @group(1) @binding(0)
 var<uniform> pyramid_transform: PyramidTransform;
 
+@group(1) @binding(1)
+var pyramid_texture: texture_2d<f32>;
+@group(1) @binding(2)
+var pyramid_sampler: sampler;
+
+struct PyramidMaterial {
+    base_color: vec3<f32>,
+    opacity: f32,
+    emissive: vec3<f32>,
+    metallic: f32,
+    roughness: f32,
+}
+@group(1) @binding(3)
+var<uniform> pyramid_material: PyramidMaterial;
+
+struct Light {
+    view_projection: mat4x4<f32>,
+    direction: vec4<f32>,
+    color: vec4<f32>,
+    ambient: f32,
+}
+@group(2) @binding(0)
+var<uniform> light: Light;
+@group(2) @binding(1)
+var shadow_map: texture_depth_2d;
+@group(2) @binding(2)
+var shadow_sampler: sampler_comparison;
+
+struct PointLight {
+    position: vec3<f32>,
+    range: f32,
+    color: vec4<f32>,
+}
+// `PointLights` and `point_lights` at group 3 are appended by the engine, as
+// a storage or uniform buffer depending on the backend
+
 struct VertexInput {
     @location(0) position: vec3<f32>,
     @location(1) color: vec3<f32>,
+    @location(2) uv: vec2<f32>,
+    @location(3) normal: vec3<f32>,
+};
+
+struct InstanceInput {
+    @location(4) model_0: vec4<f32>,
+    @location(5) model_1: vec4<f32>,
+    @location(6) model_2: vec4<f32>,
+    @location(7) model_3: vec4<f32>,
+    @location(8) color: vec4<f32>,
 };
 
 struct VertexOutput {
     @builtin(position) position: vec4<f32>,
     @location(0) color: vec3<f32>,
+    @location(1) uv: vec2<f32>,
+    @location(2) normal: vec3<f32>,
+    @location(3) world_position: vec3<f32>,
 }
 
 @vertex
-fn vertex_main(input: VertexInput) -> VertexOutput {
+fn vertex_main(input: VertexInput, instance: InstanceInput) -> VertexOutput {
+    let model = pyramid_transform.transform * mat4x4<f32>(instance.model_0, instance.model_1, instance.model_2, instance.model_3);
+    let world_position = model * vec4<f32>(input.position, 1.0);
+
     var output: VertexOutput;
-    output.position = camera.view_projection * pyramid_transform.transform * vec4<f32>(input.position, 1.0);
-    output.color = input.color;
+    output.position = camera.view_projection * world_position;
+    output.color = input.color * instance.color.rgb;
+    output.uv = input.uv;
+    output.normal = (model * vec4<f32>(input.normal, 0.0)).xyz;
+    output.world_position = world_position.xyz;
     return output;
 }
 
+// 3x3 PCF of the shadow map, 1 is fully lit
+fn shadow(world_position: vec3<f32>) -> f32 {
+    let light_position = light.view_projection * vec4<f32>(world_position, 1.0);
+    let ndc = light_position.xyz / light_position.w;
+    let uv = ndc.xy * vec2<f32>(0.5, -0.5) + 0.5;
+
+    if any(uv < vec2<f32>(0.0)) || any(uv > vec2<f32>(1.0)) || ndc.z > 1.0 {
+        return 1.0;
+    }
+
+    let texel = 1.0 / vec2<f32>(textureDimensions(shadow_map));
+    var visibility = 0.0;
+    for (var x = -1; x <= 1; x++) {
+        for (var y = -1; y <= 1; y++) {
+            let offset = vec2<f32>(f32(x), f32(y)) * texel;
+            visibility += textureSampleCompareLevel(shadow_map, shadow_sampler, uv + offset, ndc.z);
+        }
+    }
+    return visibility / 9.0;
+}
+
 @fragment
-fn fragment_main(input: VertexOutput) -> @location(0) vec4<f32> {
-    return vec4<f32>(input.color, 1.0);
+fn fragment_main(input: VertexOutput, @builtin(front_facing) is_front_facing: bool) -> @location(0) vec4<f32> {
+    let texture_color = textureSample(pyramid_texture, pyramid_sampler, input.uv).rgb;
+    let albedo = input.color * texture_color * pyramid_material.base_color;
+
+    // Back faces are only drawn without culling, and are lit from the inside
+    let normal = select(-1.0, 1.0, is_front_facing) * normalize(input.normal);
+    let view_direction = normalize(camera.position.xyz - input.world_position);
+
+    // Metals have no diffuse and tint their highlights, until there is a PBR
+    // path the roughness only controls the Blinn-Phong exponent
+    let diffuse_color = albedo * (1.0 - pyramid_material.metallic);
+    let specular_color = mix(vec3<f32>(0.04), albedo, pyramid_material.metallic);
+    let shininess = exp2(10.0 * (1.0 - pyramid_material.roughness) + 1.0);
+
+    // Lambert diffuse with a constant ambient term
+    let light_direction = -light.direction.xyz;
+    let visibility = shadow(input.world_position);
+    let diffuse = max(dot(normal, light_direction), 0.0) * visibility;
+    let specular = specular_term(normal, light_direction, view_direction, shininess) * visibility;
+    var color = albedo * light.color.rgb * light.ambient
+        + light.color.rgb * (diffuse_color * diffuse * (1.0 - light.ambient) + specular_color * specular);
+
+    // Point lights with quadratic falloff to zero at the range
+    for (var i = 0u; i < point_lights.count; i++) {
+        let point_light = point_lights.lights[i];
+        let offset = point_light.position - input.world_position;
+        let distance = length(offset);
+        let direction = offset / max(distance, 0.0001);
+        let attenuation = pow(clamp(1.0 - distance / point_light.range, 0.0, 1.0), 2.0);
+        let point_diffuse = max(dot(normal, direction), 0.0);
+        let point_specular = specular_term(normal, direction, view_direction, shininess);
+        color += point_light.color.rgb * attenuation * (diffuse_color * point_diffuse + specular_color * point_specular);
+    }
+
+    return vec4<f32>(color + pyramid_material.emissive, pyramid_material.opacity);
+}
+
+// Blinn-Phong highlight, none when the light is behind the surface
+fn specular_term(normal: vec3<f32>, light_direction: vec3<f32>, view_direction: vec3<f32>, shininess: f32) -> f32 {
+    let half_direction = normalize(light_direction + view_direction);
+    let is_lit = select(0.0, 1.0, dot(normal, light_direction) > 0.0);
+    return is_lit * pow(max(dot(normal, half_direction), 0.0), shininess);
 }
\ No newline at end of file
@@ -0,0 +1,37 @@
+struct Params {
+    time: f32,
+    amplitude: f32,
+    frequency: f32,
+    speed: f32,
+    vertex_count: u32,
+    vertex_stride: u32,
+}
+@group(0) @binding(0)
+var<uniform> params: Params;
+
+// The vertices are read as floats, since the tightly packed vec3 of the vertex
+// buffer do not match the alignment of WGSL structs
+@group(0) @binding(1)
+var<storage, read> rest_vertices: array<f32>;
+@group(0) @binding(2)
+var<storage, read_write> deformed_vertices: array<f32>;
+
+@compute @workgroup_size(64)
+fn compute_main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let index = id.x;
+    if index >= params.vertex_count {
+        return;
+    }
+
+    let base = index * params.vertex_stride;
+    for (var i = 0u; i < params.vertex_stride; i++) {
+        deformed_vertices[base + i] = rest_vertices[base + i];
+    }
+
+    // Sway around the vertical axis, more the higher the vertex is, so the
+    // base stays on the ground
+    let y = rest_vertices[base + 1u];
+    let phase = params.time * params.speed + y * params.frequency;
+    deformed_vertices[base] += params.amplitude * y * sin(phase);
+    deformed_vertices[base + 2u] += params.amplitude * y * cos(phase);
+}
@@ -0,0 +1,53 @@
+struct Camera {
+    view: mat4x4<f32>,
+    projection: mat4x4<f32>,
+    view_projection: mat4x4<f32>,
+    inverse_view_projection: mat4x4<f32>,
+    position: vec4<f32>,
+}
+@group(0) @binding(0)
+var<uniform> camera: Camera;
+
+struct PyramidTransform {
+    transform: mat4x4<f32>,
+    // The pick ID of the first instance of the object
+    id_offset: u32,
+}
+@group(1) @binding(0)
+var<uniform> pyramid_transform: PyramidTransform;
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+};
+
+struct InstanceInput {
+    @location(4) model_0: vec4<f32>,
+    @location(5) model_1: vec4<f32>,
+    @location(6) model_2: vec4<f32>,
+    @location(7) model_3: vec4<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) @interpolate(flat) id: u32,
+}
+
+@vertex
+fn vertex_main(
+    input: VertexInput,
+    instance: InstanceInput,
+    @builtin(instance_index) instance_index: u32,
+) -> VertexOutput {
+    let model = pyramid_transform.transform * mat4x4<f32>(instance.model_0, instance.model_1, instance.model_2, instance.model_3);
+
+    var output: VertexOutput;
+    output.position = camera.view_projection * model * vec4<f32>(input.position, 1.0);
+    // 0 is reserved for nothing picked
+    output.id = pyramid_transform.id_offset + instance_index + 1u;
+    return output;
+}
+
+@fragment
+fn fragment_main(input: VertexOutput) -> @location(0) u32 {
+    return input.id;
+}
@@ -0,0 +1,95 @@
+struct Camera {
+    view: mat4x4<f32>,
+    projection: mat4x4<f32>,
+    view_projection: mat4x4<f32>,
+    inverse_view_projection: mat4x4<f32>,
+    position: vec4<f32>,
+}
+@group(0) @binding(0)
+var<uniform> camera: Camera;
+
+struct PyramidTransform {
+    transform: mat4x4<f32>,
+}
+@group(1) @binding(0)
+var<uniform> pyramid_transform: PyramidTransform;
+
+struct Wireframe {
+    color: vec4<f32>,
+    thickness: f32,
+    wireframe_overlay: u32,
+    side_count: u32,
+    height: f32,
+    base_radius: f32,
+}
+@group(2) @binding(0)
+var<uniform> wireframe: Wireframe;
+
+struct InstanceInput {
+    @location(4) model_0: vec4<f32>,
+    @location(5) model_1: vec4<f32>,
+    @location(6) model_2: vec4<f32>,
+    @location(7) model_3: vec4<f32>,
+    @location(8) color: vec4<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) barycentric: vec3<f32>,
+}
+
+const TAU: f32 = 6.28318530718;
+
+fn base_position(i: u32) -> vec3<f32> {
+    let angle = f32(i) / f32(wireframe.side_count) * TAU;
+    return vec3<f32>(wireframe.base_radius * cos(angle), 0.0, wireframe.base_radius * sin(angle));
+}
+
+// The vertices are unshared so each corner gets its own barycentric coordinate,
+// in the same winding as the indices of the pyramid
+@vertex
+fn vertex_main(@builtin(vertex_index) index: u32, instance: InstanceInput) -> VertexOutput {
+    let side = index / 3u;
+
+    var position: vec3<f32>;
+    var barycentric: vec3<f32>;
+    switch index % 3u {
+        case 0u: {
+            position = vec3<f32>(0.0, wireframe.height, 0.0);
+            barycentric = vec3<f32>(1.0, 0.0, 0.0);
+        }
+        case 1u: {
+            position = base_position((side + 1u) % wireframe.side_count);
+            barycentric = vec3<f32>(0.0, 1.0, 0.0);
+        }
+        default: {
+            position = base_position(side);
+            barycentric = vec3<f32>(0.0, 0.0, 1.0);
+        }
+    }
+
+    let model = pyramid_transform.transform * mat4x4<f32>(instance.model_0, instance.model_1, instance.model_2, instance.model_3);
+
+    var output: VertexOutput;
+    output.position = camera.view_projection * model * vec4<f32>(position, 1.0);
+    output.barycentric = barycentric;
+    return output;
+}
+
+@fragment
+fn fragment_main(input: VertexOutput) -> @location(0) vec4<f32> {
+    if wireframe.wireframe_overlay == 0u {
+        discard;
+    }
+
+    // Screen space distance to the closest edge, antialiased over a pixel
+    let width = fwidth(input.barycentric);
+    let edge = smoothstep(width * (wireframe.thickness - 0.5), width * (wireframe.thickness + 0.5), input.barycentric);
+    let coverage = 1.0 - min(min(edge.x, edge.y), edge.z);
+
+    if coverage <= 0.0 {
+        discard;
+    }
+
+    return vec4<f32>(wireframe.color.rgb, wireframe.color.a * coverage);
+}
@@ -0,0 +1,39 @@
+struct Camera {
+    view: mat4x4<f32>,
+    projection: mat4x4<f32>,
+    view_projection: mat4x4<f32>,
+    inverse_view_projection: mat4x4<f32>,
+    position: vec4<f32>,
+}
+@group(0) @binding(0)
+var<uniform> camera: Camera;
+
+@group(1) @binding(0)
+var skybox_texture: texture_cube<f32>;
+@group(1) @binding(1)
+var skybox_sampler: sampler;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) ndc: vec2<f32>,
+}
+
+// Fullscreen triangle on the far plane
+@vertex
+fn vertex_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    let ndc = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u)) * 2.0 - 1.0;
+
+    var output: VertexOutput;
+    output.position = vec4<f32>(ndc, 1.0, 1.0);
+    output.ndc = ndc;
+    return output;
+}
+
+@fragment
+fn fragment_main(input: VertexOutput) -> @location(0) vec4<f32> {
+    // Only the direction from the camera matters, so the translation cancels out
+    let far = camera.inverse_view_projection * vec4<f32>(input.ndc, 1.0, 1.0);
+    let direction = far.xyz / far.w - camera.position.xyz;
+
+    return vec4<f32>(textureSample(skybox_texture, skybox_sampler, direction).rgb, 1.0);
+}
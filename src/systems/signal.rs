@@ -1,6 +1,14 @@
+use glam::*;
+
 use crate::{
-    engine::{self, signal::QueueBehavior},
-    systems::{handlers::PyramidModel, Pipeline},
+    engine::{self, signal::QueueBehavior, SceneId},
+    systems::{
+        handlers::{
+            CameraModel, CapturedFrame, ComputeParams, LightModel, PickedObject, PointLight,
+            PyramidInstance, PyramidModel, PyramidWireframe,
+        },
+        ActionMap, Pipeline, RgbColor,
+    },
 };
 
 use super::handlers::PyramidTransform;
@@ -61,19 +69,186 @@ macro_rules! signals {
 }
 
 signals! {
+    // The size in physical pixels, e.g. from `engine::utils::observe_resize`
     #[queue = QueueBehavior::Replace(|a, _| a.is_resize())]
     Resize {
         width: f64,
         height: f64,
     }
 
+    #[queue = QueueBehavior::Replace(|a, _| a.is_fps_limit_update())]
+    FpsLimitUpdate {
+        fps_limit: Option<u32>,
+    }
+
+    #[queue = QueueBehavior::Replace(|a, _| a.is_paused_update())]
+    PausedUpdate {
+        paused: bool,
+    }
+
+    #[queue = QueueBehavior::Replace(|a, _| a.is_time_scale_update())]
+    TimeScaleUpdate {
+        time_scale: f32,
+    }
+
+    #[queue = QueueBehavior::Replace(|a, _| a.is_present_mode_update())]
+    PresentModeUpdate {
+        present_mode: wgpu::PresentMode,
+    }
+
+    #[queue = QueueBehavior::Replace(|a, _| a.is_clear_color_update())]
+    ClearColorUpdate {
+        clear_color: RgbColor,
+    }
+
+    #[queue = QueueBehavior::Replace(|a, _| a.is_exposure_update())]
+    ExposureUpdate {
+        exposure: f32,
+    }
+
+    #[queue = QueueBehavior::Replace(|a, _| a.is_bloom_update())]
+    BloomUpdate {
+        threshold: f32,
+        intensity: f32,
+    }
+
+    #[queue = QueueBehavior::Replace(|a, _| a.is_fxaa_update())]
+    FxaaUpdate {
+        fxaa: bool,
+    }
+
+    #[queue = QueueBehavior::Replace(|a, _| a.is_trails_update())]
+    TrailsUpdate {
+        trails: bool,
+    }
+
+    #[queue = QueueBehavior::Replace(|a, _| a.is_render_scale_update())]
+    RenderScaleUpdate {
+        render_scale: f32,
+    }
+
+    #[queue = QueueBehavior::Replace(|a, _| a.is_switch_scene())]
+    SwitchScene {
+        scene: SceneId,
+    }
+
+    #[queue = QueueBehavior::Queued]
+    CaptureFrame {}
+
+    #[queue = QueueBehavior::Ignored]
+    FrameCaptured {
+        frame: CapturedFrame,
+    }
+
+    #[queue = QueueBehavior::Ignored]
+    InitFailed {
+        message: String,
+    }
+
+    #[queue = QueueBehavior::Ignored]
+    Picked {
+        object: Option<PickedObject>,
+    }
+
+    #[queue = QueueBehavior::Ignored]
+    Stats {
+        average_fps: f32,
+        frame_time_p99: f32,
+        frame_times: Vec<f32>,
+        visible_instances: usize,
+    }
+
+    // The state to save before the window closes
+    #[queue = QueueBehavior::Ignored]
+    CloseRequested {
+        camera: CameraModel,
+    }
+
+    #[queue = QueueBehavior::Ignored]
+    LoadProgress {
+        progress: f32,
+    }
+
+    #[queue = QueueBehavior::Queued]
+    CancelLoad {}
+
+    #[queue = QueueBehavior::Replace(|a, _| a.is_camera_update())]
+    CameraUpdate {
+        model: CameraModel,
+    }
+
+    #[queue = QueueBehavior::Ignored]
+    CameraMoved {
+        model: CameraModel,
+    }
+
+    #[queue = QueueBehavior::Replace(|a, _| a.is_light_update())]
+    LightUpdate {
+        model: LightModel,
+    }
+
+    #[queue = QueueBehavior::Replace(|a, _| a.is_point_lights_update())]
+    PointLightsUpdate {
+        lights: Vec<PointLight>,
+    }
+
     #[queue = QueueBehavior::Ignored]
     PyramidTransformUpdate {
         transform: PyramidTransform,
     }
 
+    #[queue = QueueBehavior::Replace(|a, _| a.is_pyramid_auto_rotation_update())]
+    PyramidAutoRotationUpdate {
+        axis: Vec3,
+        speed: f32,
+    }
+
     #[queue = QueueBehavior::Ignored]
     PyramidModelUpdate {
         model: PyramidModel,
     }
+
+    #[queue = QueueBehavior::Replace(|a, _| a.is_pyramid_instances_update())]
+    PyramidInstancesUpdate {
+        instances: Vec<PyramidInstance>,
+    }
+
+    #[queue = QueueBehavior::Replace(|a, _| a.is_pyramid_polygon_mode_update())]
+    PyramidPolygonModeUpdate {
+        polygon_mode: wgpu::PolygonMode,
+    }
+
+    #[queue = QueueBehavior::Replace(|a, _| a.is_pyramid_opacity_update())]
+    PyramidOpacityUpdate {
+        opacity: f32,
+    }
+
+    #[queue = QueueBehavior::Replace(|a, _| a.is_pyramid_wireframe_update())]
+    PyramidWireframeUpdate {
+        wireframe: PyramidWireframe,
+    }
+
+    #[queue = QueueBehavior::Replace(|a, _| a.is_compute_update())]
+    ComputeUpdate {
+        is_enabled: bool,
+        params: ComputeParams,
+    }
+
+    #[queue = QueueBehavior::Replace(|a, _| a.is_handlers_enabled_update())]
+    HandlersEnabledUpdate {
+        camera: bool,
+        pyramid: bool,
+        cursor_lock: bool,
+    }
+
+    #[queue = QueueBehavior::Replace(|a, _| a.is_gizmo_update())]
+    GizmoUpdate {
+        is_enabled: bool,
+        is_on_top: bool,
+    }
+
+    #[queue = QueueBehavior::Replace(|a, _| a.is_action_map_update())]
+    ActionMapUpdate {
+        actions: ActionMap,
+    }
 }
@@ -14,6 +14,11 @@ macro_rules! rgb_basic_colors {
 
 #[repr(C)]
 #[derive(Debug, Clone, Copy, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "Vec3", into = "Vec3")
+)]
 pub struct RgbColor(Vec3);
 
 impl RgbColor {
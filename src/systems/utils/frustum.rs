@@ -0,0 +1,120 @@
+use glam::*;
+
+/// A view frustum made of six planes.
+///
+/// Each plane is a normalized plane equation `ax + by + cz + d = 0` stored as
+/// `(a, b, c, d)`, with the normal pointing into the frustum.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Frustum {
+    pub planes: [Vec4; 6],
+}
+
+impl Frustum {
+    pub const LEFT: usize = 0;
+    pub const RIGHT: usize = 1;
+    pub const TOP: usize = 2;
+    pub const BOTTOM: usize = 3;
+    pub const NEAR: usize = 4;
+    pub const FAR: usize = 5;
+
+    /// Extract the planes from a view projection matrix using the
+    /// Gribb-Hartmann method.
+    ///
+    /// This assumes the depth range of wgpu, which is `0.0..=1.0`.
+    pub fn from_view_projection(view_projection: Mat4) -> Self {
+        let rows = [0, 1, 2, 3].map(|i| view_projection.row(i));
+
+        let planes = [
+            rows[3] + rows[0],
+            rows[3] - rows[0],
+            rows[3] - rows[1],
+            rows[3] + rows[1],
+            rows[2],
+            rows[3] - rows[2],
+        ]
+        .map(|plane| plane / plane.truncate().length());
+
+        Self { planes }
+    }
+
+    /// Whether the sphere is inside or intersecting the frustum.
+    pub fn contains_sphere(&self, center: Vec3, radius: f32) -> bool {
+        self.planes
+            .iter()
+            .all(|plane| plane.truncate().dot(center) + plane.w >= -radius)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn view() -> Mat4 {
+        Mat4::look_at_rh(vec3(0.0, 0.0, 5.0), Vec3::ZERO, Vec3::Y)
+    }
+
+    fn assert_planes(frustum: Frustum, expected: [Vec4; 6]) {
+        for (i, (plane, expected)) in frustum.planes.iter().zip(expected).enumerate() {
+            assert!(
+                plane.abs_diff_eq(expected, 1e-5),
+                "plane {i} is {plane}, expected {expected}"
+            );
+        }
+    }
+
+    #[test]
+    fn perspective_planes() {
+        let frustum = Frustum::from_view_projection(
+            Mat4::perspective_rh(90f32.to_radians(), 1.0, 1.0, 100.0) * view(),
+        );
+
+        let s = std::f32::consts::FRAC_1_SQRT_2;
+        let mut expected = [Vec4::ZERO; 6];
+        expected[Frustum::LEFT] = vec4(s, 0.0, -s, 5.0 * s);
+        expected[Frustum::RIGHT] = vec4(-s, 0.0, -s, 5.0 * s);
+        expected[Frustum::TOP] = vec4(0.0, -s, -s, 5.0 * s);
+        expected[Frustum::BOTTOM] = vec4(0.0, s, -s, 5.0 * s);
+        expected[Frustum::NEAR] = vec4(0.0, 0.0, -1.0, 4.0);
+        expected[Frustum::FAR] = vec4(0.0, 0.0, 1.0, 95.0);
+
+        assert_planes(frustum, expected);
+    }
+
+    #[test]
+    fn orthographic_planes() {
+        let frustum = Frustum::from_view_projection(
+            Mat4::orthographic_rh(-2.0, 2.0, -1.0, 1.0, 0.5, 10.0) * view(),
+        );
+
+        let mut expected = [Vec4::ZERO; 6];
+        expected[Frustum::LEFT] = vec4(1.0, 0.0, 0.0, 2.0);
+        expected[Frustum::RIGHT] = vec4(-1.0, 0.0, 0.0, 2.0);
+        expected[Frustum::TOP] = vec4(0.0, -1.0, 0.0, 1.0);
+        expected[Frustum::BOTTOM] = vec4(0.0, 1.0, 0.0, 1.0);
+        expected[Frustum::NEAR] = vec4(0.0, 0.0, -1.0, 4.5);
+        expected[Frustum::FAR] = vec4(0.0, 0.0, 1.0, 5.0);
+
+        assert_planes(frustum, expected);
+    }
+
+    #[test]
+    fn contains_sphere() {
+        let frustum = Frustum::from_view_projection(
+            Mat4::perspective_rh(90f32.to_radians(), 1.0, 1.0, 100.0) * view(),
+        );
+
+        // Inside
+        assert!(frustum.contains_sphere(Vec3::ZERO, 0.5));
+
+        // Outside, behind the camera and to the right
+        assert!(!frustum.contains_sphere(vec3(0.0, 0.0, 10.0), 0.5));
+        assert!(!frustum.contains_sphere(vec3(20.0, 0.0, 0.0), 1.0));
+
+        // Straddling the near and the left plane
+        assert!(frustum.contains_sphere(vec3(0.0, 0.0, 4.2), 0.5));
+        assert!(frustum.contains_sphere(vec3(-5.2, 0.0, 0.0), 0.5));
+
+        // Just outside the left plane
+        assert!(!frustum.contains_sphere(vec3(-6.0, 0.0, 0.0), 0.5));
+    }
+}
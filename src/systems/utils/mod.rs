@@ -1,5 +1,19 @@
 mod color;
+mod frustum;
+#[cfg(feature = "rng")]
+mod rng;
+mod shader;
+#[cfg(all(feature = "hot-reload", not(target_arch = "wasm32")))]
+mod shader_watcher;
+mod texture;
 mod transform;
 
 pub use color::{ColorError, RgbColor};
+pub use frustum::Frustum;
+#[cfg(feature = "rng")]
+pub use rng::Rng;
+pub use shader::{capture_validation_error, create_shader_module, ShaderError};
+#[cfg(all(feature = "hot-reload", not(target_arch = "wasm32")))]
+pub use shader_watcher::ShaderWatcher;
+pub use texture::{Texture, TextureData, TextureError};
 pub use transform::Transform;
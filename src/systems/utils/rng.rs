@@ -0,0 +1,49 @@
+use std::ops::Range;
+
+use glam::*;
+use rand::{Rng as _, SeedableRng};
+
+/// Deterministic random number generator.
+///
+/// The same seed always produces the same sequence on every platform, so
+/// generated layouts are reproducible, e.g. for golden images rendered with
+/// the headless display.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    seed: u64,
+    inner: rand_pcg::Pcg32,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            inner: rand_pcg::Pcg32::seed_from_u64(seed),
+        }
+    }
+
+    /// The seed the generator was created with.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// A uniform value in `0.0..1.0`.
+    pub fn unit(&mut self) -> f32 {
+        self.inner.gen()
+    }
+
+    /// A uniform value in the range, `range.start` if it is empty.
+    pub fn range(&mut self, range: Range<f32>) -> f32 {
+        match range.is_empty() {
+            true => range.start,
+            false => self.inner.gen_range(range),
+        }
+    }
+
+    /// A uniform point in the disk of the radius.
+    pub fn in_disk(&mut self, radius: f32) -> Vec2 {
+        let angle = self.unit() * std::f32::consts::TAU;
+        let distance = self.unit().sqrt() * radius;
+        Vec2::from_angle(angle) * distance
+    }
+}
@@ -0,0 +1,92 @@
+use futures::FutureExt;
+use thiserror::Error;
+
+/// Create a shader module from WGSL, returning the compilation error instead of
+/// panicking in the uncaptured error handler of the device.
+///
+/// The errors are only known synchronously on native, on the web they are
+/// logged once known and this always succeeds, see
+/// [`capture_validation_error`].
+pub fn create_shader_module(
+    device: &wgpu::Device,
+    name: &str,
+    source: &str,
+) -> Result<wgpu::ShaderModule, ShaderError> {
+    let (module, error) = capture_validation_error(device, || {
+        device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some(name),
+            source: wgpu::ShaderSource::Wgsl(source.into()),
+        })
+    });
+
+    // The compilation messages have the location of the error
+    if let Some(message) = module
+        .get_compilation_info()
+        .now_or_never()
+        .and_then(|info| {
+            info.messages
+                .into_iter()
+                .find(|message| matches!(message.message_type, wgpu::CompilationMessageType::Error))
+        })
+    {
+        let (line, column) = message
+            .location
+            .map(|location| (location.line_number, location.line_position))
+            .unwrap_or_default();
+
+        return Err(ShaderError::Compilation {
+            name: name.to_string(),
+            line,
+            column,
+            message: message.message,
+        });
+    }
+
+    match error {
+        Some(e) => Err(ShaderError::Validation {
+            name: name.to_string(),
+            message: e.to_string(),
+        }),
+        None => Ok(module),
+    }
+}
+
+/// Run `f` in a validation error scope, returning the error it caused.
+///
+/// On the web, the error scope resolves asynchronously, so the error is logged
+/// once known and [`None`] is returned.
+pub fn capture_validation_error<T>(
+    device: &wgpu::Device,
+    f: impl FnOnce() -> T,
+) -> (T, Option<wgpu::Error>) {
+    device.push_error_scope(wgpu::ErrorFilter::Validation);
+    let value = f();
+    let error = device.pop_error_scope();
+
+    cfg_if::cfg_if! {
+        if #[cfg(target_arch = "wasm32")] {
+            wasm_bindgen_futures::spawn_local(async move {
+                if let Some(e) = error.await {
+                    log::error!("Validation error: {e}");
+                }
+            });
+            (value, None)
+        } else {
+            (value, error.now_or_never().flatten())
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ShaderError {
+    #[error("shader {name} failed to compile at {line}:{column}: {message}")]
+    Compilation {
+        name: String,
+        line: u32,
+        column: u32,
+        message: String,
+    },
+
+    #[error("shader {name} is invalid: {message}")]
+    Validation { name: String, message: String },
+}
@@ -0,0 +1,66 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::mpsc,
+};
+
+use notify::Watcher;
+
+/// Watcher of a WGSL file on disk for hot reloading.
+///
+/// The parent directory is watched instead of the file, so the file is still
+/// tracked when an editor saves by replacing it.
+pub struct ShaderWatcher {
+    path: PathBuf,
+    rx: mpsc::Receiver<notify::Result<notify::Event>>,
+    _watcher: notify::RecommendedWatcher,
+}
+
+impl ShaderWatcher {
+    pub fn new(path: impl Into<PathBuf>) -> notify::Result<Self> {
+        let path = path.into();
+        let (tx, rx) = mpsc::channel();
+
+        let mut watcher = notify::recommended_watcher(tx)?;
+        watcher.watch(
+            path.parent().unwrap_or(Path::new(".")),
+            notify::RecursiveMode::NonRecursive,
+        )?;
+
+        log::debug!("Watching shader {}", path.display());
+
+        Ok(Self {
+            path,
+            rx,
+            _watcher: watcher,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The new source of the shader if it changed since the last poll.
+    pub fn poll(&self) -> Option<String> {
+        // Drain all the events so a save only reloads once
+        let is_changed = self
+            .rx
+            .try_iter()
+            .filter_map(|event| {
+                event
+                    .inspect_err(|e| log::warn!("Failed to watch shader: {e}"))
+                    .ok()
+            })
+            .filter(|event| event.kind.is_create() || event.kind.is_modify())
+            .fold(false, |is_changed, event| {
+                is_changed || event.paths.iter().any(|path| path.ends_with(&self.path))
+            });
+
+        if !is_changed {
+            return None;
+        }
+
+        std::fs::read_to_string(&self.path)
+            .inspect_err(|e| log::warn!("Failed to read shader {}: {e}", self.path.display()))
+            .ok()
+    }
+}
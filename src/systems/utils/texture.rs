@@ -0,0 +1,346 @@
+use thiserror::Error;
+
+/// RGBA8 pixels of a texture on the CPU.
+#[derive(Debug, Clone)]
+pub struct TextureData {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+impl TextureData {
+    pub fn new(width: u32, height: u32, pixels: Vec<u8>) -> Result<Self, TextureError> {
+        let expected = width as usize * height as usize * 4;
+
+        match (width, height, pixels.len()) {
+            (0, _, _) | (_, 0, _) => Err(TextureError::EmptySize(width, height)),
+            (_, _, len) if len != expected => Err(TextureError::InvalidPixelCount(len, expected)),
+            _ => Ok(Self {
+                width,
+                height,
+                pixels,
+            }),
+        }
+    }
+
+    /// A single white pixel, used when no texture is provided.
+    pub fn white() -> Self {
+        Self {
+            width: 1,
+            height: 1,
+            pixels: vec![255; 4],
+        }
+    }
+
+    /// Decode PNG or JPEG bytes, the size does not need to be a power of two.
+    #[cfg(feature = "image")]
+    pub fn from_image_bytes(bytes: &[u8]) -> Result<Self, TextureError> {
+        let image = image::load_from_memory(bytes)?.to_rgba8();
+        let (width, height) = image.dimensions();
+
+        Self::new(width, height, image.into_raw())
+    }
+}
+
+/// A texture on the GPU with its view and sampler.
+#[derive(Debug)]
+pub struct Texture {
+    pub texture: wgpu::Texture,
+    pub view: wgpu::TextureView,
+    pub sampler: wgpu::Sampler,
+}
+
+impl Texture {
+    pub const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+
+    /// The highest anisotropic filtering level supported by wgpu.
+    pub const MAX_ANISOTROPY: u16 = 16;
+
+    /// Create the texture and write the data to mip level 0.
+    ///
+    /// If `mipmaps` is true, the full mip chain is generated from level 0.
+    /// `anisotropy` is the anisotropic filtering level of the sampler, 1 to
+    /// disable it, see [`Texture::clamp_anisotropy`].
+    pub fn new(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        data: &TextureData,
+        mipmaps: bool,
+        anisotropy: u16,
+        label: Option<&str>,
+    ) -> Self {
+        let anisotropy = Self::clamp_anisotropy(anisotropy, Self::MAX_ANISOTROPY);
+
+        let size = wgpu::Extent3d {
+            width: data.width,
+            height: data.height,
+            depth_or_array_layers: 1,
+        };
+
+        let mip_level_count = match mipmaps {
+            true => size.max_mips(wgpu::TextureDimension::D2),
+            false => 1,
+        };
+
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label,
+            size,
+            mip_level_count,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: Self::FORMAT,
+            usage: match mip_level_count {
+                1 => wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+                _ => {
+                    wgpu::TextureUsages::TEXTURE_BINDING
+                        | wgpu::TextureUsages::COPY_DST
+                        | wgpu::TextureUsages::RENDER_ATTACHMENT
+                }
+            },
+            view_formats: &[],
+        });
+
+        queue.write_texture(
+            texture.as_image_copy(),
+            &data.pixels,
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(data.width * 4),
+                rows_per_image: Some(data.height),
+            },
+            size,
+        );
+
+        if mip_level_count > 1 {
+            Self::generate_mipmaps(device, queue, &texture);
+        }
+
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let sampler = device.create_sampler(&Self::sampler_descriptor(
+            label,
+            mip_level_count,
+            anisotropy,
+        ));
+
+        Self {
+            texture,
+            view,
+            sampler,
+        }
+    }
+
+    /// Clamp the anisotropic filtering level to a power of two from 1 to
+    /// `max_anisotropy`, e.g. [`Display::max_anisotropy`], logging if it
+    /// changes.
+    ///
+    /// [`Display::max_anisotropy`]: crate::systems::handlers::Display::max_anisotropy
+    pub fn clamp_anisotropy(anisotropy: u16, max_anisotropy: u16) -> u16 {
+        let max_anisotropy = max_anisotropy.clamp(1, Self::MAX_ANISOTROPY);
+        let clamped = match anisotropy {
+            0 => 1,
+            _ => (1 << anisotropy.ilog2()).min(max_anisotropy),
+        };
+
+        if clamped != anisotropy {
+            log::warn!(
+                "Anisotropy {anisotropy} is not supported, up to {max_anisotropy}, clamping to {clamped}"
+            );
+        }
+
+        clamped
+    }
+
+    pub fn mip_level_count(&self) -> u32 {
+        self.texture.mip_level_count()
+    }
+
+    /// The sampler of a texture with `mip_level_count` levels.
+    fn sampler_descriptor(
+        label: Option<&str>,
+        mip_level_count: u32,
+        anisotropy: u16,
+    ) -> wgpu::SamplerDescriptor<'_> {
+        // Trilinear filtering across the mip chain, which anisotropic filtering
+        // requires
+        wgpu::SamplerDescriptor {
+            label,
+            address_mode_u: wgpu::AddressMode::Repeat,
+            address_mode_v: wgpu::AddressMode::Repeat,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Linear,
+            lod_min_clamp: 0.0,
+            lod_max_clamp: mip_level_count as f32,
+            anisotropy_clamp: anisotropy,
+            ..Default::default()
+        }
+    }
+
+    /// Generate the mip chain by blitting each level into the next.
+    fn generate_mipmaps(device: &wgpu::Device, queue: &wgpu::Queue, texture: &wgpu::Texture) {
+        log::debug!("Generating {} mip levels", texture.mip_level_count() - 1);
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Mipmap Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("../shaders/blit.wgsl").into()),
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Mipmap Render Pipeline"),
+            layout: None,
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vertex_main"),
+                buffers: &[],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fragment_main"),
+                targets: &[Some(texture.format().into())],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        let bind_group_layout = pipeline.get_bind_group_layout(0);
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Mipmap Sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        let views = (0..texture.mip_level_count())
+            .map(|mip_level| {
+                texture.create_view(&wgpu::TextureViewDescriptor {
+                    label: Some("Mipmap Texture View"),
+                    base_mip_level: mip_level,
+                    mip_level_count: Some(1),
+                    ..Default::default()
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Mipmap Encoder"),
+        });
+
+        for (source, target) in views.iter().zip(views.iter().skip(1)) {
+            let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Mipmap Bind Group"),
+                layout: &bind_group_layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(source),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::Sampler(&sampler),
+                    },
+                ],
+            });
+
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Mipmap Render Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: target,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                occlusion_query_set: None,
+                timestamp_writes: None,
+            });
+
+            render_pass.set_pipeline(&pipeline);
+            render_pass.set_bind_group(0, &bind_group, &[]);
+            render_pass.draw(0..3, 0..1);
+        }
+
+        queue.submit(std::iter::once(encoder.finish()));
+    }
+
+    /// The bind group layout entries of the texture view at `binding` and the
+    /// sampler at `binding + 1`.
+    pub fn bind_group_layout_entries(
+        binding: u32,
+        visibility: wgpu::ShaderStages,
+    ) -> [wgpu::BindGroupLayoutEntry; 2] {
+        [
+            wgpu::BindGroupLayoutEntry {
+                binding,
+                visibility,
+                ty: wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                    multisampled: false,
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: binding + 1,
+                visibility,
+                ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                count: None,
+            },
+        ]
+    }
+
+    /// The bind group entries matching [`Texture::bind_group_layout_entries`].
+    pub fn bind_group_entries(&self, binding: u32) -> [wgpu::BindGroupEntry; 2] {
+        [
+            wgpu::BindGroupEntry {
+                binding,
+                resource: wgpu::BindingResource::TextureView(&self.view),
+            },
+            wgpu::BindGroupEntry {
+                binding: binding + 1,
+                resource: wgpu::BindingResource::Sampler(&self.sampler),
+            },
+        ]
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum TextureError {
+    #[cfg(feature = "image")]
+    #[error("failed to decode image: {0}")]
+    Image(#[from] image::ImageError),
+    #[error("texture size is empty: {0}x{1}")]
+    EmptySize(u32, u32),
+    #[error("texture pixel bytes do not match size: {0} != {1}")]
+    InvalidPixelCount(usize, usize),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::systems::handlers;
+
+    #[test]
+    fn mipmaps_set_mip_level_count() {
+        let Some((device, queue)) = handlers::test_device() else {
+            return;
+        };
+        let data = TextureData::new(256, 64, vec![255; 256 * 64 * 4]).unwrap();
+
+        for (mipmaps, expected) in [(true, 9), (false, 1)] {
+            let texture = Texture::new(&device, &queue, &data, mipmaps, 1, None);
+            assert_eq!(texture.mip_level_count(), expected);
+
+            let sampler = Texture::sampler_descriptor(None, texture.mip_level_count(), 1);
+            assert_eq!(sampler.lod_max_clamp, expected as f32);
+        }
+    }
+}
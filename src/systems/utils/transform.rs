@@ -1,6 +1,11 @@
 use glam::*;
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct Transform {
     pub position: Vec3,
     pub rotation: Quat,
@@ -22,6 +27,14 @@ impl Transform {
         }
     }
 
+    /// Decompose an affine matrix into the translation, rotation, and scale.
+    ///
+    /// Shear and perspective cannot be represented and are lost.
+    pub fn from_matrix(matrix: Mat4) -> Self {
+        let (scale, rotation, position) = matrix.to_scale_rotation_translation();
+        Self::new(position, rotation, scale)
+    }
+
     pub fn matrix(&self) -> Mat4 {
         Mat4::from_scale_rotation_translation(self.scale, self.rotation, self.position)
     }
@@ -38,3 +51,15 @@ impl Transform {
         self.scale *= scale;
     }
 }
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl From<Mat4> for Transform {
+    fn from(matrix: Mat4) -> Self {
+        Self::from_matrix(matrix)
+    }
+}
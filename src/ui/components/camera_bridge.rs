@@ -0,0 +1,70 @@
+//! Bridge between Leptos signals and the engine's camera.
+//!
+//! Each binding reads a field of [`EngineController::camera_model`], which the
+//! engine keeps in sync through [`systems::Signal::CameraMoved`], and writing
+//! to it sends [`systems::Signal::CameraUpdate`] back to the engine, so UI
+//! sliders can be bound to the camera directly.
+
+use glam::*;
+use leptos::*;
+
+use crate::{
+    systems::{self, handlers::CameraModel},
+    ui::components::engine::EngineController,
+};
+
+/// The position of the camera.
+pub fn camera_position(controller: EngineController) -> (Signal<Vec3>, SignalSetter<Vec3>) {
+    camera_binding(
+        controller,
+        |model| model.position,
+        |model, position| model.position = position,
+    )
+}
+
+/// The vertical field of view in radians, [`None`] for an orthographic
+/// projection, in which case setting it does nothing.
+pub fn camera_vertical_fov(
+    controller: EngineController,
+) -> (Signal<Option<f32>>, SignalSetter<Option<f32>>) {
+    camera_binding(
+        controller,
+        |model| match model.projection {
+            systems::handlers::ProjectionKind::Perspective { vertical_fov } => Some(vertical_fov),
+            _ => None,
+        },
+        |model, fov| {
+            if let (systems::handlers::ProjectionKind::Perspective { vertical_fov }, Some(fov)) =
+                (&mut model.projection, fov)
+            {
+                *vertical_fov = fov;
+            }
+        },
+    )
+}
+
+/// The maximum movement speed of the camera.
+pub fn camera_speed(controller: EngineController) -> (Signal<f32>, SignalSetter<f32>) {
+    camera_binding(
+        controller,
+        |model| model.speed,
+        |model, speed| model.speed = speed,
+    )
+}
+
+/// Bind a field of the camera model, sending the model to the engine when set.
+pub fn camera_binding<T: Clone + PartialEq + 'static>(
+    controller: EngineController,
+    get: fn(&CameraModel) -> T,
+    set: fn(&mut CameraModel, T),
+) -> (Signal<T>, SignalSetter<T>) {
+    let model = controller.camera_model();
+
+    let getter = create_memo(move |_| model.with(get)).into();
+    let setter = SignalSetter::map(move |value| {
+        model.update(|model| set(model, value));
+        controller.signal_camera_update();
+    });
+
+    (getter, setter)
+}
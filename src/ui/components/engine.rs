@@ -1,8 +1,8 @@
 use leptos::*;
-use leptos_use::{use_element_bounding, use_interval_fn};
+use leptos_use::use_interval_fn;
 
 use crate::{
-    systems,
+    engine, systems,
     ui::components::{
         engine_canvas::{EngineRx, EngineTx},
         EngineCanvas,
@@ -16,7 +16,7 @@ pub fn Engine(
     #[prop(default = "".to_string(), into)] style: String,
 ) -> impl IntoView {
     let container_node = create_node_ref::<html::Div>();
-    let container_size = use_element_bounding(container_node);
+    let container_size = create_rw_signal(None::<(u32, u32)>);
 
     let controller = controller.unwrap_or_default();
 
@@ -28,34 +28,79 @@ pub fn Engine(
         systems::Signal::PyramidModelUpdate(signal) => {
             controller.pyramid_model.set(signal.model);
         }
+        // Throttled by the engine, so the bindings follow the camera as it flies.
+        systems::Signal::CameraMoved(signal) => {
+            controller.camera_model.set(signal.model);
+        }
+        systems::Signal::Picked(signal) => {
+            controller.picked.set(signal.object);
+        }
+        systems::Signal::InitFailed(signal) => {
+            controller.init_error.set(Some(signal.message));
+            controller.running.set(false);
+        }
+        // Captured frames are not saved yet.
+        systems::Signal::FrameCaptured(signal) => {
+            log::debug!(
+                "Frame captured with size {}x{}",
+                signal.frame.width,
+                signal.frame.height
+            );
+        }
+        // Only the culled instances are displayed from the stats.
+        systems::Signal::Stats(signal) => {
+            controller
+                .visible_instances
+                .set(Some(signal.visible_instances));
+        }
+        // Load progress is sent every frame while loading but not displayed yet.
+        systems::Signal::LoadProgress(_) => {}
         _ => log::warn!("Unhandled signal: {signal:?}"),
     });
 
+    // Clear the error of the last failed start.
+    create_effect(move |_| {
+        if controller.running.get() {
+            controller.init_error.set(None);
+        }
+    });
+
+    // Observe the container size in physical pixels.
+    let resize_observer = store_value(None::<web_sys::ResizeObserver>);
+    container_node.on_load(move |container_node| {
+        let observer = engine::utils::observe_resize(&container_node, move |width, height| {
+            container_size.set(Some((width, height)));
+        });
+        resize_observer.set_value(Some(observer));
+    });
+    on_cleanup(move || {
+        if let Some(observer) = resize_observer.get_value() {
+            observer.disconnect();
+        }
+    });
+
     // Keep the engine same size as the container.
     create_effect(move |_| {
         controller.running.get();
-        container_size.width.get();
-        container_size.height.get();
 
-        // We can only resize the engine if the container node and engine tx are available.
-        let (container_node, tx) = match (container_node.get(), controller.tx().get()) {
-            (Some(container_node), Some(tx)) => (container_node, tx),
+        // We can only resize the engine if the container size and engine tx are available.
+        let ((width, height), tx) = match (container_size.get(), controller.tx().get()) {
+            (Some(size), Some(tx)) => (size, tx),
             _ => return,
         };
 
-        let container = container_node.get_bounding_client_rect();
-        let width = container.width();
-        let height = container.height();
-
-        tx.send(systems::ResizeSignal::in_signal(width, height))
-            .unwrap();
+        tx.send(systems::ResizeSignal::in_signal(
+            width as f64,
+            height as f64,
+        ))
+        .unwrap();
     });
 
     view! {
         <div ref=container_node style=format!("overflow: hidden; {style}")>
             <Show
                 when=move || controller.running().get()
-                fallback=|| view! {
+                fallback=move || view! {
                     <div style="\
                         display: flex; \
                         flex-direction: column; \
@@ -68,7 +113,10 @@ pub fn Engine(
                             maxWidth: min(100%, 400px); \
                             textAlign: center; \
                         ">
-                            "Click 'Start Engine' to see the output."
+                            {move || match controller.init_error().get() {
+                                Some(e) => format!("Engine failed to start: {e}"),
+                                None => "Click 'Start Engine' to see the output.".to_string(),
+                            }}
                         </h4>
                     </div>
                 }
@@ -77,7 +125,7 @@ pub fn Engine(
                     system_pipeline_args=move || systems::Args {
                         pyramid_transform: controller.pyramid_transform().get(),
                         pyramid_model: controller.pyramid_model().get(),
-                        ..Default::default()
+                        ..controller.args().get()
                     }
                     tx=controller.tx().split()
                     rx=controller.rx().split()
@@ -91,10 +139,15 @@ pub fn Engine(
 #[derive(Debug, Clone, Copy)]
 pub struct EngineController {
     running: RwSignal<bool>,
+    init_error: RwSignal<Option<String>>,
     tx: RwSignal<EngineTx>,
     rx: RwSignal<EngineRx>,
+    args: RwSignal<systems::Args>,
     pyramid_transform: RwSignal<systems::handlers::PyramidTransform>,
     pyramid_model: RwSignal<systems::handlers::PyramidModel>,
+    camera_model: RwSignal<systems::handlers::CameraModel>,
+    picked: RwSignal<Option<systems::handlers::PickedObject>>,
+    visible_instances: RwSignal<Option<usize>>,
 }
 
 impl EngineController {
@@ -102,6 +155,11 @@ impl EngineController {
         self.running
     }
 
+    /// The error of the last failed start, cleared when started again.
+    pub fn init_error(&self) -> RwSignal<Option<String>> {
+        self.init_error
+    }
+
     pub fn tx(&self) -> RwSignal<EngineTx> {
         self.tx
     }
@@ -110,6 +168,15 @@ impl EngineController {
         self.rx
     }
 
+    /// The args to start the engine with, from the URL query with the `serde`
+    /// feature.
+    ///
+    /// The pyramid fields are overridden by [`EngineController::pyramid_transform`]
+    /// and [`EngineController::pyramid_model`], which start from these args.
+    pub fn args(&self) -> RwSignal<systems::Args> {
+        self.args
+    }
+
     pub fn pyramid_transform(&self) -> RwSignal<systems::handlers::PyramidTransform> {
         self.pyramid_transform
     }
@@ -129,6 +196,34 @@ impl EngineController {
     pub fn pyramid_model(&self) -> RwSignal<systems::handlers::PyramidModel> {
         self.pyramid_model
     }
+
+    /// The camera model, kept in sync with the engine's camera.
+    pub fn camera_model(&self) -> RwSignal<systems::handlers::CameraModel> {
+        self.camera_model
+    }
+
+    /// The object picked by the last click, [`None`] if it hit the background.
+    pub fn picked(&self) -> RwSignal<Option<systems::handlers::PickedObject>> {
+        self.picked
+    }
+
+    /// The number of pyramid instances drawn after culling, from the last
+    /// [`systems::Signal::Stats`], [`None`] before the first one.
+    pub fn visible_instances(&self) -> RwSignal<Option<usize>> {
+        self.visible_instances
+    }
+
+    pub fn signal_camera_update(&self) {
+        self.tx().with(|tx| match tx {
+            Some(tx) => {
+                tx.send(systems::CameraUpdateSignal::in_signal(
+                    self.camera_model().get(),
+                ))
+                .unwrap();
+            }
+            None => log::debug!("Engine has not started, skipping signal camera"),
+        });
+    }
 }
 
 impl EngineController {
@@ -152,22 +247,50 @@ impl EngineController {
                 * 1e3) as u64,
         );
     }
+
+    /// The args from the URL query, or the defaults if there is none or it is
+    /// invalid.
+    fn query_args() -> systems::Args {
+        #[cfg(feature = "serde")]
+        {
+            let query = window().location().search().unwrap_or_default();
+            if !query.is_empty() {
+                match systems::Args::from_query(&query) {
+                    Ok(args) => return args,
+                    Err(e) => log::warn!("Failed to parse args from URL query: {e}"),
+                }
+            }
+        }
+
+        systems::Args::default()
+    }
 }
 
 impl Default for EngineController {
     fn default() -> Self {
         let running = create_rw_signal(false);
+        let init_error = create_rw_signal(None);
         let tx = create_rw_signal(None);
         let rx = create_rw_signal(None);
-        let pyramid_transform = create_rw_signal(systems::handlers::PyramidTransform::default());
-        let pyramid_model = create_rw_signal(systems::handlers::PyramidModel::default());
+        let args = Self::query_args();
+        let pyramid_transform = create_rw_signal(args.pyramid_transform.clone());
+        let pyramid_model = create_rw_signal(args.pyramid_model.clone());
+        let args = create_rw_signal(args);
+        let camera_model = create_rw_signal(systems::handlers::CameraModel::default());
+        let picked = create_rw_signal(None);
+        let visible_instances = create_rw_signal(None);
 
         Self {
             running,
+            init_error,
             tx,
             rx,
+            args,
             pyramid_transform,
             pyramid_model,
+            camera_model,
+            picked,
+            visible_instances,
         }
     }
 }
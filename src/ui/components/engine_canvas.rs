@@ -75,6 +75,7 @@ pub fn EngineCanvas(
                     .with_window_attributes(window_attributes)
                     .with_rx(rx)
                     .with_tx(tx)
+                    .with_batching_signals(true)
                     .with_system_pipeline::<systems::Pipeline>(system_pipeline_args)
                     .run()
                     .unwrap();
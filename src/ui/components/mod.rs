@@ -1,3 +1,4 @@
+pub mod camera_bridge;
 pub mod engine;
 pub mod engine_canvas;
 pub mod footer;
@@ -1,6 +1,8 @@
 use leptos::*;
 
-use crate::ui::components::{engine::EngineController, PyramidTransformConfiguration};
+use crate::ui::components::{
+    camera_bridge, engine::EngineController, PyramidTransformConfiguration,
+};
 
 const INSTRUCTIONS: &[&str] = &[
     "Click the button to start or stop the engine.",
@@ -8,7 +10,7 @@ const INSTRUCTIONS: &[&str] = &[
     "Move the mouse to look around when the cursor is locked.",
     "Use the W, A, S, D, Space, Shift keys to move around when the cursor is locked.",
     "Change the configurations to see the changes in real-time.",
-    "Press the Escape key or the Tab key to unlock the cursor.",
+    "Press the Escape key to unlock the cursor.",
 ];
 
 #[component]
@@ -16,6 +18,8 @@ pub fn SidePanel(
     #[prop(into)] controller: EngineController,
     #[prop(default = "".to_string(), into)] style: String,
 ) -> impl IntoView {
+    let (camera_speed, set_camera_speed) = camera_bridge::camera_speed(controller);
+
     view! {
         <div style=format!("\
             display: flex; \
@@ -38,6 +42,34 @@ pub fn SidePanel(
             <div style="margin-bottom: 16px;" />
             <h3 style="margin-top: 0;">"Configurations"</h3>
             <PyramidTransformConfiguration controller=controller />
+            <Show when=move || controller.visible_instances().get().is_some()>
+                <div style="margin-top: 16px;">
+                    {move || format!(
+                        "Visible instances: {}",
+                        controller.visible_instances().get().unwrap_or_default(),
+                    )}
+                </div>
+            </Show>
+            <div style="margin-bottom: 16px;" />
+            <h4 style="margin-top: 0; margin-bottom: 16px;">"Camera"</h4>
+            <div style="display: flex; flex-direction: column; gap: 8px;">
+                <label for="camera-speed">
+                    {move || format!("Speed: {:.2}", camera_speed.get())}
+                </label>
+                <input
+                    id="camera-speed"
+                    type="range"
+                    min=move || controller.camera_model().with(|model| model.min_speed)
+                    max=move || controller.camera_model().with(|model| model.max_speed)
+                    step=0.1
+                    prop:value=move || camera_speed.get()
+                    on:input=move |event| {
+                        if let Ok(speed) = event_target_value(&event).parse::<f32>() {
+                            set_camera_speed.set(speed);
+                        }
+                    }
+                />
+            </div>
             <div style="margin-bottom: 16px;" />
             <h3 style="margin-top: 0;">"Instructions"</h3>
             <ul style="margin-top: 0;">